@@ -9,7 +9,7 @@ use std::{
 };
 
 use chewing::{
-    conversion::{ChewingEngine, FuzzyChewingEngine, Interval, SimpleEngine, Symbol},
+    conversion::{ChewingEngine, FuzzyChewingEngine, Interval, SimpleEngine},
     dictionary::{
         Dictionary, Layered, LookupStrategy, SystemDictionaryLoader, Trie, UserDictionaryLoader,
     },
@@ -17,10 +17,7 @@ use chewing::{
         AbbrevTable, BasicEditor, CharacterForm, ConversionEngineKind, Editor, EditorKeyBehavior,
         LanguageMode, LaxUserFreqEstimate, SymbolSelector, UserPhraseAddDirection,
         keyboard::{AnyKeyboardLayout, KeyCode, KeyboardLayout, Modifiers, Qwerty},
-        zhuyin_layout::{
-            DaiChien26, Et, Et26, GinYieh, Hsu, Ibm, KeyboardLayoutCompat, Pinyin, Standard,
-            SyllableEditor,
-        },
+        zhuyin_layout::KeyboardLayoutCompat,
     },
     zhuyin::Syllable,
 };
@@ -536,37 +533,12 @@ pub unsafe extern "C" fn chewing_config_set_str(
 
     match name.as_ref() {
         "chewing.keyboard_type" => {
-            use KeyboardLayoutCompat as KB;
             ctx.kb_compat = match string.parse() {
                 Ok(kbtype) => kbtype,
                 Err(_) => return ERROR,
             };
-            let (keyboard, syl): (AnyKeyboardLayout, Box<dyn SyllableEditor>) = match ctx.kb_compat
-            {
-                KB::Default => (AnyKeyboardLayout::qwerty(), Box::new(Standard::new())),
-                KB::Hsu => (AnyKeyboardLayout::qwerty(), Box::new(Hsu::new())),
-                KB::Ibm => (AnyKeyboardLayout::qwerty(), Box::new(Ibm::new())),
-                KB::GinYieh => (AnyKeyboardLayout::qwerty(), Box::new(GinYieh::new())),
-                KB::Et => (AnyKeyboardLayout::qwerty(), Box::new(Et::new())),
-                KB::Et26 => (AnyKeyboardLayout::qwerty(), Box::new(Et26::new())),
-                KB::Dvorak => (AnyKeyboardLayout::qwerty(), Box::new(Standard::new())),
-                KB::DvorakHsu => (AnyKeyboardLayout::qwerty(), Box::new(Hsu::new())),
-                KB::DachenCp26 => (AnyKeyboardLayout::qwerty(), Box::new(DaiChien26::new())),
-                KB::HanyuPinyin => (AnyKeyboardLayout::qwerty(), Box::new(Pinyin::hanyu())),
-                KB::ThlPinyin => (AnyKeyboardLayout::qwerty(), Box::new(Pinyin::thl())),
-                KB::Mps2Pinyin => (AnyKeyboardLayout::qwerty(), Box::new(Pinyin::mps2())),
-                KB::Carpalx => (AnyKeyboardLayout::qwerty(), Box::new(Standard::new())),
-                KB::Colemak => (AnyKeyboardLayout::colemak(), Box::new(Standard::new())),
-                KB::ColemakDhAnsi => (
-                    AnyKeyboardLayout::colemak_dh_ansi(),
-                    Box::new(Standard::new()),
-                ),
-                KB::ColemakDhOrth => (
-                    AnyKeyboardLayout::colemak_dh_orth(),
-                    Box::new(Standard::new()),
-                ),
-                KB::Workman => (AnyKeyboardLayout::workman(), Box::new(Standard::new())),
-            };
+            let keyboard = ctx.kb_compat.keyboard();
+            let syl = ctx.kb_compat.syllable_editor();
             ctx.keyboard = keyboard;
             ctx.editor.set_syllable_editor(syl);
         }
@@ -598,31 +570,8 @@ pub unsafe extern "C" fn chewing_set_KBType(ctx: *mut ChewingContext, kbtype: c_
         Ok(kb) => kb,
         Err(()) => KB::Default,
     };
-    let (keyboard, syl): (AnyKeyboardLayout, Box<dyn SyllableEditor>) = match kb_compat {
-        KB::Default => (AnyKeyboardLayout::qwerty(), Box::new(Standard::new())),
-        KB::Hsu => (AnyKeyboardLayout::qwerty(), Box::new(Hsu::new())),
-        KB::Ibm => (AnyKeyboardLayout::qwerty(), Box::new(Ibm::new())),
-        KB::GinYieh => (AnyKeyboardLayout::qwerty(), Box::new(GinYieh::new())),
-        KB::Et => (AnyKeyboardLayout::qwerty(), Box::new(Et::new())),
-        KB::Et26 => (AnyKeyboardLayout::qwerty(), Box::new(Et26::new())),
-        KB::Dvorak => (AnyKeyboardLayout::dvorak(), Box::new(Standard::new())),
-        KB::DvorakHsu => (AnyKeyboardLayout::dvorak_on_qwerty(), Box::new(Hsu::new())),
-        KB::DachenCp26 => (AnyKeyboardLayout::qwerty(), Box::new(DaiChien26::new())),
-        KB::HanyuPinyin => (AnyKeyboardLayout::qwerty(), Box::new(Pinyin::hanyu())),
-        KB::ThlPinyin => (AnyKeyboardLayout::qwerty(), Box::new(Pinyin::thl())),
-        KB::Mps2Pinyin => (AnyKeyboardLayout::qwerty(), Box::new(Pinyin::mps2())),
-        KB::Carpalx => (AnyKeyboardLayout::qwerty(), Box::new(Standard::new())),
-        KB::Colemak => (AnyKeyboardLayout::colemak(), Box::new(Standard::new())),
-        KB::ColemakDhAnsi => (
-            AnyKeyboardLayout::colemak_dh_ansi(),
-            Box::new(Standard::new()),
-        ),
-        KB::ColemakDhOrth => (
-            AnyKeyboardLayout::colemak_dh_orth(),
-            Box::new(Standard::new()),
-        ),
-        KB::Workman => (AnyKeyboardLayout::workman(), Box::new(Standard::new())),
-    };
+    let keyboard = kb_compat.keyboard();
+    let syl = kb_compat.syllable_editor();
     ctx.kb_compat = kb_compat;
     ctx.keyboard = keyboard;
     ctx.editor.set_syllable_editor(syl);
@@ -903,9 +852,8 @@ pub unsafe extern "C" fn chewing_get_phoneSeq(ctx: *const ChewingContext) -> *mu
         .editor
         .symbols()
         .iter()
-        .cloned()
-        .filter(Symbol::is_syllable)
-        .map(|sym| sym.to_syllable().unwrap().to_u16())
+        .filter_map(|sym| sym.to_syllable())
+        .map(|syl| syl.to_u16())
         .collect();
     let len = syllables.len();
     let ptr = Box::into_raw(syllables.into_boxed_slice());
@@ -922,11 +870,44 @@ pub unsafe extern "C" fn chewing_get_phoneSeqLen(ctx: *const ChewingContext) ->
     ctx.editor
         .symbols()
         .iter()
-        .cloned()
-        .filter(Symbol::is_syllable)
+        .filter(|sym| sym.is_syllable())
         .count() as c_int
 }
 
+/// # Safety
+///
+/// This function should be called with valid pointers.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chewing_get_phoneSeqUnits(ctx: *const ChewingContext) -> *mut c_ushort {
+    let ctx = as_ref_or_return!(ctx, null_mut());
+
+    let units: Vec<_> = ctx
+        .editor
+        .symbols()
+        .iter()
+        .filter_map(|sym| sym.to_syllable())
+        .flat_map(|syl| syl.encode_u16(&mut [0; 2]).to_vec())
+        .collect();
+    let len = units.len();
+    let ptr = Box::into_raw(units.into_boxed_slice());
+    owned_into_raw(Owned::CUShortSlice(len), ptr.cast())
+}
+
+/// # Safety
+///
+/// This function should be called with valid pointers.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn chewing_get_phoneSeqUnitsLen(ctx: *const ChewingContext) -> c_int {
+    let ctx = as_ref_or_return!(ctx, ERROR);
+
+    ctx.editor
+        .symbols()
+        .iter()
+        .filter_map(|sym| sym.to_syllable())
+        .map(|syl| syl.len_u16())
+        .sum::<usize>() as c_int
+}
+
 /// # Safety
 ///
 /// This function should be called with valid pointers.
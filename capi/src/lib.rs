@@ -322,6 +322,11 @@ pub mod layout {
     /// * KB_COLEMAK_DH_ANSI
     /// * KB_COLEMAK_DH_ORTH
     /// * KB_WORKMAN
+    /// * KB_MS_DOUBLE_PINYIN
+    /// * KB_ZIRANMA_DOUBLE_PINYIN
+    /// * KB_XIAOHE_DOUBLE_PINYIN
+    /// * KB_KEYPAD
+    /// * KB_FANGYIN
     ///
     /// See also [chewing_kbtype_Enumerate] for getting the list of supported
     /// layouts programmatically.
@@ -800,6 +805,19 @@ pub mod output {
     /// state machine.
     pub use super::io::chewing_get_phoneSeqLen;
 
+    /// Returns the phonetic sequence in the Chewing IM internal state machine
+    /// as u16 code units.
+    ///
+    /// Mandarin syllables take one unit, the same value as in
+    /// [chewing_get_phoneSeq]. Taiwanese Hokkien and Hakka syllables take a
+    /// leading and a trailing unit. The memory must be freed by the caller
+    /// using function [chewing_free][super::setup::chewing_free].
+    pub use super::io::chewing_get_phoneSeqUnits;
+
+    /// Returns the number of u16 code units in the phonetic sequence returned
+    /// by [chewing_get_phoneSeqUnits].
+    pub use super::io::chewing_get_phoneSeqUnitsLen;
+
     /// Converts the u16 encoded syllables to a bopomofo string.
     ///
     /// If both of the buf and the len are 0, this function will return buf
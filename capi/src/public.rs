@@ -117,6 +117,11 @@ pub enum KB {
     ColemakDhOrth,
     Workman,
     Colemak,
+    MsDoublePinyin,
+    ZiranmaDoublePinyin,
+    XiaoheDoublePinyin,
+    Keypad,
+    Fangyin,
 }
 
 /// Opaque context handle used for chewing APIs.
@@ -113,7 +113,7 @@ pub fn main() -> Result<()> {
             null_mut(),
         );
 
-        let mut ops = stdin().bytes();
+        let mut ops = stdin().lock().bytes();
 
         // Take first few bytes as mode settings
         let kb_type = ops.next().transpose()?.unwrap_or_default().into();
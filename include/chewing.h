@@ -176,6 +176,11 @@ typedef enum KB {
   KB_COLEMAK_DH_ORTH,
   KB_WORKMAN,
   KB_COLEMAK,
+  KB_MS_DOUBLE_PINYIN,
+  KB_ZIRANMA_DOUBLE_PINYIN,
+  KB_XIAOHE_DOUBLE_PINYIN,
+  KB_KEYPAD,
+  KB_FANGYIN,
   KB_TYPE_NUM,
 } KB;
 
@@ -507,6 +512,20 @@ unsigned short *chewing_get_phoneSeq(const struct ChewingContext *ctx);
  */
 int chewing_get_phoneSeqLen(const struct ChewingContext *ctx);
 
+/**
+ * # Safety
+ *
+ * This function should be called with valid pointers.
+ */
+unsigned short *chewing_get_phoneSeqUnits(const struct ChewingContext *ctx);
+
+/**
+ * # Safety
+ *
+ * This function should be called with valid pointers.
+ */
+int chewing_get_phoneSeqUnitsLen(const struct ChewingContext *ctx);
+
 /**
  * # Safety
  *
@@ -0,0 +1,305 @@
+//! Readings of Chinese text
+//!
+//! Annotates text with the most likely syllable of each character. The text
+//! is segmented into the phrases of the dictionaries first, so polyphonic
+//! characters get their reading from the phrase they belong to.
+
+use std::{cmp::Reverse, collections::HashMap, fmt::Write};
+
+use crate::{dictionary::Dictionary, zhuyin::Syllable};
+
+/// A part of the annotated text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    /// The annotated text.
+    pub text: String,
+    /// The reading of each character, or empty if the text has no reading.
+    pub syllables: Vec<Syllable>,
+}
+
+impl Annotation {
+    /// Returns true if the text was found as a phrase in the dictionaries.
+    pub fn has_reading(&self) -> bool {
+        !self.syllables.is_empty()
+    }
+    /// Returns each character with its reading.
+    pub fn chars(&self) -> impl Iterator<Item = (char, Option<Syllable>)> + '_ {
+        let mut syllables = self.syllables.iter().copied();
+        self.text.chars().map(move |ch| (ch, syllables.next()))
+    }
+}
+
+/// Finds the readings of text with the phrases of a dictionary.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::{
+///     conversion::Annotator,
+///     dictionary::TrieBuf,
+///     syl,
+///     zhuyin::Bopomofo::*,
+/// };
+///
+/// let dict = TrieBuf::from([
+///     (vec![syl![X, I, ENG, TONE2]], vec![("行", 200)]),
+///     (vec![syl![H, ANG, TONE2]], vec![("行", 100)]),
+///     (vec![syl![I, EN, TONE2], syl![H, ANG, TONE2]], vec![("銀行", 100)]),
+/// ]);
+/// let annotator = Annotator::new(&dict);
+///
+/// let annotations = annotator.annotate("銀行行");
+/// assert_eq!(vec![syl![I, EN, TONE2], syl![H, ANG, TONE2]], annotations[0].syllables);
+/// assert_eq!(vec![syl![X, I, ENG, TONE2]], annotations[1].syllables);
+/// ```
+#[derive(Debug)]
+pub struct Annotator {
+    phrases: HashMap<String, (Vec<Syllable>, u32)>,
+    max_len: usize,
+}
+
+impl Annotator {
+    /// Creates an annotator with all phrases of the dictionary.
+    pub fn new(dict: &dyn Dictionary) -> Annotator {
+        Self::with_filter(dict, |_| true)
+    }
+    /// Creates an annotator with only the phrases that occur in the text.
+    pub(crate) fn for_text(dict: &dyn Dictionary, text: &str) -> Annotator {
+        Self::with_filter(dict, |phrase| text.contains(phrase))
+    }
+    fn with_filter(dict: &dyn Dictionary, filter: impl Fn(&str) -> bool) -> Annotator {
+        let mut phrases: HashMap<String, (Vec<Syllable>, u32)> = HashMap::new();
+        for (syllables, phrase) in dict.entries() {
+            if syllables.len() != phrase.as_str().chars().count() || !filter(phrase.as_str()) {
+                continue;
+            }
+            match phrases.get(phrase.as_str()) {
+                Some(&(_, freq)) if freq >= phrase.freq() => (),
+                _ => {
+                    let freq = phrase.freq();
+                    phrases.insert(phrase.into(), (syllables, freq));
+                }
+            }
+        }
+        let max_len = phrases
+            .keys()
+            .map(|phrase| phrase.chars().count())
+            .max()
+            .unwrap_or(0);
+        Annotator { phrases, max_len }
+    }
+    /// Returns the most likely readings of the phrases of the text.
+    ///
+    /// The text is split into the fewest phrases from the dictionary, and
+    /// ties are broken by the total frequency of the phrases. Each character
+    /// that is not in the dictionary is returned alone without reading.
+    pub fn annotate(&self, text: &str) -> Vec<Annotation> {
+        let bounds = char_bounds(text);
+        let len = bounds.len() - 1;
+        // The best split of the first i chars as (phrases, frequency, start)
+        let mut best: Vec<(usize, Reverse<u64>, usize)> = Vec::with_capacity(len + 1);
+        best.push((0, Reverse(0), 0));
+        for end in 1..=len {
+            let (count, Reverse(total), _) = best[end - 1];
+            // A character without reading is always a candidate
+            let mut best_end = (count + 1, Reverse(total), end - 1);
+            for start in end.saturating_sub(self.max_len)..end {
+                let Some(&(_, freq)) = self.phrases.get(&text[bounds[start]..bounds[end]]) else {
+                    continue;
+                };
+                let (count, Reverse(total), _) = best[start];
+                let candidate = (count + 1, Reverse(total + u64::from(freq)), start);
+                if candidate < best_end {
+                    best_end = candidate;
+                }
+            }
+            best.push(best_end);
+        }
+
+        let mut annotations = vec![];
+        let mut end = len;
+        while end > 0 {
+            let (_, _, start) = best[end];
+            let text = &text[bounds[start]..bounds[end]];
+            let syllables = self
+                .phrases
+                .get(text)
+                .map(|(syllables, _)| syllables.clone())
+                .unwrap_or_default();
+            annotations.push(Annotation {
+                text: text.to_string(),
+                syllables,
+            });
+            end = start;
+        }
+        annotations.reverse();
+        annotations
+    }
+    /// Returns the readings of the phrases of the text split by longest
+    /// match.
+    ///
+    /// From the start of the text, the longest phrase from the dictionary is
+    /// taken at each position. Each character that is not in the dictionary
+    /// is returned alone without reading.
+    pub fn annotate_longest_match(&self, text: &str) -> Vec<Annotation> {
+        let bounds = char_bounds(text);
+        let len = bounds.len() - 1;
+        let mut annotations = vec![];
+        let mut start = 0;
+        while start < len {
+            let longest = (start + 1..=len.min(start + self.max_len))
+                .rev()
+                .find_map(|end| {
+                    let text = &text[bounds[start]..bounds[end]];
+                    let (syllables, _) = self.phrases.get(text)?;
+                    Some((
+                        end,
+                        Annotation {
+                            text: text.to_string(),
+                            syllables: syllables.clone(),
+                        },
+                    ))
+                });
+            let (end, annotation) = longest.unwrap_or_else(|| {
+                let annotation = Annotation {
+                    text: text[bounds[start]..bounds[start + 1]].to_string(),
+                    syllables: vec![],
+                };
+                (start + 1, annotation)
+            });
+            annotations.push(annotation);
+            start = end;
+        }
+        annotations
+    }
+}
+
+/// Returns the byte offset of each char of the text, followed by the length
+/// of the text.
+fn char_bounds(text: &str) -> Vec<usize> {
+    text.char_indices()
+        .map(|(i, _)| i)
+        .chain([text.len()])
+        .collect()
+}
+
+/// Formats the annotations as HTML ruby text.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::{conversion::{Annotation, to_ruby}, syl, zhuyin::Bopomofo::*};
+///
+/// let annotations = [
+///     Annotation { text: "行".to_string(), syllables: vec![syl![H, ANG, TONE2]] },
+///     Annotation { text: "!".to_string(), syllables: vec![] },
+/// ];
+/// assert_eq!("<ruby>行<rt>ㄏㄤˊ</rt></ruby>!", to_ruby(&annotations));
+/// ```
+pub fn to_ruby(annotations: &[Annotation]) -> String {
+    let mut ruby = String::new();
+    for annotation in annotations {
+        if annotation.has_reading() {
+            ruby.push_str("<ruby>");
+        }
+        for (ch, syl) in annotation.chars() {
+            match ch {
+                '&' => ruby.push_str("&amp;"),
+                '<' => ruby.push_str("&lt;"),
+                '>' => ruby.push_str("&gt;"),
+                _ => ruby.push(ch),
+            }
+            if let Some(syl) = syl {
+                let _ = write!(ruby, "<rt>{syl}</rt>");
+            }
+        }
+        if annotation.has_reading() {
+            ruby.push_str("</ruby>");
+        }
+    }
+    ruby
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dictionary::TrieBuf, syl, zhuyin::Bopomofo::*};
+
+    use super::{Annotation, Annotator};
+
+    fn annotator() -> Annotator {
+        let dict = TrieBuf::from([
+            (vec![syl![CH, ANG, TONE2]], vec![("長", 200)]),
+            (vec![syl![ZH, ANG, TONE3]], vec![("長", 100)]),
+            (vec![syl![X, I, AU, TONE4]], vec![("校", 100)]),
+            (
+                vec![syl![X, I, AU, TONE4], syl![ZH, ANG, TONE3]],
+                vec![("校長", 100)],
+            ),
+            (
+                vec![syl![CH, ANG, TONE2], syl![ZH, ANG, TONE3]],
+                vec![("長長", 10)],
+            ),
+        ]);
+        Annotator::new(&dict)
+    }
+
+    #[test]
+    fn reading_from_phrase() {
+        let annotations = annotator().annotate("校長");
+        assert_eq!(
+            vec![Annotation {
+                text: "校長".to_string(),
+                syllables: vec![syl![X, I, AU, TONE4], syl![ZH, ANG, TONE3]],
+            }],
+            annotations
+        );
+    }
+
+    #[test]
+    fn fallback_to_most_frequent_reading() {
+        let annotations = annotator().annotate("長");
+        assert_eq!(vec![syl![CH, ANG, TONE2]], annotations[0].syllables);
+    }
+
+    #[test]
+    fn fewest_phrases() {
+        let annotations = annotator().annotate("校長長");
+        let texts: Vec<_> = annotations.iter().map(|it| it.text.as_str()).collect();
+        assert_eq!(vec!["校長", "長"], texts);
+    }
+
+    #[test]
+    fn longest_match() {
+        let annotations = annotator().annotate_longest_match("長長校長");
+        let texts: Vec<_> = annotations.iter().map(|it| it.text.as_str()).collect();
+        assert_eq!(vec!["長長", "校長"], texts);
+        let annotations = annotator().annotate_longest_match("長校長");
+        let texts: Vec<_> = annotations.iter().map(|it| it.text.as_str()).collect();
+        assert_eq!(vec!["長", "校長"], texts);
+    }
+
+    #[test]
+    fn long_text() {
+        let text = "校長長a，".repeat(2500);
+        let annotations = annotator().annotate(&text);
+        assert_eq!(10000, annotations.len());
+        let texts: Vec<_> = annotations[..4].iter().map(|it| it.text.as_str()).collect();
+        assert_eq!(vec!["校長", "長", "a", "，"], texts);
+        let annotations = annotator().annotate_longest_match(&text);
+        assert_eq!(10000, annotations.len());
+    }
+
+    #[test]
+    fn characters_without_reading() {
+        let annotations = annotator().annotate("長a，");
+        let chars: Vec<_> = annotations.iter().flat_map(|it| it.chars()).collect();
+        assert_eq!(
+            vec![
+                ('長', Some(syl![CH, ANG, TONE2])),
+                ('a', None),
+                ('，', None)
+            ],
+            chars
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! Batch conversion
+//!
+//! Converts syllables written as text to Chinese sentences without driving an
+//! [`Editor`][crate::editor::Editor] key by key.
+
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    dictionary::{Dictionary, LookupStrategy},
+    editor::{
+        keyboard::KeyboardLayout,
+        known_readings,
+        zhuyin_layout::{KeyBehavior, KeyboardLayoutCompat},
+    },
+    zhuyin::{Bopomofo, BopomofoKind, PinyinVariant, Syllable},
+};
+
+use super::{ChewingEngine, Composition, ConversionEngine, Symbol};
+
+/// How the syllables of the input text are written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(variant_size_differences)]
+pub enum SyllableFormat {
+    /// Bopomofo letters, e.g. `ㄘㄜˋ ㄕˋ` or `ㄘㄜˋㄕˋ`.
+    ///
+    /// A syllable ends at a tone mark, a space, or a letter that cannot
+    /// follow the letters before it. The neutral tone mark may also be
+    /// written before the syllable.
+    Bopomofo,
+    /// Romanized syllables with tone numbers or diacritics, e.g. `ce4 shi4`
+    /// or `ce4shi4`.
+    Pinyin(PinyinVariant),
+    /// The keys typed on a keyboard layout, e.g. `hk4g4` on the default
+    /// layout.
+    Keys(KeyboardLayoutCompat),
+}
+
+/// Errors when parsing the syllables of the input text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSyllablesError {
+    /// The character is not a bopomofo letter or is out of place.
+    InvalidBopomofo(char),
+    /// The word is not a valid romanized syllable.
+    InvalidPinyin(String),
+    /// The key is not accepted by the keyboard layout.
+    InvalidKey(char),
+    /// The keys at the end of the text do not complete a syllable.
+    Incomplete,
+}
+
+impl Display for ParseSyllablesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseSyllablesError::InvalidBopomofo(ch) => write!(f, "invalid bopomofo '{ch}'"),
+            ParseSyllablesError::InvalidPinyin(word) => write!(f, "invalid pinyin '{word}'"),
+            ParseSyllablesError::InvalidKey(ch) => write!(f, "invalid key '{ch}'"),
+            ParseSyllablesError::Incomplete => write!(f, "incomplete syllable at the end"),
+        }
+    }
+}
+
+impl Error for ParseSyllablesError {}
+
+impl SyllableFormat {
+    /// Parses the syllables of the text.
+    ///
+    /// Key sequences of some layouts can be read as more than one syllable.
+    /// The readings that form the best conversion with the dictionary are
+    /// used, like the editor does.
+    pub fn parse(
+        self,
+        dict: &dyn Dictionary,
+        text: &str,
+    ) -> Result<Vec<Syllable>, ParseSyllablesError> {
+        let mut comp = self.parse_composition(dict, text)?;
+        if comp.is_ambiguous(0, comp.len()) {
+            let engine = ChewingEngine::new();
+            let (intervals, _) = engine.convert_with_score(dict, &comp);
+            comp.resolve_readings(dict, &intervals, LookupStrategy::Standard);
+        }
+        Ok(comp
+            .symbols()
+            .iter()
+            .filter_map(|sym| sym.to_syllable())
+            .collect())
+    }
+
+    /// Parses the text to a composition that keeps every reading.
+    fn parse_composition(
+        self,
+        dict: &dyn Dictionary,
+        text: &str,
+    ) -> Result<Composition, ParseSyllablesError> {
+        let mut comp = Composition::new();
+        match self {
+            SyllableFormat::Bopomofo => parse_bopomofo(text)?
+                .into_iter()
+                .for_each(|syl| comp.push(Symbol::from(syl))),
+            SyllableFormat::Pinyin(variant) => parse_pinyin(text, variant)?
+                .into_iter()
+                .for_each(|syl| comp.push(Symbol::from(syl))),
+            SyllableFormat::Keys(layout) => parse_keys(dict, text, layout)?
+                .iter()
+                .for_each(|readings| comp.insert_readings(comp.len(), readings)),
+        }
+        Ok(comp)
+    }
+}
+
+fn parse_bopomofo(text: &str) -> Result<Vec<Syllable>, ParseSyllablesError> {
+    let mut syllables = vec![];
+    let mut letters: Vec<Bopomofo> = vec![];
+    let mut light_tone = false;
+    let mut finish = |letters: &mut Vec<Bopomofo>, light_tone: &mut bool| {
+        if !letters.is_empty() {
+            let mut syllable = Syllable::new();
+            letters
+                .drain(..)
+                .for_each(|bopomofo| syllable.update(bopomofo));
+            if *light_tone {
+                syllable.update(Bopomofo::TONE5);
+            }
+            syllables.push(syllable);
+        }
+        *light_tone = false;
+    };
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            finish(&mut letters, &mut light_tone);
+            continue;
+        }
+        let bopomofo =
+            Bopomofo::try_from(ch).map_err(|_| ParseSyllablesError::InvalidBopomofo(ch))?;
+        let kind = bopomofo.kind();
+        if kind == BopomofoKind::Tone {
+            if letters.is_empty() {
+                // The neutral tone mark is also written before the syllable
+                if bopomofo != Bopomofo::TONE5 || light_tone {
+                    return Err(ParseSyllablesError::InvalidBopomofo(ch));
+                }
+                light_tone = true;
+                continue;
+            }
+            // The first tone is not stored in the syllable
+            if bopomofo != Bopomofo::TONE1 {
+                letters.push(bopomofo);
+            }
+            finish(&mut letters, &mut light_tone);
+            continue;
+        }
+        // A letter that cannot follow the last one starts a new syllable
+        if letters
+            .last()
+            .is_some_and(|last| kind_order(last.kind()) >= kind_order(kind))
+        {
+            finish(&mut letters, &mut light_tone);
+        }
+        letters.push(bopomofo);
+    }
+    if light_tone && letters.is_empty() {
+        return Err(ParseSyllablesError::Incomplete);
+    }
+    finish(&mut letters, &mut light_tone);
+    Ok(syllables)
+}
+
+fn kind_order(kind: BopomofoKind) -> u8 {
+    match kind {
+        BopomofoKind::Initial => 0,
+        BopomofoKind::Medial => 1,
+        BopomofoKind::Rime => 2,
+        BopomofoKind::Coda => 3,
+        BopomofoKind::Tone => 4,
+    }
+}
+
+fn parse_pinyin(text: &str, variant: PinyinVariant) -> Result<Vec<Syllable>, ParseSyllablesError> {
+    let mut syllables = vec![];
+    for word in text.split(|ch: char| ch.is_whitespace() || ch == '\'') {
+        // Tone numbers also separate syllables
+        for part in word.split_inclusive(|ch: char| ch.is_ascii_digit()) {
+            let syllable = Syllable::from_pinyin(part, variant)
+                .map_err(|_| ParseSyllablesError::InvalidPinyin(part.to_string()))?;
+            syllables.push(syllable);
+        }
+    }
+    Ok(syllables)
+}
+
+fn parse_keys(
+    dict: &dyn Dictionary,
+    text: &str,
+    layout: KeyboardLayoutCompat,
+) -> Result<Vec<Vec<Syllable>>, ParseSyllablesError> {
+    let keyboard = layout.keyboard();
+    let mut editor = layout.syllable_editor();
+    let mut syllables = vec![];
+    // The space key completes the last syllable on some layouts
+    for key in text.chars().map(Some).chain([None]) {
+        let ch = key.unwrap_or(' ');
+        if !ch.is_ascii() {
+            return Err(ParseSyllablesError::InvalidKey(ch));
+        }
+        if ch.is_ascii_whitespace() && editor.is_empty() {
+            continue;
+        }
+        let error = match key {
+            Some(ch) => ParseSyllablesError::InvalidKey(ch),
+            None => ParseSyllablesError::Incomplete,
+        };
+        match editor.key_press(keyboard.map_ascii(ch as u8)) {
+            KeyBehavior::Commit => {
+                let read = editor.read();
+                // Only a tone was typed
+                if !read.has_initial() && !read.has_medial() && !read.has_rime() {
+                    return Err(error);
+                }
+                let mut readings = known_readings(dict, editor.as_ref(), LookupStrategy::Standard);
+                if readings.is_empty() {
+                    readings.push(read);
+                }
+                syllables.push(readings);
+                editor.clear();
+            }
+            KeyBehavior::Absorb | KeyBehavior::Fuzzy(_) => (),
+            _ => return Err(error),
+        }
+    }
+    Ok(syllables)
+}
+
+/// Converts the syllables to at most `n` distinct sentences, best first.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::{
+///     conversion::{ChewingEngine, convert_syllables},
+///     dictionary::TrieBuf,
+///     syl,
+///     zhuyin::Bopomofo::*,
+/// };
+///
+/// let dict = TrieBuf::from([
+///     (vec![syl![C, E, TONE4]], vec![("測", 100)]),
+///     (vec![syl![SH, TONE4]], vec![("試", 100), ("是", 90)]),
+///     (vec![syl![SH, U]], vec![("書", 100)]),
+///     (vec![syl![C, E, TONE4], syl![SH, TONE4]], vec![("測試", 100)]),
+///     (vec![syl![SH, TONE4], syl![SH, U]], vec![("是書", 100)]),
+/// ]);
+/// let engine = ChewingEngine::new();
+///
+/// let syllables = [syl![C, E, TONE4], syl![SH, TONE4], syl![SH, U]];
+/// let sentences = convert_syllables(&engine, &dict, &syllables, 2);
+/// assert_eq!(2, sentences.len());
+/// assert!(sentences.contains(&"測是書".to_string()));
+/// ```
+pub fn convert_syllables(
+    engine: &dyn ConversionEngine,
+    dict: &dyn Dictionary,
+    syllables: &[Syllable],
+    n: usize,
+) -> Vec<String> {
+    let mut comp = Composition::new();
+    for &syllable in syllables {
+        comp.push(Symbol::from(syllable));
+    }
+    convert_composition(engine, dict, &comp, n)
+}
+
+fn convert_composition(
+    engine: &dyn ConversionEngine,
+    dict: &dyn Dictionary,
+    comp: &Composition,
+    n: usize,
+) -> Vec<String> {
+    let mut sentences: Vec<String> = vec![];
+    for intervals in engine.convert(dict, comp) {
+        if sentences.len() >= n {
+            break;
+        }
+        let sentence: String = intervals.iter().map(|it| it.str.as_ref()).collect();
+        if !sentences.contains(&sentence) {
+            sentences.push(sentence);
+        }
+    }
+    sentences
+}
+
+/// Parses the syllables of the text and converts them to at most `n`
+/// distinct sentences, best first.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::{
+///     conversion::{ChewingEngine, SyllableFormat, convert_text},
+///     dictionary::TrieBuf,
+///     syl,
+///     zhuyin::{Bopomofo::*, PinyinVariant},
+/// };
+///
+/// let dict = TrieBuf::from([
+///     (vec![syl![C, E, TONE4]], vec![("測", 100)]),
+///     (vec![syl![SH, TONE4]], vec![("試", 100)]),
+/// ]);
+/// let engine = ChewingEngine::new();
+/// let pinyin = SyllableFormat::Pinyin(PinyinVariant::HanyuPinyin);
+///
+/// assert_eq!(vec!["測試"], convert_text(&engine, &dict, pinyin, "ce4 shi4", 1)?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn convert_text(
+    engine: &dyn ConversionEngine,
+    dict: &dyn Dictionary,
+    format: SyllableFormat,
+    text: &str,
+    n: usize,
+) -> Result<Vec<String>, ParseSyllablesError> {
+    let comp = format.parse_composition(dict, text)?;
+    Ok(convert_composition(engine, dict, &comp, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        conversion::ChewingEngine,
+        dictionary::TrieBuf,
+        editor::zhuyin_layout::KeyboardLayoutCompat,
+        syl,
+        zhuyin::{Bopomofo::*, PinyinVariant},
+    };
+
+    use super::{ParseSyllablesError, SyllableFormat, convert_syllables, convert_text};
+
+    fn dict() -> TrieBuf {
+        TrieBuf::from([
+            (vec![syl![C, E, TONE4]], vec![("測", 100), ("冊", 90)]),
+            (vec![syl![SH, TONE4]], vec![("試", 100), ("是", 90)]),
+            (vec![syl![SH, U]], vec![("書", 100)]),
+            (vec![syl![M, E, TONE5]], vec![("麼", 100)]),
+            (
+                vec![syl![C, E, TONE4], syl![SH, TONE4]],
+                vec![("測試", 100)],
+            ),
+            (vec![syl![SH, TONE4], syl![SH, U]], vec![("是書", 100)]),
+        ])
+    }
+
+    #[test]
+    fn parse_bopomofo() {
+        let dict = dict();
+        let expected = vec![
+            syl![C, E, TONE4],
+            syl![SH, TONE4],
+            syl![SH, U],
+            syl![M, E, TONE5],
+        ];
+        assert_eq!(
+            Ok(expected.clone()),
+            SyllableFormat::Bopomofo.parse(&dict, "ㄘㄜˋ ㄕˋ ㄕㄨ ˙ㄇㄜ")
+        );
+        assert_eq!(
+            Ok(expected),
+            SyllableFormat::Bopomofo.parse(&dict, "ㄘㄜˋㄕˋㄕㄨˉㄇㄜ˙")
+        );
+        assert_eq!(
+            Err(ParseSyllablesError::InvalidBopomofo('a')),
+            SyllableFormat::Bopomofo.parse(&dict, "ㄘㄜˋa")
+        );
+    }
+
+    #[test]
+    fn parse_pinyin() {
+        let dict = dict();
+        let format = SyllableFormat::Pinyin(PinyinVariant::HanyuPinyin);
+        let expected = vec![syl![C, E, TONE4], syl![SH, TONE4], syl![SH, U]];
+        assert_eq!(Ok(expected.clone()), format.parse(&dict, "ce4 shi4 shu"));
+        assert_eq!(Ok(expected), format.parse(&dict, "cè'shì shū"));
+        assert_eq!(
+            Err(ParseSyllablesError::InvalidPinyin("xyz".to_string())),
+            format.parse(&dict, "ce4 xyz")
+        );
+    }
+
+    #[test]
+    fn parse_keys() {
+        let dict = dict();
+        let expected = vec![syl![C, E, TONE4], syl![SH, TONE4], syl![SH, U]];
+        let format = SyllableFormat::Keys(KeyboardLayoutCompat::Default);
+        assert_eq!(Ok(expected.clone()), format.parse(&dict, "hk4g4gj "));
+        assert_eq!(Ok(expected.clone()), format.parse(&dict, "hk4 g4 gj"));
+        let format = SyllableFormat::Keys(KeyboardLayoutCompat::HanyuPinyin);
+        assert_eq!(Ok(expected), format.parse(&dict, "ce4shi4shu"));
+        let format = SyllableFormat::Keys(KeyboardLayoutCompat::Default);
+        assert_eq!(
+            Err(ParseSyllablesError::InvalidKey('=')),
+            format.parse(&dict, "hk4=")
+        );
+        assert_eq!(
+            Err(ParseSyllablesError::Incomplete),
+            format.parse(&dict, "hk44")
+        );
+    }
+
+    #[test]
+    fn top_n_sentences() {
+        let dict = dict();
+        let engine = ChewingEngine::new();
+        let syllables = [syl![C, E, TONE4], syl![SH, TONE4], syl![SH, U]];
+        let sentences = convert_syllables(&engine, &dict, &syllables, 10);
+        assert_eq!(2, sentences.len());
+        assert!(sentences.contains(&"測試書".to_string()));
+        assert!(sentences.contains(&"測是書".to_string()));
+        assert_eq!(
+            sentences[..1],
+            convert_syllables(&engine, &dict, &syllables, 1)
+        );
+    }
+
+    #[test]
+    fn convert_bopomofo_text() {
+        let dict = dict();
+        let engine = ChewingEngine::new();
+        assert_eq!(
+            Ok(vec!["測試".to_string()]),
+            convert_text(&engine, &dict, SyllableFormat::Bopomofo, "ㄘㄜˋㄕˋ", 1)
+        );
+    }
+}
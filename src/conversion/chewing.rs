@@ -30,28 +30,81 @@ impl ChewingEngine {
         dict: &'a dyn Dictionary,
         comp: &'a Composition,
     ) -> impl Iterator<Item = Vec<Interval>> + Clone + 'a {
-        iter::once_with(move || {
-            if comp.is_empty() {
-                return vec![PossiblePath::default()];
-            }
-            let intervals = self.find_intervals(dict, comp);
-            let paths = self.find_k_paths(Self::MAX_OUT_PATHS, comp.len(), intervals);
-            trace!("paths: {:#?}", paths);
-            debug_assert!(!paths.is_empty());
+        iter::once_with(move || self.sorted_paths(dict, comp))
+            .flatten()
+            .map(|p| Self::to_intervals(comp, p))
+    }
+    /// Returns the best conversion and its score.
+    ///
+    /// The score is only meaningful when compared with the score of another
+    /// conversion of the same syllables.
+    pub(crate) fn convert_with_score(
+        &self,
+        dict: &dyn Dictionary,
+        comp: &Composition,
+    ) -> (Vec<Interval>, i32) {
+        let best = self
+            .sorted_paths(dict, comp)
+            .into_iter()
+            .next()
+            .expect("should have at least one path");
+        let score = best.score();
+        (Self::to_intervals(comp, best), score)
+    }
+    /// Returns the score of a conversion of the syllables.
+    ///
+    /// The intervals need not be the best conversion, which allows comparing
+    /// the best conversion with any other text with the same reading.
+    pub(crate) fn score(
+        &self,
+        dict: &dyn Dictionary,
+        comp: &Composition,
+        intervals: &[Interval],
+    ) -> i32 {
+        let intervals = intervals
+            .iter()
+            .map(|interval| {
+                let phrase = if interval.is_phrase {
+                    let freq = comp
+                        .lookup_phrases(dict, interval.start, interval.end, self.lookup_strategy)
+                        .into_iter()
+                        .filter(|phrase| phrase.as_str() == interval.str.as_ref())
+                        .map(|phrase| phrase.freq())
+                        .max()
+                        .unwrap_or(0);
+                    Phrase::new(interval.str.clone(), freq).into()
+                } else {
+                    comp.symbols[interval.start].into()
+                };
+                PossibleInterval {
+                    start: interval.start,
+                    end: interval.end,
+                    phrase,
+                }
+            })
+            .collect();
+        PossiblePath { intervals }.score()
+    }
+    fn sorted_paths(&self, dict: &dyn Dictionary, comp: &Composition) -> Vec<PossiblePath> {
+        if comp.is_empty() {
+            return vec![PossiblePath::default()];
+        }
+        let intervals = self.find_intervals(dict, comp);
+        let paths = self.find_k_paths(Self::MAX_OUT_PATHS, comp.len(), intervals);
+        trace!("paths: {:#?}", paths);
+        debug_assert!(!paths.is_empty());
 
-            let mut trimmed_paths = self.trim_paths(paths);
-            debug_assert!(!trimmed_paths.is_empty());
+        let mut trimmed_paths = self.trim_paths(paths);
+        debug_assert!(!trimmed_paths.is_empty());
 
-            trimmed_paths.sort_by(|a, b| b.cmp(a));
-            trimmed_paths
-        })
-        .flatten()
-        .map(|p| {
-            p.intervals
-                .into_iter()
-                .map(|it| it.into())
-                .fold(vec![], |acc, interval| glue_fn(comp, acc, interval))
-        })
+        trimmed_paths.sort_by(|a, b| b.cmp(a));
+        trimmed_paths
+    }
+    fn to_intervals(comp: &Composition, path: PossiblePath) -> Vec<Interval> {
+        path.intervals
+            .into_iter()
+            .map(|it| it.into())
+            .fold(vec![], |acc, interval| glue_fn(comp, acc, interval))
     }
 }
 
@@ -131,7 +184,7 @@ impl ChewingEngine {
 
         let mut max_freq = 0;
         let mut best_phrase = None;
-        'next_phrase: for phrase in dict.lookup_all_phrases(&symbols, self.lookup_strategy) {
+        'next_phrase: for phrase in com.lookup_phrases(dict, start, end, self.lookup_strategy) {
             // If there exists a user selected interval which is a
             // sub-interval of this phrase but the substring is
             // different then we can skip this phrase.
@@ -0,0 +1,339 @@
+//! New phrases in text
+//!
+//! Finds phrases in a document that are not in the dictionaries, so they can
+//! be added to a user dictionary in bulk. Candidates are the terms of a word
+//! list and the character sequences that repeat in the text more often than
+//! chance. Their readings come from the readings of the phrases and
+//! characters they contain.
+
+use std::collections::HashMap;
+
+use crate::{
+    dictionary::{Dictionary, DictionaryMut, LookupStrategy, UpdateDictionaryError},
+    zhuyin::Syllable,
+};
+
+use super::Annotator;
+
+/// The frequency of added phrases, the same as phrases added in the editor.
+const NEW_PHRASE_FREQ: u32 = 100;
+
+/// A phrase that is not in the dictionaries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewPhrase {
+    /// The text of the phrase.
+    pub phrase: String,
+    /// The reading of each character.
+    pub syllables: Vec<Syllable>,
+    /// How many times the phrase occurs in the text.
+    pub count: usize,
+}
+
+/// Finds phrases in text that are not in the dictionaries.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::{conversion::PhraseFinder, dictionary::TrieBuf, syl, zhuyin::Bopomofo::*};
+///
+/// let dict = TrieBuf::from([
+///     (vec![syl![C, E, TONE4]], vec![("測", 100)]),
+///     (vec![syl![SH, TONE4]], vec![("試", 100)]),
+/// ]);
+/// let finder = PhraseFinder::new(&dict);
+///
+/// let phrases = finder.find("測試，測試");
+/// assert_eq!("測試", phrases[0].phrase);
+/// assert_eq!(vec![syl![C, E, TONE4], syl![SH, TONE4]], phrases[0].syllables);
+/// assert_eq!(2, phrases[0].count);
+/// ```
+#[derive(Debug)]
+pub struct PhraseFinder<'a> {
+    dict: &'a dyn Dictionary,
+    annotator: Annotator,
+    terms: Vec<String>,
+    min_count: usize,
+    min_pmi: f64,
+    max_len: usize,
+}
+
+impl PhraseFinder<'_> {
+    /// Creates a finder that skips the phrases of the dictionary.
+    pub fn new(dict: &dyn Dictionary) -> PhraseFinder<'_> {
+        PhraseFinder {
+            dict,
+            annotator: Annotator::new(dict),
+            terms: vec![],
+            min_count: 2,
+            min_pmi: 1.0,
+            max_len: 4,
+        }
+    }
+    /// Sets the terms that are always candidates, even if they occur only
+    /// once or not at all in the text.
+    pub fn terms<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.terms = terms.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Sets how many times a character sequence must occur in the text to
+    /// be a candidate. The default is 2.
+    pub fn min_count(mut self, min_count: usize) -> Self {
+        self.min_count = min_count;
+        self
+    }
+    /// Sets the lowest pointwise mutual information, in bits, between the
+    /// parts of a character sequence for it to be a candidate. The default
+    /// is 1, i.e. the sequence occurs at least twice as often as its parts
+    /// would occur together by chance.
+    pub fn min_pmi(mut self, min_pmi: f64) -> Self {
+        self.min_pmi = min_pmi;
+        self
+    }
+    /// Sets the most characters of a repeated character sequence. The
+    /// default is 4.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+    /// Returns the reading of the text, or `None` if a character has no
+    /// reading.
+    pub fn reading(&self, text: &str) -> Option<Vec<Syllable>> {
+        let mut syllables = vec![];
+        for annotation in self.annotator.annotate(text) {
+            if !annotation.has_reading() {
+                return None;
+            }
+            syllables.extend(annotation.syllables);
+        }
+        Some(syllables)
+    }
+    /// Returns the new phrases in the text, the most frequent first.
+    ///
+    /// The terms are returned if they are not in the dictionaries. Other
+    /// character sequences are returned if they
+    ///
+    /// - lie between punctuation or other characters without reading,
+    /// - occur often enough and more often than their parts would by chance,
+    /// - are not part of a phrase from the dictionaries,
+    /// - and are not part of a longer sequence that occurs as often.
+    pub fn find(&self, text: &str) -> Vec<NewPhrase> {
+        let mut found: Vec<NewPhrase> = vec![];
+        for term in &self.terms {
+            let term = term.trim();
+            if term.chars().count() < 2 || found.iter().any(|it| it.phrase == term) {
+                continue;
+            }
+            if let Some(syllables) = self.reading(term) {
+                if !self.is_known(term, &syllables) {
+                    found.push(NewPhrase {
+                        phrase: term.to_string(),
+                        syllables,
+                        count: text.matches(term).count(),
+                    });
+                }
+            }
+        }
+
+        // Split the text into segments at characters without reading, such
+        // as punctuation. Each character keeps its reading and the index of
+        // its phrase from the dictionaries.
+        let mut segments = vec![vec![]];
+        for (i, annotation) in self.annotator.annotate(text).into_iter().enumerate() {
+            for (ch, syl) in annotation.chars() {
+                match syl {
+                    Some(syl) if ch.is_alphabetic() => {
+                        segments.last_mut().unwrap().push((ch, syl, i))
+                    }
+                    _ if segments.last().is_some_and(|it| !it.is_empty()) => segments.push(vec![]),
+                    _ => (),
+                }
+            }
+        }
+        let total: usize = segments.iter().map(Vec::len).sum();
+        // Every sequence of up to max_len characters is counted, but only
+        // the ones across phrases from the dictionaries are candidates.
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut candidates: HashMap<String, Vec<Syllable>> = HashMap::new();
+        for segment in &segments {
+            for start in 0..segment.len() {
+                for end in start + 1..=segment.len().min(start + self.max_len) {
+                    let window = &segment[start..end];
+                    let sequence: String = window.iter().map(|(ch, _, _)| ch).collect();
+                    *counts.entry(sequence.clone()).or_default() += 1;
+                    if window[0].2 != window[window.len() - 1].2 {
+                        candidates
+                            .entry(sequence)
+                            .or_insert_with(|| window.iter().map(|(_, syl, _)| *syl).collect());
+                    }
+                }
+            }
+        }
+        // The lowest pointwise mutual information of the two parts of the
+        // sequence over all split points
+        let pmi = |sequence: &str, count: usize| {
+            sequence
+                .char_indices()
+                .skip(1)
+                .map(|(i, _)| {
+                    let (left, right) = sequence.split_at(i);
+                    (count as f64 * total as f64 / (counts[left] * counts[right]) as f64).log2()
+                })
+                .fold(f64::INFINITY, f64::min)
+        };
+        let mut repeated: Vec<NewPhrase> = candidates
+            .into_iter()
+            .map(|(phrase, syllables)| NewPhrase {
+                count: counts[&phrase],
+                phrase,
+                syllables,
+            })
+            .filter(|it| it.count >= self.min_count && pmi(&it.phrase, it.count) >= self.min_pmi)
+            .collect();
+        // The highest count of a longer sequence that contains each sequence
+        let mut longer: HashMap<String, usize> = HashMap::new();
+        for candidate in &repeated {
+            let bounds: Vec<usize> = candidate
+                .phrase
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain([candidate.phrase.len()])
+                .collect();
+            for start in 0..bounds.len() {
+                for end in start + 2..bounds.len() {
+                    if end - start == bounds.len() - 1 {
+                        continue;
+                    }
+                    let count = longer
+                        .entry(candidate.phrase[bounds[start]..bounds[end]].to_string())
+                        .or_default();
+                    *count = (*count).max(candidate.count);
+                }
+            }
+        }
+        repeated.retain(|candidate| {
+            longer
+                .get(&candidate.phrase)
+                .is_none_or(|&count| count < candidate.count)
+                && !found.iter().any(|it| it.phrase == candidate.phrase)
+                && !self.is_known(&candidate.phrase, &candidate.syllables)
+        });
+        found.extend(repeated);
+        found.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.phrase.cmp(&b.phrase)));
+        found
+    }
+    fn is_known(&self, phrase: &str, syllables: &[Syllable]) -> bool {
+        self.dict
+            .lookup_all_phrases(&syllables, LookupStrategy::Standard)
+            .iter()
+            .any(|it| it.as_str() == phrase)
+    }
+}
+
+/// Adds the phrases to the dictionary.
+///
+/// The phrases get the same frequency as phrases added in the editor. The
+/// dictionary is not flushed.
+pub fn add_new_phrases(
+    dict: &mut dyn DictionaryMut,
+    phrases: &[NewPhrase],
+) -> Result<(), UpdateDictionaryError> {
+    for phrase in phrases {
+        dict.add_phrase(
+            &phrase.syllables,
+            (phrase.phrase.as_str(), NEW_PHRASE_FREQ).into(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dictionary::{Dictionary, LookupStrategy, TrieBuf},
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::{PhraseFinder, add_new_phrases};
+
+    fn dict() -> TrieBuf {
+        TrieBuf::from([
+            (vec![syl![X, I, ENG, TONE2]], vec![("行", 200)]),
+            (vec![syl![H, ANG, TONE2]], vec![("行", 100)]),
+            (vec![syl![I, EN, TONE2]], vec![("銀", 100)]),
+            (
+                vec![syl![I, EN, TONE2], syl![H, ANG, TONE2]],
+                vec![("銀行", 100)],
+            ),
+            (vec![syl![K, A, TONE3]], vec![("卡", 100)]),
+            (vec![syl![D, AI, TONE4]], vec![("貸", 100)]),
+        ])
+    }
+
+    #[test]
+    fn repeated_sequences() {
+        let dict = dict();
+        let finder = PhraseFinder::new(&dict);
+        let phrases = finder.find("銀行卡。銀行卡貸。銀行");
+        assert_eq!(1, phrases.len());
+        assert_eq!("銀行卡", phrases[0].phrase);
+        assert_eq!(
+            vec![syl![I, EN, TONE2], syl![H, ANG, TONE2], syl![K, A, TONE3]],
+            phrases[0].syllables
+        );
+        assert_eq!(2, phrases[0].count);
+    }
+
+    #[test]
+    fn chance_sequences() {
+        let dict = dict();
+        let finder = PhraseFinder::new(&dict);
+        // 卡 is so common that 卡貸 is expected to occur by chance
+        assert!(finder.find("卡貸。卡貸。卡卡卡卡卡卡").is_empty());
+        let finder = PhraseFinder::new(&dict).min_pmi(0.0);
+        let phrases = finder.find("卡貸。卡貸。卡卡卡卡卡卡");
+        assert!(phrases.iter().any(|it| it.phrase == "卡貸"));
+    }
+
+    #[test]
+    fn punctuation_splits_sequences() {
+        let dict = TrieBuf::from([
+            (vec![syl![K, A, TONE3]], vec![("卡", 100)]),
+            (vec![syl![D, AI, TONE4]], vec![("貸", 100)]),
+            (vec![syl![J, IU, TONE4]], vec![("。", 100)]),
+        ]);
+        let finder = PhraseFinder::new(&dict);
+        assert!(finder.find("卡。貸卡。貸").is_empty());
+    }
+
+    #[test]
+    fn terms() {
+        let dict = dict();
+        let finder = PhraseFinder::new(&dict).terms(["貸卡", "銀行", "貸款"]);
+        let phrases = finder.find("貸卡");
+        assert_eq!(1, phrases.len());
+        assert_eq!("貸卡", phrases[0].phrase);
+        assert_eq!(1, phrases[0].count);
+        assert_eq!(None, finder.reading("貸款"));
+    }
+
+    #[test]
+    fn add_phrases() {
+        let mut dict = dict();
+        let phrases = PhraseFinder::new(&dict).terms(["銀卡"]).find("");
+        add_new_phrases(&mut dict, &phrases).unwrap();
+        assert_eq!(
+            "銀卡",
+            dict.lookup_first_phrase(
+                &[syl![I, EN, TONE2], syl![K, A, TONE3]],
+                LookupStrategy::Standard
+            )
+            .unwrap()
+            .as_str()
+        );
+    }
+}
@@ -1,7 +1,11 @@
 //! Algorithms to convert syllables to Chinese characters.
 
+mod annotate;
+mod batch;
 mod chewing;
 mod fuzzy;
+mod learn;
+mod proofread;
 mod simple;
 mod symbol;
 
@@ -12,15 +16,23 @@ use std::{
 };
 
 use crate::{
-    dictionary::Dictionary,
+    dictionary::{Dictionary, LookupStrategy, Phrase, lookup_readings},
     zhuyin::{Syllable, SyllableSlice},
 };
 
+pub use self::annotate::{Annotation, Annotator, to_ruby};
+pub use self::batch::{ParseSyllablesError, SyllableFormat, convert_syllables, convert_text};
 pub use self::chewing::ChewingEngine;
 pub use self::fuzzy::FuzzyChewingEngine;
+pub use self::learn::{NewPhrase, PhraseFinder, add_new_phrases};
+pub use self::proofread::{Proofreader, Suggestion};
 pub use self::simple::SimpleEngine;
 pub(crate) use self::symbol::{full_width_symbol_input, special_symbol_input};
 
+/// Text between characters without reading is converted in runs of at most
+/// this many characters, like the auto commit of the editor.
+pub const MAX_RUN_LEN: usize = 39;
+
 /// Converts a composition buffer to list of intervals.
 ///
 /// [`Composition`] contains all user inputs and selection information. The out
@@ -183,6 +195,8 @@ pub struct Composition {
     gaps: Vec<Gap>,
     /// User set constraint on that output must match.
     selections: Vec<Interval>,
+    /// All readings of ambiguous syllables, empty for other symbols.
+    readings: Vec<Vec<Syllable>>,
 }
 
 impl Composition {
@@ -224,6 +238,20 @@ impl Composition {
     pub fn symbols(&self) -> &[Symbol] {
         &self.symbols
     }
+    /// Returns every syllable the symbol can be read as.
+    ///
+    /// This is the syllable itself unless the symbol was inserted with
+    /// [`insert_readings`][Composition::insert_readings], and empty for
+    /// characters.
+    pub fn readings(&self, index: usize) -> &[Syllable] {
+        match self.symbols.get(index) {
+            Some(Symbol::Syllable(syl)) if self.readings[index].is_empty() => {
+                std::slice::from_ref(syl)
+            }
+            Some(Symbol::Syllable(_)) => &self.readings[index],
+            _ => &[],
+        }
+    }
     pub fn selections(&self) -> &[Interval] {
         &self.selections
     }
@@ -282,12 +310,87 @@ impl Composition {
         }
         self.gaps.insert(index, Gap::Normal);
         self.gaps[0] = Gap::Begin;
+        self.readings.insert(index, vec![]);
+    }
+    /// Inserts a syllable that can be read as any of the readings.
+    ///
+    /// The symbol is the first reading until the conversion picks another
+    /// one. See [`readings`][Composition::readings].
+    pub fn insert_readings(&mut self, index: usize, readings: &[Syllable]) {
+        assert!(!readings.is_empty());
+        self.insert(index, Symbol::Syllable(readings[0]));
+        if readings.len() > 1 {
+            self.readings[index] = readings.to_vec();
+        }
     }
     pub fn replace(&mut self, index: usize, sym: Symbol) {
         assert!(index < self.len());
         self.symbols[index] = sym;
+        self.readings[index].clear();
         self.set_gap(index, Gap::Normal);
     }
+    /// Sets the symbol to one of its readings.
+    fn set_reading(&mut self, index: usize, syl: Syllable) {
+        assert!(self.readings(index).contains(&syl));
+        self.symbols[index] = Symbol::Syllable(syl);
+    }
+    /// Returns whether any symbol in the range has more than one reading.
+    pub(crate) fn is_ambiguous(&self, start: usize, end: usize) -> bool {
+        self.readings[start..end]
+            .iter()
+            .any(|readings| !readings.is_empty())
+    }
+    /// Sets ambiguous syllables to the readings of the phrases they are
+    /// converted to.
+    ///
+    /// Phrases read with more than one combination of readings use the
+    /// combination with the most frequent entry.
+    pub(crate) fn resolve_readings(
+        &mut self,
+        dict: &dyn Dictionary,
+        intervals: &[Interval],
+        strategy: LookupStrategy,
+    ) {
+        for interval in intervals {
+            if !interval.is_phrase || !self.is_ambiguous(interval.start, interval.end) {
+                continue;
+            }
+            let readings: Vec<_> = (interval.start..interval.end)
+                .map(|i| self.readings(i))
+                .collect();
+            let best = lookup_readings(dict, &readings, strategy)
+                .into_iter()
+                .filter(|(_, phrase)| phrase.as_str() == interval.str.as_ref())
+                .fold(None, |best, (syllables, phrase)| match best {
+                    Some((_, freq)) if freq >= phrase.freq() => best,
+                    _ => Some((syllables, phrase.freq())),
+                });
+            if let Some((syllables, _)) = best {
+                for (index, syl) in (interval.start..).zip(syllables) {
+                    self.set_reading(index, syl);
+                }
+            }
+        }
+    }
+    /// Returns all phrases read with the syllables in the range.
+    ///
+    /// Every reading of ambiguous syllables is looked up.
+    pub(crate) fn lookup_phrases(
+        &self,
+        dict: &(impl Dictionary + ?Sized),
+        start: usize,
+        end: usize,
+        strategy: LookupStrategy,
+    ) -> Vec<Phrase> {
+        if !self.is_ambiguous(start, end) {
+            return dict.lookup_all_phrases(&&self.symbols[start..end], strategy);
+        }
+        let readings: Vec<_> = (start..end).map(|i| self.readings(i)).collect();
+        lookup_readings(dict, &readings, strategy)
+            .into_iter()
+            .map(|(_, phrase)| phrase)
+            .collect()
+    }
     pub fn push_selection(&mut self, interval: Interval) {
         assert!(interval.end <= self.len());
         let mut to_remove = vec![];
@@ -320,6 +423,7 @@ impl Composition {
         }
         self.symbols.drain(0..n);
         self.gaps.drain(0..n);
+        self.readings.drain(0..n);
         if !self.gaps.is_empty() {
             self.gaps[0] = Gap::Begin;
         }
@@ -342,6 +446,7 @@ impl Composition {
         }
         self.symbols.remove(index);
         self.gaps.remove(index);
+        self.readings.remove(index);
         if !self.gaps.is_empty() {
             self.gaps[0] = Gap::Begin;
         }
@@ -350,5 +455,6 @@ impl Composition {
         self.symbols.clear();
         self.gaps.clear();
         self.selections.clear();
+        self.readings.clear();
     }
 }
@@ -0,0 +1,254 @@
+//! Homophone proofreading
+//!
+//! Finds homophone mistakes in text. The text is annotated with its most
+//! likely readings and converted again with the [`ChewingEngine`]. Spans
+//! where the conversion differs from the text and scores much higher than
+//! the text itself are reported.
+
+use std::ops::Range;
+
+use crate::dictionary::Dictionary;
+
+use super::{Annotation, Annotator, ChewingEngine, Composition, Interval, MAX_RUN_LEN, Symbol};
+
+/// The score difference that gives a confidence of 0.5.
+const SCORE_SCALE: f64 = 1000.0;
+
+/// A span of text that is likely a homophone mistake.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// The range of the span, counted in characters.
+    pub range: Range<usize>,
+    /// The original text of the span.
+    pub original: String,
+    /// The text with the same reading preferred by the conversion engine.
+    pub suggestion: String,
+    /// How much the suggestion is preferred, from 0 to 1.
+    pub confidence: f64,
+}
+
+/// Checks text for homophone mistakes.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::{conversion::Proofreader, dictionary::TrieBuf, syl, zhuyin::Bopomofo::*};
+///
+/// let dict = TrieBuf::from([
+///     (vec![syl![Z, AI, TONE4]], vec![("在", 200), ("再", 100)]),
+///     (vec![syl![J, I, AN, TONE4]], vec![("見", 100)]),
+///     (vec![syl![Z, AI, TONE4], syl![J, I, AN, TONE4]], vec![("再見", 100)]),
+/// ]);
+/// let proofreader = Proofreader::new(&dict);
+///
+/// let suggestions = proofreader.check("在見");
+/// assert_eq!("再見", suggestions[0].suggestion);
+/// assert_eq!(0..2, suggestions[0].range);
+/// ```
+#[derive(Debug)]
+pub struct Proofreader<'a> {
+    dict: &'a dyn Dictionary,
+    annotator: Annotator,
+    engine: ChewingEngine,
+    min_confidence: f64,
+}
+
+impl Proofreader<'_> {
+    /// Creates a proofreader that uses the phrases of the dictionary.
+    pub fn new(dict: &dyn Dictionary) -> Proofreader<'_> {
+        Proofreader {
+            dict,
+            annotator: Annotator::new(dict),
+            engine: ChewingEngine::new(),
+            min_confidence: 0.5,
+        }
+    }
+    /// Sets the minimum confidence of reported suggestions.
+    ///
+    /// The default is 0.5. With 0, every span where the conversion differs
+    /// from the text is reported.
+    pub fn min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+    /// Returns the suggestions for the text, in order.
+    pub fn check(&self, text: &str) -> Vec<Suggestion> {
+        let mut suggestions = vec![];
+        let mut offset = 0;
+        let mut run: Vec<Annotation> = vec![];
+        let mut run_len = 0;
+        for annotation in self.annotator.annotate(text) {
+            let len = annotation.text.chars().count();
+            if !annotation.has_reading() || run_len + len > MAX_RUN_LEN {
+                suggestions.extend(self.check_run(&run, offset));
+                offset += run_len;
+                run.clear();
+                run_len = 0;
+            }
+            if annotation.has_reading() {
+                run.push(annotation);
+                run_len += len;
+            } else {
+                offset += len;
+            }
+        }
+        suggestions.extend(self.check_run(&run, offset));
+        suggestions
+    }
+    fn check_run(&self, run: &[Annotation], offset: usize) -> Vec<Suggestion> {
+        if run.is_empty() {
+            return vec![];
+        }
+        let original: Vec<char> = run
+            .iter()
+            .flat_map(|annotation| annotation.text.chars())
+            .collect();
+        let mut comp = Composition::new();
+        run.iter()
+            .flat_map(|annotation| annotation.syllables.iter())
+            .for_each(|&syl| comp.push(Symbol::from(syl)));
+        let (best, score) = self.engine.convert_with_score(self.dict, &comp);
+        // The best conversion of the original text, with its characters pinned
+        let mut pinned = comp.clone();
+        for (start, ch) in original.iter().enumerate() {
+            pinned.push_selection(Interval {
+                start,
+                end: start + 1,
+                is_phrase: true,
+                str: ch.to_string().into(),
+            });
+        }
+        let (kept, _) = self.engine.convert_with_score(self.dict, &pinned);
+
+        // Split both conversions into regions at their common boundaries and
+        // score each differing region by swapping in the original text.
+        let mut suggestions = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < best.len() {
+            let (best_start, kept_start) = (i, j);
+            let mut best_end = best[i].end;
+            let mut kept_end = kept[j].end;
+            i += 1;
+            j += 1;
+            while best_end != kept_end {
+                if best_end < kept_end {
+                    best_end = best[i].end;
+                    i += 1;
+                } else {
+                    kept_end = kept[j].end;
+                    j += 1;
+                }
+            }
+            let differs = |interval: &&Interval| {
+                !original[interval.start..interval.end]
+                    .iter()
+                    .copied()
+                    .eq(interval.str.chars())
+            };
+            if !best[best_start..i].iter().any(|it| differs(&it)) {
+                continue;
+            }
+            let mixed: Vec<Interval> = best[..best_start]
+                .iter()
+                .chain(&kept[kept_start..j])
+                .chain(&best[i..])
+                .cloned()
+                .collect();
+            let original_score = self.engine.score(self.dict, &comp, &mixed);
+            let delta = f64::from(score.saturating_sub(original_score).max(0));
+            let confidence = delta / (delta + SCORE_SCALE);
+            if confidence < self.min_confidence {
+                continue;
+            }
+            for interval in best[best_start..i].iter().filter(differs) {
+                suggestions.push(Suggestion {
+                    range: offset + interval.start..offset + interval.end,
+                    original: original[interval.start..interval.end].iter().collect(),
+                    suggestion: interval.str.to_string(),
+                    confidence,
+                });
+            }
+        }
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dictionary::TrieBuf, syl, zhuyin::Bopomofo::*};
+
+    use super::Proofreader;
+
+    fn dict() -> TrieBuf {
+        TrieBuf::from([
+            (vec![syl![Z, AI, TONE4]], vec![("在", 200), ("再", 100)]),
+            (vec![syl![J, I, AN, TONE4]], vec![("見", 100)]),
+            (
+                vec![syl![Z, AI, TONE4], syl![J, I, AN, TONE4]],
+                vec![("再見", 100)],
+            ),
+            (vec![syl![SH, TONE4]], vec![("是", 100), ("事", 90)]),
+        ])
+    }
+
+    #[test]
+    fn correct_text() {
+        let dict = dict();
+        let proofreader = Proofreader::new(&dict);
+        assert!(proofreader.check("再見").is_empty());
+    }
+
+    #[test]
+    fn homophone_mistake() {
+        let dict = dict();
+        let proofreader = Proofreader::new(&dict);
+        let suggestions = proofreader.check("是，在見");
+        assert_eq!(1, suggestions.len());
+        assert_eq!(2..4, suggestions[0].range);
+        assert_eq!("在見", suggestions[0].original);
+        assert_eq!("再見", suggestions[0].suggestion);
+        assert!(suggestions[0].confidence > 0.5);
+    }
+
+    #[test]
+    fn close_alternatives_are_not_reported() {
+        let dict = dict();
+        let proofreader = Proofreader::new(&dict);
+        assert!(proofreader.check("事").is_empty());
+        let proofreader = Proofreader::new(&dict).min_confidence(0.0);
+        assert_eq!("是", proofreader.check("事")[0].suggestion);
+    }
+
+    #[test]
+    fn paragraph_length_text() {
+        // Every syllable has a common and a rare character, and the text is
+        // written with the rare ones, so every span differs.
+        let syllables: Vec<_> = [B, P, M, F, D, T, N, L, G, K, H]
+            .into_iter()
+            .flat_map(|initial| [A, O, AI, EI, AN, EN].map(|rime| (initial, rime)))
+            .flat_map(|(initial, rime)| {
+                [TONE1, TONE2, TONE3, TONE4].map(|tone| syl![initial, rime, tone])
+            })
+            .collect();
+        let char_of =
+            |i: usize, rare: bool| char::from_u32(0x4e00 + 2 * i as u32 + u32::from(rare)).unwrap();
+        let mut dict = TrieBuf::new_in_memory();
+        for (i, &syl) in syllables.iter().enumerate() {
+            let j = (i + 1) % syllables.len();
+            let common: String = [char_of(i, false), char_of(j, false)].iter().collect();
+            dict.add_phrase(&[syl], (char_of(i, false).to_string(), 100).into())
+                .unwrap();
+            dict.add_phrase(&[syl], (char_of(i, true).to_string(), 10).into())
+                .unwrap();
+            dict.add_phrase(&[syl, syllables[j]], (common, 50).into())
+                .unwrap();
+        }
+        let text: String = (0..2000)
+            .map(|i| char_of(i * 7 % syllables.len(), true))
+            .collect();
+
+        let proofreader = Proofreader::new(&dict).min_confidence(0.0);
+        let suggestions = proofreader.check(&text);
+        assert!(!suggestions.is_empty());
+    }
+}
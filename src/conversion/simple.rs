@@ -35,10 +35,10 @@ impl SimpleEngine {
                     str: sym.to_char().unwrap().to_string().into_boxed_str(),
                 });
             } else {
-                let phrase = dict.lookup_first_phrase(
-                    &[sym.to_syllable().unwrap()],
-                    crate::dictionary::LookupStrategy::Standard,
-                );
+                let phrase = comp
+                    .lookup_phrases(dict, i, i + 1, crate::dictionary::LookupStrategy::Standard)
+                    .into_iter()
+                    .next();
                 let phrase_str = phrase.map_or_else(
                     || sym.to_syllable().unwrap().to_string(),
                     |phrase| phrase.to_string(),
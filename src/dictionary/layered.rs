@@ -113,6 +113,13 @@ impl Dictionary for Layered {
         phrases
     }
 
+    fn has_prefix(&self, syllables: &dyn SyllableSlice, strategy: LookupStrategy) -> bool {
+        self.sys_dict
+            .iter()
+            .chain(iter::once(&self.user_dict))
+            .any(|d| d.has_prefix(syllables, strategy))
+    }
+
     /// Returns all entries from all dictionaries.
     ///
     /// **NOTE**: Duplicate entries are not removed.
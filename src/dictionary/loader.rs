@@ -7,16 +7,22 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use log::{info, warn};
+use log::{error, info, warn};
 
 use crate::{
     editor::{AbbrevTable, SymbolSelector},
-    path::{find_extra_dat_by_path, find_path_by_files, sys_path_from_env_var, userphrase_path},
+    path::{
+        find_extra_dat_by_path, find_path_by_files, sys_path_from_env_var, trusted_keys_dir,
+        userphrase_path,
+    },
 };
 
 #[cfg(feature = "sqlite")]
 use super::SqliteDictionary;
-use super::{Dictionary, Trie, TrieBuf, uhash};
+use super::{
+    Dictionary, SignatureError, SignaturePolicy, TrieBuf, TrieOpenOptions, TrustedKeys,
+    signature_path, uhash,
+};
 
 const SD_WORD_FILE_NAME: &str = "word.dat";
 const SD_TSI_FILE_NAME: &str = "tsi.dat";
@@ -31,6 +37,8 @@ const SYMBOLS_FILE_NAME: &str = "symbols.dat";
 #[derive(Debug, Default)]
 pub struct SystemDictionaryLoader {
     sys_path: Option<String>,
+    signature_policy: Option<SignaturePolicy>,
+    trusted_keys: Option<TrustedKeys>,
 }
 
 /// Errors during loading system or user dictionaries.
@@ -40,6 +48,8 @@ pub enum LoadDictionaryError {
     NotFound,
     /// IO Error.
     IoError(io::Error),
+    /// The dictionary is not signed by a trusted key.
+    InvalidSignature(PathBuf),
 }
 
 impl Display for LoadDictionaryError {
@@ -54,6 +64,34 @@ fn io_err(err: io::Error) -> LoadDictionaryError {
     LoadDictionaryError::IoError(err)
 }
 
+/// Returns whether the content of the dictionary at the path should be
+/// loaded according to the signature policy.
+///
+/// The content is verified instead of the file, so the file cannot be
+/// replaced between the verification and the loading.
+fn check_signature(policy: SignaturePolicy, keys: &TrustedKeys, path: &Path, data: &[u8]) -> bool {
+    if policy == SignaturePolicy::Off {
+        return true;
+    }
+    let verified = fs::read_to_string(signature_path(path))
+        .map_err(SignatureError::from)
+        .and_then(|signature| keys.verify_bytes(data, &signature));
+    match verified {
+        Ok(comment) => {
+            info!("Verified {}: {comment}", path.display());
+            true
+        }
+        Err(e) if policy == SignaturePolicy::Warn => {
+            warn!("Signature of {} is not valid: {e}", path.display());
+            true
+        }
+        Err(e) => {
+            error!("Rejected {}: {e}", path.display());
+            false
+        }
+    }
+}
+
 impl SystemDictionaryLoader {
     /// Creates a new system dictionary loader.
     pub fn new() -> SystemDictionaryLoader {
@@ -64,48 +102,111 @@ impl SystemDictionaryLoader {
         self.sys_path = Some(path.into());
         self
     }
+    /// Override the signature policy from the `CHEWING_SIGNATURE_POLICY`
+    /// environment variable.
+    pub fn signature_policy(mut self, policy: SignaturePolicy) -> SystemDictionaryLoader {
+        self.signature_policy = Some(policy);
+        self
+    }
+    /// Override the default trusted keys, which are the `*.pub` files in the
+    /// `keys.d` folder of the system data directory or in the folder of the
+    /// `CHEWING_KEYS_DIR` environment variable.
+    pub fn trusted_keys(mut self, keys: TrustedKeys) -> SystemDictionaryLoader {
+        self.trusted_keys = Some(keys);
+        self
+    }
     /// Searches and loads the system dictionaries and extra dictionaries.
     ///
     /// If no dictionary were found, a builtn minimum dictionary will be loaded.
+    ///
+    /// Depending on the signature policy, each dictionary must have a
+    /// detached minisign signature made by a trusted key. Under
+    /// [`SignaturePolicy::Enforce`] a system dictionary with an invalid
+    /// signature is an error, and an extra dictionary is skipped.
     pub fn load(&self) -> Result<Vec<Box<dyn Dictionary>>, LoadDictionaryError> {
-        let search_path = if let Some(sys_path) = &self.sys_path {
-            sys_path.to_owned()
-        } else {
-            sys_path_from_env_var()
-        };
+        let mut results = self.load_system()?;
+        results.extend(self.load_extra()?);
+        Ok(results)
+    }
+    /// Searches and loads only the system dictionaries, `word.dat` and
+    /// `tsi.dat`.
+    pub fn load_system(&self) -> Result<Vec<Box<dyn Dictionary>>, LoadDictionaryError> {
+        let search_path = self.search_path();
         let sys_path = find_path_by_files(&search_path, &[SD_WORD_FILE_NAME, SD_TSI_FILE_NAME])
             .ok_or(LoadDictionaryError::NotFound)?;
+        let (policy, keys) = self.signature_keys(&search_path)?;
 
         let mut results: Vec<Box<dyn Dictionary>> = vec![];
+        for file_name in [SD_WORD_FILE_NAME, SD_TSI_FILE_NAME] {
+            let dict_path = sys_path.join(file_name);
+            info!("Loading {file_name}");
+            let data = fs::read(&dict_path).map_err(io_err)?;
+            if !check_signature(policy, &keys, &dict_path, &data) {
+                return Err(LoadDictionaryError::InvalidSignature(dict_path));
+            }
+            let dict = TrieOpenOptions::new()
+                .read_bytes(dict_path, data)
+                .map_err(io_err)?;
+            results.push(Box::new(dict));
+        }
+        Ok(results)
+    }
+    /// Searches and loads only the extra dictionaries.
+    pub fn load_extra(&self) -> Result<Vec<Box<dyn Dictionary>>, LoadDictionaryError> {
+        let search_path = self.search_path();
+        let (policy, keys) = self.signature_keys(&search_path)?;
 
-        let word_dict_path = sys_path.join(SD_WORD_FILE_NAME);
-        info!("Loading {SD_WORD_FILE_NAME}");
-        let word_dict = Trie::open(word_dict_path).map_err(io_err)?;
-        results.push(Box::new(word_dict));
-
-        let tsi_dict_path = sys_path.join(SD_TSI_FILE_NAME);
-        info!("Loading {SD_TSI_FILE_NAME}");
-        let tsi_dict = Trie::open(tsi_dict_path).map_err(io_err)?;
-        results.push(Box::new(tsi_dict));
-
-        let extra_files = find_extra_dat_by_path(&search_path);
-        for path in extra_files {
+        let mut results: Vec<Box<dyn Dictionary>> = vec![];
+        for path in find_extra_dat_by_path(&search_path) {
             info!("Loading {}", path.display());
-            match Trie::open(&path) {
+            let data = match fs::read(&path) {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("Failed to load {}: {e}", path.display());
+                    continue;
+                }
+            };
+            if !check_signature(policy, &keys, &path, &data) {
+                continue;
+            }
+            match TrieOpenOptions::new().read_bytes(path.clone(), data) {
                 Ok(dict) => results.push(Box::new(dict)),
                 Err(e) => warn!("Failed to load {}: {e}", path.display()),
             }
         }
-
         Ok(results)
     }
-    /// Loads the abbrev table.
-    pub fn load_abbrev(&self) -> Result<AbbrevTable, LoadDictionaryError> {
-        let search_path = if let Some(sys_path) = &self.sys_path {
+    fn search_path(&self) -> String {
+        if let Some(sys_path) = &self.sys_path {
             sys_path.to_owned()
         } else {
             sys_path_from_env_var()
+        }
+    }
+    fn signature_keys(
+        &self,
+        search_path: &str,
+    ) -> Result<(SignaturePolicy, TrustedKeys), LoadDictionaryError> {
+        let policy = self
+            .signature_policy
+            .unwrap_or_else(SignaturePolicy::from_env_var);
+        let keys = match &self.trusted_keys {
+            Some(keys) => keys.clone(),
+            None if policy == SignaturePolicy::Off => TrustedKeys::new(),
+            None => {
+                let mut keys = TrustedKeys::new();
+                if let Some(dir) = trusted_keys_dir(search_path) {
+                    keys.add_dir(&dir)
+                        .map_err(|e| io_err(io::Error::other(e)))?;
+                }
+                keys
+            }
         };
+        Ok((policy, keys))
+    }
+    /// Loads the abbrev table.
+    pub fn load_abbrev(&self) -> Result<AbbrevTable, LoadDictionaryError> {
+        let search_path = self.search_path();
         let sys_path = find_path_by_files(&search_path, &[ABBREV_FILE_NAME])
             .ok_or(LoadDictionaryError::NotFound)?;
         let abbrev_path = sys_path.join(ABBREV_FILE_NAME);
@@ -114,11 +215,7 @@ impl SystemDictionaryLoader {
     }
     /// Loads the symbol table.
     pub fn load_symbol_selector(&self) -> Result<SymbolSelector, LoadDictionaryError> {
-        let search_path = if let Some(sys_path) = &self.sys_path {
-            sys_path.to_owned()
-        } else {
-            sys_path_from_env_var()
-        };
+        let search_path = self.search_path();
         let sys_path = find_path_by_files(&search_path, &[SYMBOLS_FILE_NAME])
             .ok_or(LoadDictionaryError::NotFound)?;
         let symbol_path = sys_path.join(SYMBOLS_FILE_NAME);
@@ -173,7 +270,7 @@ impl UserDictionaryLoader {
             #[cfg(feature = "sqlite")]
             {
                 let trie_dict = SqliteDictionary::open(user_dict_path)
-                    .map_err(|e| io::Error::new(io::ErrorKind::Other, Box::new(e)))?;
+                    .map_err(|e| io::Error::other(Box::new(e)))?;
                 for (syllables, phrase) in trie_dict.entries() {
                     let freq = phrase.freq();
                     let last_used = phrase.last_used().unwrap_or_default();
@@ -181,13 +278,13 @@ impl UserDictionaryLoader {
                         .as_dict_mut()
                         .unwrap()
                         .update_phrase(&syllables, phrase, freq, last_used)
-                        .map_err(|e| io::Error::new(io::ErrorKind::Other, Box::new(e)))?;
+                        .map_err(|e| io::Error::other(Box::new(e)))?;
                 }
                 fresh_dict
                     .as_dict_mut()
                     .unwrap()
                     .flush()
-                    .map_err(|e| io::Error::new(io::ErrorKind::Other, Box::new(e)))?;
+                    .map_err(|e| io::Error::other(Box::new(e)))?;
             }
         } else {
             let uhash_path = userdata_dir.join(UD_UHASH_FILE_NAME);
@@ -204,13 +301,13 @@ impl UserDictionaryLoader {
                             .as_dict_mut()
                             .unwrap()
                             .update_phrase(&syllables, phrase, freq, last_used)
-                            .map_err(|e| io::Error::new(io::ErrorKind::Other, Box::new(e)))?;
+                            .map_err(|e| io::Error::other(Box::new(e)))?;
                     }
                     fresh_dict
                         .as_dict_mut()
                         .unwrap()
                         .flush()
-                        .map_err(|e| io::Error::new(io::ErrorKind::Other, Box::new(e)))?;
+                        .map_err(|e| io::Error::other(Box::new(e)))?;
                 }
             }
         }
@@ -0,0 +1,177 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{Mutex, PoisonError},
+};
+
+use fs4::{FileExt, TryLockError};
+
+/// The lock file and the number of locks held by this process for each
+/// lock file.
+static HOLDERS: Mutex<BTreeMap<PathBuf, (File, usize)>> = Mutex::new(BTreeMap::new());
+
+/// An advisory lock that marks a user dictionary as used by a running editor.
+///
+/// The lock is an exclusive file lock on a file next to the dictionary with
+/// the `.lock` suffix, which contains the process id of the holder. The
+/// operating system releases the lock when the holder exits, even if it
+/// crashed. Tools that modify the dictionary outside of the editor take the
+/// lock too, so they do not overwrite the changes of the editor. Editors
+/// never wait for the lock.
+///
+/// The lock is released when the last lock of the process is dropped.
+#[derive(Debug)]
+pub struct UserDictionaryLock {
+    path: PathBuf,
+}
+
+fn lock_path(dict_path: &Path) -> PathBuf {
+    let mut path = OsString::from(dict_path.as_os_str());
+    path.push(".lock");
+    path.into()
+}
+
+/// Takes the exclusive lock of the file without waiting.
+///
+/// Returns false if another process holds the lock.
+fn try_lock(file: &File) -> io::Result<bool> {
+    match FileExt::try_lock(file) {
+        Ok(()) => Ok(true),
+        Err(TryLockError::WouldBlock) => Ok(false),
+        Err(TryLockError::Error(error)) => Err(error),
+    }
+}
+
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+impl UserDictionaryLock {
+    /// Locks the dictionary at the path for this process.
+    ///
+    /// Fails with [`io::ErrorKind::WouldBlock`] if another process holds
+    /// the lock.
+    pub fn acquire(dict_path: &Path) -> io::Result<UserDictionaryLock> {
+        let path = lock_path(dict_path);
+        let mut holders = HOLDERS.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((_, count)) = holders.get_mut(&path) {
+            *count += 1;
+            return Ok(UserDictionaryLock { path });
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        if !try_lock(&file)? {
+            let holder = read_pid(&path).map_or("another process".to_string(), |pid| {
+                format!("process {pid}")
+            });
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                format!("{} is locked by {holder}", dict_path.display()),
+            ));
+        }
+        file.set_len(0)?;
+        file.write_all(process::id().to_string().as_bytes())?;
+        holders.insert(path.clone(), (file, 1));
+        Ok(UserDictionaryLock { path })
+    }
+    /// Returns the process id of the running process that locked the
+    /// dictionary at the path.
+    ///
+    /// The process id is 0 if it cannot be read, e.g. on Windows where the
+    /// locked file cannot be read by other processes.
+    pub fn holder(dict_path: &Path) -> Option<u32> {
+        let path = lock_path(dict_path);
+        let holders = HOLDERS.lock().unwrap_or_else(PoisonError::into_inner);
+        if holders.contains_key(&path) {
+            return Some(process::id());
+        }
+        // The lock is released again when the file is closed
+        let file = OpenOptions::new().read(true).write(true).open(&path).ok()?;
+        if try_lock(&file).unwrap_or(true) {
+            return None;
+        }
+        Some(read_pid(&path).unwrap_or(0))
+    }
+}
+
+impl Drop for UserDictionaryLock {
+    fn drop(&mut self) {
+        let mut holders = HOLDERS.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some((file, count)) = holders.get_mut(&self.path) else {
+            return;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return;
+        }
+        // The lock file is kept, because removing it could let another
+        // process lock a file that is no longer there. Closing the file
+        // releases the lock.
+        let _ = file.set_len(0);
+        holders.remove(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io, process};
+
+    use tempfile::tempdir;
+
+    use super::{UserDictionaryLock, lock_path, try_lock};
+
+    #[test]
+    fn acquire_and_release() {
+        let dir = tempdir().unwrap();
+        let dict_path = dir.path().join("chewing.dat");
+        assert_eq!(None, UserDictionaryLock::holder(&dict_path));
+
+        let first = UserDictionaryLock::acquire(&dict_path).unwrap();
+        let second = UserDictionaryLock::acquire(&dict_path).unwrap();
+        assert_eq!(Some(process::id()), UserDictionaryLock::holder(&dict_path));
+        drop(first);
+        assert_eq!(Some(process::id()), UserDictionaryLock::holder(&dict_path));
+        drop(second);
+        assert_eq!(None, UserDictionaryLock::holder(&dict_path));
+    }
+
+    #[test]
+    fn stale_lock_file() {
+        let dir = tempdir().unwrap();
+        let dict_path = dir.path().join("chewing.dat");
+        // Left behind by a process that exited without releasing it
+        fs::write(lock_path(&dict_path), "4194304").unwrap();
+        assert_eq!(None, UserDictionaryLock::holder(&dict_path));
+        let _lock = UserDictionaryLock::acquire(&dict_path).unwrap();
+        assert_eq!(
+            process::id().to_string(),
+            fs::read_to_string(lock_path(&dict_path)).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn locked_by_other_process() {
+        let dir = tempdir().unwrap();
+        let dict_path = dir.path().join("chewing.dat");
+        // Another open file description behaves like another process
+        fs::write(lock_path(&dict_path), "4194304").unwrap();
+        let other = fs::File::open(lock_path(&dict_path)).unwrap();
+        assert!(try_lock(&other).unwrap());
+
+        assert_eq!(Some(4194304), UserDictionaryLock::holder(&dict_path));
+        let error = UserDictionaryLock::acquire(&dict_path).unwrap_err();
+        assert_eq!(io::ErrorKind::WouldBlock, error.kind());
+        assert!(error.to_string().contains("process 4194304"));
+        drop(other);
+        assert!(UserDictionaryLock::acquire(&dict_path).is_ok());
+    }
+}
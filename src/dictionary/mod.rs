@@ -14,15 +14,24 @@ use crate::zhuyin::{Syllable, SyllableSlice};
 
 pub use layered::Layered;
 pub use loader::{LoadDictionaryError, SystemDictionaryLoader, UserDictionaryLoader};
+pub use lock::UserDictionaryLock;
+pub use signature::{SignatureError, SignaturePolicy, TrustedKeys, signature_path};
 #[cfg(feature = "sqlite")]
 pub use sqlite::{SqliteDictionary, SqliteDictionaryBuilder, SqliteDictionaryError};
-pub use trie::{Trie, TrieBuilder, TrieOpenOptions, TrieStatistics};
+pub use stats::{DictionaryStatistics, statistics};
+pub use trie::{
+    Trie, TrieBuilder, TrieCorruption, TrieOpenOptions, TrieSalvage, TrieSectionSizes,
+    TrieStatistics,
+};
 pub use trie_buf::TrieBuf;
 
 mod layered;
 mod loader;
+mod lock;
+mod signature;
 #[cfg(feature = "sqlite")]
 mod sqlite;
+mod stats;
 mod trie;
 mod trie_buf;
 mod uhash;
@@ -366,6 +375,15 @@ pub trait Dictionary: Debug {
     ) -> Vec<Phrase> {
         self.lookup_first_n_phrases(syllables, usize::MAX, strategy)
     }
+    /// Returns whether any phrase is read with syllables that start with the
+    /// syllables.
+    ///
+    /// This is used to skip impossible combinations when looking up
+    /// ambiguous readings. The default implementation always returns true.
+    fn has_prefix(&self, syllables: &dyn SyllableSlice, strategy: LookupStrategy) -> bool {
+        let _ = (syllables, strategy);
+        true
+    }
     /// Returns an iterator to all phrases in the dictionary.
     fn entries(&self) -> Entries<'_>;
     /// Returns information about the dictionary instance.
@@ -469,9 +487,53 @@ pub trait DictionaryBuilder {
     fn as_any(&self) -> &dyn Any;
 }
 
+/// Returns all phrases read with one of the readings at each position,
+/// together with the syllables they are read with.
+///
+/// Combinations that are not the prefix of any phrase are skipped using
+/// [`Dictionary::has_prefix`].
+pub(crate) fn lookup_readings<D: Dictionary + ?Sized>(
+    dict: &D,
+    readings: &[&[Syllable]],
+    strategy: LookupStrategy,
+) -> Vec<(Vec<Syllable>, Phrase)> {
+    fn search<D: Dictionary + ?Sized>(
+        dict: &D,
+        readings: &[&[Syllable]],
+        strategy: LookupStrategy,
+        syllables: &mut Vec<Syllable>,
+        result: &mut Vec<(Vec<Syllable>, Phrase)>,
+    ) {
+        let Some(&candidates) = readings.get(syllables.len()) else {
+            for phrase in dict.lookup_all_phrases(syllables, strategy) {
+                result.push((syllables.clone(), phrase));
+            }
+            return;
+        };
+        for &syl in candidates {
+            syllables.push(syl);
+            if syllables.len() == readings.len() || dict.has_prefix(syllables, strategy) {
+                search(dict, readings, strategy, syllables, result);
+            }
+            syllables.pop();
+        }
+    }
+    let mut result = vec![];
+    if !readings.is_empty() {
+        search(dict, readings, strategy, &mut vec![], &mut result);
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::dictionary::{Dictionary, DictionaryBuilder, DictionaryMut};
+    use crate::{
+        dictionary::{
+            Dictionary, DictionaryBuilder, DictionaryMut, LookupStrategy, TrieBuf, lookup_readings,
+        },
+        syl,
+        zhuyin::Bopomofo::*,
+    };
 
     #[test]
     fn ensure_object_safe() {
@@ -479,4 +541,42 @@ mod tests {
         const _: Option<&dyn DictionaryMut> = None;
         const _: Option<&dyn DictionaryBuilder> = None;
     }
+
+    #[test]
+    fn lookup_readings_of_every_position() {
+        let dict = TrieBuf::from([
+            (
+                vec![syl![ZH, U, ENG], syl![U, EN, TONE2]],
+                vec![("中文", 100)],
+            ),
+            (
+                vec![syl![SH, U, ANG], syl![U, EN, TONE2]],
+                vec![("雙文", 1)],
+            ),
+            (vec![syl![SH, U, ANG]], vec![("雙", 1000)]),
+        ]);
+        let first = [syl![SH, U, ANG], syl![ZH, U, ENG]];
+        let second = [syl![U, EN, TONE2]];
+
+        let result: Vec<_> = lookup_readings(&dict, &[&first, &second], LookupStrategy::Standard)
+            .into_iter()
+            .map(|(syllables, phrase)| (syllables, phrase.to_string()))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (
+                    vec![syl![SH, U, ANG], syl![U, EN, TONE2]],
+                    "雙文".to_string()
+                ),
+                (
+                    vec![syl![ZH, U, ENG], syl![U, EN, TONE2]],
+                    "中文".to_string()
+                ),
+            ],
+            result
+        );
+        assert!(dict.has_prefix(&[syl![ZH, U, ENG]], LookupStrategy::Standard));
+        assert!(!dict.has_prefix(&[syl![U, EN, TONE2]], LookupStrategy::Standard));
+    }
 }
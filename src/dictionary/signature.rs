@@ -0,0 +1,270 @@
+use std::{
+    env,
+    error::Error,
+    ffi::{OsStr, OsString},
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use log::{info, warn};
+use minisign_verify::{Error as MinisignError, PublicKey, Signature};
+
+/// How the system dictionary loader checks the signatures of dictionaries.
+///
+/// The default policy is read from the `CHEWING_SIGNATURE_POLICY`
+/// environment variable, which accepts `off`, `warn`, and `enforce`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SignaturePolicy {
+    /// Signatures are not checked.
+    #[default]
+    Off,
+    /// Dictionaries without a valid signature are loaded with a warning.
+    Warn,
+    /// Dictionaries without a valid signature are rejected.
+    Enforce,
+}
+
+impl SignaturePolicy {
+    pub(crate) fn from_env_var() -> SignaturePolicy {
+        match env::var("CHEWING_SIGNATURE_POLICY") {
+            Ok(value) => value.parse().unwrap_or_else(|_| {
+                warn!("Unknown CHEWING_SIGNATURE_POLICY {value}, using enforce");
+                SignaturePolicy::Enforce
+            }),
+            Err(_) => SignaturePolicy::Off,
+        }
+    }
+}
+
+impl FromStr for SignaturePolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Ok(SignaturePolicy::Off),
+            "warn" => Ok(SignaturePolicy::Warn),
+            "enforce" => Ok(SignaturePolicy::Enforce),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Errors during signature verification.
+#[derive(Debug)]
+pub enum SignatureError {
+    /// The file, its signature, or the public key cannot be read.
+    IoError(io::Error),
+    /// The signature or the public key is not in the minisign format.
+    InvalidEncoding,
+    /// The signature was not made by any of the trusted keys.
+    UntrustedKey,
+    /// The file does not match the signature.
+    InvalidSignature,
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureError::IoError(error) => write!(f, "{error}"),
+            SignatureError::InvalidEncoding => write!(f, "not a minisign signature or key"),
+            SignatureError::UntrustedKey => write!(f, "not signed by a trusted key"),
+            SignatureError::InvalidSignature => write!(f, "signature verification failed"),
+        }
+    }
+}
+
+impl Error for SignatureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SignatureError::IoError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SignatureError {
+    fn from(value: io::Error) -> Self {
+        SignatureError::IoError(value)
+    }
+}
+
+impl From<MinisignError> for SignatureError {
+    fn from(value: MinisignError) -> Self {
+        match value {
+            MinisignError::IoError(error) => SignatureError::IoError(error),
+            MinisignError::UnexpectedKeyId => SignatureError::UntrustedKey,
+            MinisignError::InvalidSignature => SignatureError::InvalidSignature,
+            _ => SignatureError::InvalidEncoding,
+        }
+    }
+}
+
+/// Returns the path of the detached signature of a file.
+///
+/// Signatures are stored next to the signed file with the `.minisig` suffix,
+/// the same as the `minisign` tool.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut sig_path = OsString::from(path.as_os_str());
+    sig_path.push(".minisig");
+    sig_path.into()
+}
+
+/// A set of minisign public keys used to verify detached signatures.
+///
+/// Verification never uses the network. Keys are read from local files,
+/// such as the `*.pub` files in the `keys.d` folder of the system data
+/// directory.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::dictionary::TrustedKeys;
+///
+/// let mut keys = TrustedKeys::new();
+/// keys.add("RWRzJFnXiLZleAyCIv1talBjyRewelcy9gzYQq9pd3SKSFBPoy57sf5s")?;
+/// assert_eq!(1, keys.len());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct TrustedKeys {
+    keys: Vec<PublicKey>,
+}
+
+impl TrustedKeys {
+    /// Creates an empty set of keys.
+    pub fn new() -> TrustedKeys {
+        TrustedKeys::default()
+    }
+    /// Adds a public key, either the content of a minisign `.pub` file or
+    /// the base64 encoded key alone.
+    pub fn add(&mut self, key: &str) -> Result<(), SignatureError> {
+        let key = key.trim();
+        let key = if key.lines().count() > 1 {
+            PublicKey::decode(key)?
+        } else {
+            PublicKey::from_base64(key)?
+        };
+        self.keys.push(key);
+        Ok(())
+    }
+    /// Adds the public key stored in a minisign `.pub` file.
+    pub fn add_file(&mut self, path: &Path) -> Result<(), SignatureError> {
+        self.add(&fs::read_to_string(path)?)
+    }
+    /// Adds every `.pub` file in the folder.
+    pub fn add_dir(&mut self, path: &Path) -> Result<(), SignatureError> {
+        let mut files = vec![];
+        for entry in path.read_dir()?.flatten() {
+            let path = entry.path();
+            if path.is_file() && path.extension().and_then(OsStr::to_str) == Some("pub") {
+                files.push(path);
+            }
+        }
+        files.sort();
+        for path in files {
+            info!("Loading public key {}", path.display());
+            if let Err(e) = self.add_file(&path) {
+                warn!("Failed to load public key {}: {e}", path.display());
+            }
+        }
+        Ok(())
+    }
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+    /// Returns true if there is no key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+    /// Verifies the file against its detached `.minisig` signature.
+    ///
+    /// Returns the trusted comment of the signature.
+    pub fn verify(&self, path: &Path) -> Result<String, SignatureError> {
+        let signature = fs::read_to_string(signature_path(path))?;
+        self.verify_bytes(&fs::read(path)?, &signature)
+    }
+    /// Verifies the data against the content of a minisign signature file.
+    ///
+    /// Returns the trusted comment of the signature.
+    pub fn verify_bytes(&self, data: &[u8], signature: &str) -> Result<String, SignatureError> {
+        let signature = Signature::decode(signature)?;
+        for key in &self.keys {
+            match key.verify(data, &signature, true) {
+                Ok(()) => return Ok(signature.trusted_comment().to_string()),
+                Err(MinisignError::UnexpectedKeyId) => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Err(SignatureError::UntrustedKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::{SignatureError, SignaturePolicy, TrustedKeys, signature_path};
+
+    const PUBLIC_KEY: &str = "RWQBAgMEBQYHCBl/ayPhbIUyxqvIOPrNXqeJvgx2spIDNAOb+os9No1h";
+    const SIGNATURE: &str = "untrusted comment: signature from chewing-cli secret key
+RUQBAgMEBQYHCDAy5j9oKHDMveG1lNUPQUmnpBtD59knXNlZR8mJS/WKhh4jGwn4hrViTPQsWWVI+to0EQEa7nx73yGi5xIVhww=
+trusted comment: timestamp:1760000000\tfile:tsi.dat\thashed
+JJpA11rD9mHdL8I9LgVwlP8MfPKAJEY9MIFger6FPcwpRQwCEKIUMIqxXX6GZ1kw/p+hSFYUimQRHW2qAXCwDA==
+";
+
+    #[test]
+    fn verify_signature() {
+        let mut keys = TrustedKeys::new();
+        keys.add(include_str!("../../rsign.pub")).unwrap();
+        assert!(matches!(
+            keys.verify_bytes(b"dictionary", SIGNATURE),
+            Err(SignatureError::UntrustedKey)
+        ));
+        keys.add(PUBLIC_KEY).unwrap();
+        assert_eq!(
+            "timestamp:1760000000\tfile:tsi.dat\thashed",
+            keys.verify_bytes(b"dictionary", SIGNATURE).unwrap()
+        );
+        assert!(matches!(
+            keys.verify_bytes(b"dictionarx", SIGNATURE),
+            Err(SignatureError::InvalidSignature)
+        ));
+        assert!(matches!(
+            keys.verify_bytes(b"dictionary", "not a signature"),
+            Err(SignatureError::InvalidEncoding)
+        ));
+    }
+
+    #[test]
+    fn verify_detached_signature_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tsi.dat");
+        fs::write(&path, b"dictionary").unwrap();
+        let keys_dir = dir.path().join("keys.d");
+        fs::create_dir(&keys_dir).unwrap();
+        fs::write(keys_dir.join("test.pub"), PUBLIC_KEY).unwrap();
+        let mut keys = TrustedKeys::new();
+        keys.add_dir(&keys_dir).unwrap();
+        assert_eq!(1, keys.len());
+
+        assert!(matches!(
+            keys.verify(&path),
+            Err(SignatureError::IoError(_))
+        ));
+        fs::write(signature_path(&path), SIGNATURE).unwrap();
+        assert!(keys.verify(&path).is_ok());
+    }
+
+    #[test]
+    fn parse_policy() {
+        assert_eq!(Ok(SignaturePolicy::Off), "off".parse());
+        assert_eq!(Ok(SignaturePolicy::Warn), "Warn".parse());
+        assert_eq!(Ok(SignaturePolicy::Enforce), "enforce".parse());
+        assert_eq!(Err(()), "strict".parse::<SignaturePolicy>());
+    }
+}
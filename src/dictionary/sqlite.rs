@@ -6,6 +6,7 @@ use std::{
     str,
 };
 
+use log::error;
 use rusqlite::{Connection, Error as RusqliteError, OpenFlags, OptionalExtension, params};
 
 use crate::zhuyin::{Syllable, SyllableSlice};
@@ -246,10 +247,7 @@ impl SqliteDictionary {
                         userphrase_id
                     ) VALUES (?, ?, ?, ?)",
                 )?;
-                let mut syllables_bytes = vec![];
-                item.0
-                    .into_iter()
-                    .for_each(|syl| syllables_bytes.extend_from_slice(&syl.to_u16().to_le_bytes()));
+                let syllables_bytes = item.0.to_bytes();
                 stmt.execute(params![syllables_bytes, item.1, item.2, row_id])?;
             }
             tx.execute(
@@ -332,6 +330,25 @@ impl Dictionary for SqliteDictionary {
         .collect()
     }
 
+    fn has_prefix(&self, syllables: &dyn SyllableSlice, strategy: LookupStrategy) -> bool {
+        let _ = strategy;
+        let syllables_bytes = syllables.to_bytes();
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT syllables FROM dictionary_v1
+                WHERE syllables >= ?
+                ORDER BY syllables ASC
+                LIMIT 1",
+            )
+            .expect("SQL error");
+        // Blobs sharing a prefix sort next to each other
+        stmt.query_row([&syllables_bytes], |row| row.get::<_, Vec<u8>>(0))
+            .optional()
+            .expect("SQL error")
+            .is_some_and(|bytes| bytes.starts_with(&syllables_bytes))
+    }
+
     // FIXME too many clone
     fn entries(&self) -> Entries<'_> {
         let mut stmt = self
@@ -345,23 +362,24 @@ impl Dictionary for SqliteDictionary {
             stmt.query_map([], |row| {
                 let (syllables_bytes, phrase, freq, time): (Vec<u8>, Box<str>, _, _) =
                     row.try_into()?;
-                let syllables = syllables_bytes
-                    .chunks_exact(2)
-                    .map(|bytes| {
-                        let mut u16_bytes = [0; 2];
-                        u16_bytes.copy_from_slice(bytes);
-                        let syl_u16 = u16::from_le_bytes(u16_bytes);
-                        Syllable::try_from(syl_u16).unwrap()
-                    })
-                    .collect::<Vec<_>>();
+                let units = syllables_bytes.chunks_exact(2).map(|bytes| {
+                    let mut u16_bytes = [0; 2];
+                    u16_bytes.copy_from_slice(bytes);
+                    u16::from_le_bytes(u16_bytes)
+                });
+                let Ok(syllables) = Syllable::decode_u16(units).collect::<Result<Vec<_>, _>>()
+                else {
+                    error!("[!] invalid syllables in {phrase}, skipping entry.");
+                    return Ok(None);
+                };
                 let mut phrase = Phrase::new(phrase, freq);
                 if let Some(last_used) = time {
                     phrase = phrase.with_time(last_used);
                 }
-                Ok((syllables, phrase))
+                Ok(Some((syllables, phrase)))
             })
             .unwrap()
-            .map(|r| r.unwrap())
+            .filter_map(|r| r.unwrap())
             .collect::<Vec<_>>()
             .into_iter(),
         )
@@ -708,4 +726,25 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn has_prefix() -> Result<(), Box<dyn Error>> {
+        let mut dict = SqliteDictionary::open_in_memory()?;
+        dict.add_phrase(
+            &[
+                syl![Bopomofo::C, Bopomofo::E, Bopomofo::TONE4],
+                syl![Bopomofo::SH, Bopomofo::TONE4],
+            ],
+            ("測試", 9318).into(),
+        )?;
+        assert!(dict.has_prefix(
+            &[syl![Bopomofo::C, Bopomofo::E, Bopomofo::TONE4]],
+            LookupStrategy::Standard
+        ));
+        assert!(!dict.has_prefix(
+            &[syl![Bopomofo::SH, Bopomofo::TONE4]],
+            LookupStrategy::Standard
+        ));
+        Ok(())
+    }
 }
@@ -0,0 +1,171 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::zhuyin::{Syllable, valid_syllables};
+
+use super::Dictionary;
+
+/// Number of buckets of the frequency histogram.
+const FREQUENCY_BUCKETS: usize = 11;
+
+/// Statistics of the entries of a dictionary, computed by [`statistics`].
+///
+/// The node counts describe the trie that indexes the entries by syllables,
+/// the same as [`TrieStatistics`][super::TrieStatistics], so they can be
+/// compared across dictionary formats.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DictionaryStatistics {
+    /// The number of nodes, including the root and the leaf nodes.
+    pub node_count: usize,
+    /// The number of leaf nodes (phrases with same syllables).
+    pub leaf_count: usize,
+    /// The number of phrases.
+    pub phrase_count: usize,
+    /// The average number of children of the internal nodes.
+    pub avg_children: f64,
+    /// The number of phrases of each length in characters.
+    pub phrase_lengths: BTreeMap<usize, usize>,
+    /// The number of distinct syllables, including tones.
+    pub syllable_count: usize,
+    /// The number of valid Mandarin syllables, ignoring tones, that are
+    /// used by at least one phrase.
+    pub covered_syllables: usize,
+    /// The number of phrases in each frequency bucket.
+    ///
+    /// The first bucket counts the phrases with frequency 0 and bucket `n`
+    /// counts the frequencies from 10<sup>n-1</sup> to 10<sup>n</sup> - 1.
+    pub frequencies: [usize; FREQUENCY_BUCKETS],
+    /// The highest frequency.
+    pub max_frequency: u32,
+}
+
+impl DictionaryStatistics {
+    /// Returns the share of the valid Mandarin syllables used by the
+    /// dictionary, from 0 to 1.
+    pub fn syllable_coverage(&self) -> f64 {
+        self.covered_syllables as f64 / valid_syllables().len() as f64
+    }
+}
+
+/// Returns the bucket of the frequency histogram.
+fn frequency_bucket(freq: u32) -> usize {
+    match freq {
+        0 => 0,
+        _ => freq.ilog10() as usize + 1,
+    }
+}
+
+/// Calculates the statistics of the entries of any dictionary.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::{syl, zhuyin::Bopomofo::*};
+/// use chewing::dictionary::{TrieBuf, statistics};
+///
+/// let dict = TrieBuf::from([
+///     (vec![syl![C, E, TONE4]], vec![("測", 0), ("策", 0)]),
+///     (vec![syl![C, E, TONE4], syl![SH, TONE4]], vec![("測試", 100)]),
+/// ]);
+/// let stats = statistics(&dict);
+/// assert_eq!(3, stats.phrase_count);
+/// assert_eq!(2, stats.leaf_count);
+/// assert_eq!(2, stats.covered_syllables);
+/// assert_eq!(Some(&1), stats.phrase_lengths.get(&2));
+/// ```
+pub fn statistics(dict: &dyn Dictionary) -> DictionaryStatistics {
+    let mut stats = DictionaryStatistics::default();
+    let mut leaves: BTreeSet<Vec<Syllable>> = BTreeSet::new();
+    let mut syllables = HashSet::new();
+    for (entry_syllables, phrase) in dict.entries() {
+        stats.phrase_count += 1;
+        *stats
+            .phrase_lengths
+            .entry(phrase.as_str().chars().count())
+            .or_default() += 1;
+        stats.frequencies[frequency_bucket(phrase.freq())] += 1;
+        stats.max_frequency = stats.max_frequency.max(phrase.freq());
+        syllables.extend(entry_syllables.iter().copied());
+        leaves.insert(entry_syllables);
+    }
+
+    // Every distinct prefix of the syllables is an internal node, and every
+    // distinct syllables has a leaf node below its last internal node.
+    let mut prefixes: HashSet<&[Syllable]> = HashSet::new();
+    for leaf in &leaves {
+        for len in 1..=leaf.len() {
+            prefixes.insert(&leaf[..len]);
+        }
+    }
+    let internal_count = prefixes.len() + 1;
+    stats.leaf_count = leaves.len();
+    stats.node_count = internal_count + stats.leaf_count;
+    stats.avg_children = (stats.node_count - 1) as f64 / internal_count as f64;
+
+    stats.syllable_count = syllables.len();
+    stats.covered_syllables = syllables
+        .into_iter()
+        .filter(|syl| syl.is_valid())
+        .map(|mut syl| {
+            syl.remove_tone();
+            syl
+        })
+        .collect::<HashSet<_>>()
+        .len();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dictionary::{DictionaryBuilder, TrieBuf, TrieBuilder},
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::{frequency_bucket, statistics};
+
+    #[test]
+    fn frequency_buckets() {
+        assert_eq!(0, frequency_bucket(0));
+        assert_eq!(1, frequency_bucket(9));
+        assert_eq!(2, frequency_bucket(10));
+        assert_eq!(10, frequency_bucket(u32::MAX));
+    }
+
+    #[test]
+    fn node_counts_match_trie_builder() {
+        let entries = [
+            (vec![syl![G, U, O, TONE2]], ("國", 0)),
+            (vec![syl![M, I, EN, TONE2]], ("民", 0)),
+            (vec![syl![D, A, TONE4]], ("大", 0)),
+            (vec![syl![H, U, EI, TONE4]], ("會", 0)),
+            (
+                vec![syl![G, U, O, TONE2], syl![M, I, EN, TONE2]],
+                ("國民", 1),
+            ),
+            (
+                vec![
+                    syl![G, U, O, TONE2],
+                    syl![M, I, EN, TONE2],
+                    syl![D, A, TONE4],
+                    syl![H, U, EI, TONE4],
+                ],
+                ("國民大會", 10),
+            ),
+        ];
+        let mut builder = TrieBuilder::new();
+        for (syllables, phrase) in &entries {
+            builder.insert(syllables, (*phrase).into()).unwrap();
+        }
+        let dict = TrieBuf::from(entries.map(|(syllables, phrase)| (syllables, vec![phrase])));
+        let expected = builder.statistics();
+        let stats = statistics(&dict);
+        assert_eq!(expected.node_count, stats.node_count);
+        assert_eq!(expected.leaf_count, stats.leaf_count);
+        assert_eq!(expected.phrase_count, stats.phrase_count);
+        assert_eq!(4, stats.covered_syllables);
+        assert_eq!(13.0 / 8.0, stats.avg_children);
+        assert_eq!([4, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0], stats.frequencies);
+        assert_eq!(10, stats.max_frequency);
+    }
+}
@@ -3,7 +3,7 @@ use std::{
     cmp::Ordering,
     collections::VecDeque,
     error::Error,
-    fmt::Debug,
+    fmt::{Debug, Display},
     fs::{self, File},
     io::{self, BufWriter, Read, Write},
     iter,
@@ -13,8 +13,8 @@ use std::{
 };
 
 use der::{
-    DecodeValue, Document, Encode, EncodeValue, ErrorKind, FixedTag, Length, Reader, Sequence,
-    SliceReader, Tag, TagMode, TagNumber, Tagged, Writer,
+    Decode, DecodeValue, Document, Encode, EncodeValue, ErrorKind, FixedTag, Length, Reader,
+    Sequence, SliceReader, Tag, TagMode, TagNumber, Tagged, Writer,
     asn1::{ContextSpecificRef, OctetStringRef, Utf8StringRef},
 };
 use log::{error, warn};
@@ -117,7 +117,7 @@ pub struct Trie {
 }
 
 fn io_error(e: impl Into<Box<dyn Error + Send + Sync>>) -> io::Error {
-    io::Error::new(io::ErrorKind::Other, e)
+    io::Error::other(e)
 }
 
 impl Trie {
@@ -200,6 +200,16 @@ impl TrieOpenOptions {
     {
         let mut buf = vec![];
         stream.read_to_end(&mut buf)?;
+        self.decode(buf)
+    }
+    /// Creates the dictionary from the content already read from the file
+    /// at the path.
+    pub(crate) fn read_bytes(&self, path: PathBuf, buf: Vec<u8>) -> io::Result<Trie> {
+        let mut trie = self.decode(buf)?;
+        trie.path = Some(path);
+        Ok(trie)
+    }
+    fn decode(&self, buf: Vec<u8>) -> io::Result<Trie> {
         let trie_dict_doc = Document::try_from(buf).map_err(io_error)?;
         let trie_ref: TrieFileRef<'_> = trie_dict_doc.decode_msg().map_err(io_error)?;
         let info = trie_ref.info.into();
@@ -215,6 +225,306 @@ impl TrieOpenOptions {
     }
 }
 
+/// A problem found in a trie dictionary file.
+///
+/// Nodes are identified by their position in the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieCorruption {
+    /// The document is not valid DER or does not follow the format spec.
+    InvalidDocument(String),
+    /// The index size is not a multiple of the node size.
+    IndexSize(usize),
+    /// The children of the node are out of the index or not after the node.
+    NodeOutOfRange(usize),
+    /// The phrases of the leaf node are out of the phrase data.
+    LeafOutOfRange(usize),
+    /// The children of the node are not sorted by syllable.
+    UnsortedSyllables(usize),
+    /// The syllables leading to the leaf node cannot be decoded.
+    InvalidSyllables(usize),
+    /// The phrase record at the offset of the phrase data is invalid.
+    InvalidPhrase(usize),
+}
+
+impl Display for TrieCorruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrieCorruption::InvalidDocument(error) => write!(f, "invalid document: {error}"),
+            TrieCorruption::IndexSize(size) => {
+                write!(f, "index size {size} is not a multiple of the node size")
+            }
+            TrieCorruption::NodeOutOfRange(node) => {
+                write!(f, "children of node {node} are out of range")
+            }
+            TrieCorruption::LeafOutOfRange(node) => {
+                write!(f, "phrases of leaf node {node} are out of range")
+            }
+            TrieCorruption::UnsortedSyllables(node) => {
+                write!(f, "children of node {node} are not sorted by syllable")
+            }
+            TrieCorruption::InvalidSyllables(node) => {
+                write!(f, "syllables of leaf node {node} are invalid")
+            }
+            TrieCorruption::InvalidPhrase(offset) => {
+                write!(f, "phrase record at offset {offset} is invalid")
+            }
+        }
+    }
+}
+
+/// The result of checking a trie dictionary file with [`Trie::salvage`].
+#[derive(Debug, Clone, Default)]
+pub struct TrieSalvage {
+    /// The dictionary info, or the default info if it cannot be read.
+    pub info: DictionaryInfo,
+    /// Every entry that can be read.
+    pub entries: Vec<(Vec<Syllable>, Phrase)>,
+    /// The problems found in the file.
+    pub problems: Vec<TrieCorruption>,
+}
+
+/// Reads the next DER value and returns its tag, the bytes of the whole
+/// value, and its content that is present in the bytes.
+fn read_partial_tlv(bytes: &[u8]) -> Option<(Tag, &[u8], &[u8])> {
+    let mut reader = SliceReader::new(bytes).ok()?;
+    let header = der::Header::decode(&mut reader).ok()?;
+    let begin = usize::try_from(reader.position()).ok()?;
+    let end = begin
+        .saturating_add(usize::try_from(header.length).ok()?)
+        .min(bytes.len());
+    Some((header.tag, &bytes[..end], &bytes[begin..end]))
+}
+
+/// Reads the info, index, and phrase data of a possibly truncated document.
+fn read_partial_document(bytes: &[u8]) -> Option<(Option<DictionaryInfo>, &[u8], &[u8])> {
+    let (_, _, mut rest) = read_partial_tlv(bytes)?;
+    let mut next = |tag: Tag| {
+        let (actual, tlv, content) = read_partial_tlv(rest)?;
+        rest = &rest[tlv.len()..];
+        (actual == tag).then_some((tlv, content))
+    };
+    let (_, magic) = next(Tag::Utf8String)?;
+    if magic != b"CHEW" {
+        return None;
+    }
+    next(Tag::Integer)?;
+    let info = next(Tag::Sequence)?.0;
+    let info = SliceReader::new(info)
+        .and_then(|mut reader| reader.decode::<DictionaryInfoRef<'_>>())
+        .ok()
+        .map(DictionaryInfo::from);
+    let (_, index) = next(Tag::OctetString)?;
+    let data = next(Tag::Sequence).map_or(&[][..], |(_, data)| data);
+    Some((info, index, data))
+}
+
+/// Walks the index from the root, collecting the entries that can be read
+/// and the problems found.
+fn walk_index(index: &[u8], data: &[u8], salvage: &mut TrieSalvage) {
+    const SIZE: usize = TrieNodeView::SIZE;
+    if index.len() % SIZE != 0 {
+        salvage
+            .problems
+            .push(TrieCorruption::IndexSize(index.len()));
+    }
+    let node_count = index.len() / SIZE;
+    if node_count == 0 {
+        salvage.problems.push(TrieCorruption::NodeOutOfRange(0));
+        return;
+    }
+    let mut visited = vec![false; node_count];
+    let mut stack: Vec<(usize, Vec<u16>)> = vec![(0, vec![])];
+    while let Some((id, units)) = stack.pop() {
+        let node = TrieNodeView(&index[id * SIZE..(id + 1) * SIZE]);
+        let begin = node.child_begin() / SIZE;
+        let end = node.child_end() / SIZE;
+        if begin == end && id == 0 {
+            // An empty dictionary
+            continue;
+        }
+        if begin <= id || begin >= end || end > node_count {
+            salvage.problems.push(TrieCorruption::NodeOutOfRange(id));
+            if begin <= id {
+                continue;
+            }
+        }
+        let mut last_unit = None;
+        let mut sorted = true;
+        for child_id in begin..end.min(node_count) {
+            if visited[child_id] {
+                salvage.problems.push(TrieCorruption::NodeOutOfRange(id));
+                break;
+            }
+            visited[child_id] = true;
+            let bytes = &index[child_id * SIZE..(child_id + 1) * SIZE];
+            let unit = TrieNodeView(bytes).syllable();
+            if unit == 0 && child_id == begin {
+                read_leaf(child_id, TrieLeafView(bytes), &units, data, salvage);
+                continue;
+            }
+            if last_unit.is_some_and(|last| last >= unit) {
+                sorted = false;
+            }
+            last_unit = Some(unit);
+            let mut child_units = units.clone();
+            child_units.push(unit);
+            stack.push((child_id, child_units));
+        }
+        if !sorted {
+            salvage.problems.push(TrieCorruption::UnsortedSyllables(id));
+        }
+    }
+}
+
+fn read_leaf(
+    id: usize,
+    leaf: TrieLeafView<'_>,
+    units: &[u16],
+    data: &[u8],
+    salvage: &mut TrieSalvage,
+) {
+    let Ok(syllables) = Syllable::decode_u16(units.iter().copied()).collect::<Result<Vec<_>, _>>()
+    else {
+        salvage.problems.push(TrieCorruption::InvalidSyllables(id));
+        return;
+    };
+    let begin = leaf.data_begin();
+    let mut end = leaf.data_end();
+    if begin > end || end > data.len() {
+        salvage.problems.push(TrieCorruption::LeafOutOfRange(id));
+        // Only a leaf that starts inside the data can be read up to its end
+        if begin > end || begin >= data.len() {
+            return;
+        }
+        end = data.len();
+    }
+    let Ok(mut reader) = SliceReader::new(&data[begin..end]) else {
+        return;
+    };
+    while !reader.is_finished() {
+        let offset = begin + usize::try_from(reader.position()).unwrap_or_default();
+        match reader.decode::<Phrase>() {
+            Ok(phrase) if !phrase.as_str().is_empty() && !syllables.is_empty() => {
+                salvage.entries.push((syllables.clone(), phrase));
+            }
+            _ => {
+                salvage.problems.push(TrieCorruption::InvalidPhrase(offset));
+                return;
+            }
+        }
+    }
+}
+
+/// Sizes in bytes of the sections of a trie dictionary file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrieSectionSizes {
+    /// The magic number, the format version, and the DER headers.
+    pub header: usize,
+    /// The dictionary info.
+    pub info: usize,
+    /// The trie index nodes.
+    pub index: usize,
+    /// The phrase records.
+    pub phrases: usize,
+}
+
+impl TrieSectionSizes {
+    /// Returns the size of the file.
+    pub fn total(&self) -> usize {
+        self.header + self.info + self.index + self.phrases
+    }
+}
+
+impl Trie {
+    /// Returns the size of each section of the dictionary file.
+    ///
+    /// DER encoding is canonical, so the sizes are the same as the file the
+    /// dictionary was read from.
+    pub fn section_sizes(&self) -> TrieSectionSizes {
+        // OK to unwrap. The sections were decoded from a valid document.
+        let size = |len: der::Result<Length>| usize::try_from(len.unwrap()).unwrap();
+        let info = DictionaryInfoRef::new(&self.info);
+        let index = OctetStringRef::new(&self.index).unwrap();
+        let phrase_seq = PhraseSeqRef {
+            der_bytes: &self.phrase_seq,
+        };
+        let info_size = size(info.encoded_len());
+        let index_size = size(index.encoded_len());
+        let phrases_size = size(phrase_seq.encoded_len());
+        let total = size(
+            TrieFileRef {
+                info,
+                index,
+                phrase_seq,
+            }
+            .encoded_len(),
+        );
+        TrieSectionSizes {
+            header: total - info_size - index_size - phrases_size,
+            info: info_size,
+            index: index_size,
+            phrases: phrases_size,
+        }
+    }
+    /// Checks the integrity of a trie dictionary file.
+    ///
+    /// Checks the DER structure, that node and leaf offsets are in range,
+    /// that syllables are sorted, and that phrase records are valid. Returns
+    /// the problems found, or an empty vector if the file is intact.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use chewing::{syl, zhuyin::Bopomofo::*};
+    /// use chewing::dictionary::{DictionaryBuilder, Trie, TrieBuilder};
+    ///
+    /// let mut builder = TrieBuilder::new();
+    /// builder.insert(&[syl![C, E, TONE4]], ("測", 100).into())?;
+    /// let mut bytes = vec![];
+    /// builder.write(&mut bytes)?;
+    ///
+    /// assert!(Trie::verify(bytes.as_slice())?.is_empty());
+    /// bytes.truncate(bytes.len() - 2);
+    /// assert!(!Trie::verify(bytes.as_slice())?.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify<T>(stream: T) -> io::Result<Vec<TrieCorruption>>
+    where
+        T: Read,
+    {
+        Ok(Trie::salvage(stream)?.problems)
+    }
+    /// Recovers every readable entry of a possibly corrupted trie dictionary
+    /// file.
+    ///
+    /// The entries can be written to a fresh dictionary with a
+    /// [`DictionaryBuilder`]. Only errors reading the stream are returned as
+    /// errors.
+    pub fn salvage<T>(mut stream: T) -> io::Result<TrieSalvage>
+    where
+        T: Read,
+    {
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf)?;
+        let mut salvage = TrieSalvage::default();
+        if let Err(error) = Document::try_from(buf.as_slice())
+            .and_then(|doc| doc.decode_msg::<TrieFileRef<'_>>().map(|_| ()))
+        {
+            salvage
+                .problems
+                .push(TrieCorruption::InvalidDocument(error.to_string()));
+        }
+        let Some((info, index, data)) = read_partial_document(&buf) else {
+            return Ok(salvage);
+        };
+        salvage.info = info.unwrap_or_default();
+        walk_index(index, data, &mut salvage);
+        Ok(salvage)
+    }
+}
+
 struct PhrasesIter<'a> {
     reader: SliceReader<'a>,
 }
@@ -257,15 +567,14 @@ macro_rules! iter_bail_if_oob {
     };
 }
 
-impl Dictionary for Trie {
-    fn lookup_first_n_phrases(
+impl Trie {
+    /// Returns the nodes reached by the syllables from the root.
+    fn find_nodes(
         &self,
         syllables: &dyn SyllableSlice,
-        first: usize,
         strategy: LookupStrategy,
-    ) -> Vec<Phrase> {
+    ) -> Vec<TrieNodeView<'_>> {
         let dict = self.index.as_ref();
-        let data = self.phrase_seq.as_ref();
 
         bail_if_oob!(0, TrieNodeView::SIZE, dict.len());
         let root = TrieNodeView(&dict[..TrieNodeView::SIZE]);
@@ -277,24 +586,14 @@ impl Dictionary for Trie {
         }
 
         let search_predicate = match strategy {
-            LookupStrategy::Standard => |n: u16, syl: &Syllable| n == syl.to_u16(),
-            LookupStrategy::FuzzyPartialPrefix => |n: u16, syl: &Syllable| {
-                if n == 0 {
-                    return false;
-                }
-                if let Ok(syllable) = Syllable::try_from(n) {
-                    syllable.starts_with(*syl)
-                } else {
-                    false
-                }
-            },
+            LookupStrategy::Standard => |n: Syllable, syl: &Syllable| n == *syl,
+            LookupStrategy::FuzzyPartialPrefix => |n: Syllable, syl: &Syllable| n.starts_with(*syl),
         };
 
         // Perform a BFS search to find all leaf nodes
         let mut threads: VecDeque<TrieNodeView<'_>> = VecDeque::new();
         threads.push_back(root);
         for syl in syllables.to_slice().iter() {
-            debug_assert!(syl.to_u16() != 0);
             for _ in 0..threads.len() {
                 let node = threads.pop_front().unwrap();
                 bail_if_oob!(node.child_begin(), node.child_end(), dict.len());
@@ -302,8 +601,26 @@ impl Dictionary for Trie {
                     .chunks_exact(TrieNodeView::SIZE)
                     .map(TrieNodeView);
                 for n in child_nodes {
-                    if search_predicate(n.syllable(), syl) {
-                        threads.push_back(n);
+                    let unit = n.syllable();
+                    if unit == 0 {
+                        continue;
+                    }
+                    if !Syllable::is_lead_u16(unit) {
+                        if Syllable::try_from(unit).is_ok_and(|n| search_predicate(n, syl)) {
+                            threads.push_back(n);
+                        }
+                        continue;
+                    }
+                    // Extended syllables span a leading and a trailing node.
+                    bail_if_oob!(n.child_begin(), n.child_end(), dict.len());
+                    let trail_nodes = dict[n.child_begin()..n.child_end()]
+                        .chunks_exact(TrieNodeView::SIZE)
+                        .map(TrieNodeView);
+                    for t in trail_nodes {
+                        let decoded = Syllable::decode_u16([unit, t.syllable()]).next();
+                        if decoded.is_some_and(|n| n.is_ok_and(|n| search_predicate(n, syl))) {
+                            threads.push_back(t);
+                        }
                     }
                 }
             }
@@ -311,6 +628,20 @@ impl Dictionary for Trie {
                 return vec![];
             }
         }
+        threads.into()
+    }
+}
+
+impl Dictionary for Trie {
+    fn lookup_first_n_phrases(
+        &self,
+        syllables: &dyn SyllableSlice,
+        first: usize,
+        strategy: LookupStrategy,
+    ) -> Vec<Phrase> {
+        let dict = self.index.as_ref();
+        let data = self.phrase_seq.as_ref();
+        let threads = self.find_nodes(syllables, strategy);
 
         // Collect result from all threads
         let mut result = vec![];
@@ -332,6 +663,10 @@ impl Dictionary for Trie {
         result
     }
 
+    fn has_prefix(&self, syllables: &dyn SyllableSlice, strategy: LookupStrategy) -> bool {
+        !self.find_nodes(syllables, strategy).is_empty()
+    }
+
     fn entries(&self) -> Entries<'_> {
         let dict = self.index.as_ref();
         let data = self.phrase_seq.as_ref();
@@ -351,12 +686,14 @@ impl Dictionary for Trie {
         let make_dict_entry =
             |syllables: &[u16], leaf: &TrieLeafView<'_>| -> (Vec<Syllable>, Vec<Phrase>) {
                 debug_assert_eq!(leaf.reserved_zero(), 0);
+                let Ok(syllables) =
+                    Syllable::decode_u16(syllables.iter().copied()).collect::<Result<Vec<_>, _>>()
+                else {
+                    error!("[!] file corruption detected: invalid syllables, skipping entry.");
+                    return (vec![], vec![]);
+                };
                 (
-                    syllables
-                        .iter()
-                        // FIXME - skip invalid entry?
-                        .map(|&syl_u16| Syllable::try_from(syl_u16).unwrap())
-                        .collect::<Vec<_>>(),
+                    syllables,
                     PhrasesIter::new(&data[leaf.data_begin()..leaf.data_end()]).collect::<Vec<_>>(),
                 )
             };
@@ -761,7 +1098,9 @@ impl Writer for VecWriter {
 /// - **Child Len: 16 bits (u16)**
 ///     - The number of the child nodes.
 /// - **SyllableU16: 16 bits (u16)**
-///     - The [`Syllable`] encoded as an u16 integer.
+///     - The [`Syllable`] encoded as an u16 integer. Extended syllables are
+///       encoded as two code units, stored in two levels of internal nodes.
+///       See [`Syllable::encode_u16`].
 ///
 /// **Leaf node:**
 ///
@@ -805,7 +1144,7 @@ pub struct TrieBuilder {
 #[derive(Debug, PartialEq, Default)]
 struct TrieBuilderNode {
     id: usize,
-    syllable: Option<Syllable>,
+    syllable: Option<u16>,
     children: Vec<usize>,
     leaf_id: Option<NonZeroUsize>,
     phrases: Vec<Phrase>,
@@ -865,11 +1204,11 @@ impl TrieBuilder {
     }
 
     /// Allocates a new internal node and returns the new node id.
-    fn alloc_internal(&mut self, syl: Syllable) -> usize {
+    fn alloc_internal(&mut self, unit: u16) -> usize {
         let next_id = self.arena.len();
         let internal = TrieBuilderNode {
             id: next_id,
-            syllable: Some(syl),
+            syllable: Some(unit),
             ..Default::default()
         };
         self.arena.push(internal);
@@ -878,18 +1217,21 @@ impl TrieBuilder {
 
     /// Iterates through the syllables and insert all missing internal nodes.
     ///
+    /// Extended syllables are encoded in two u16 code units and use two
+    /// internal nodes.
+    ///
     /// Returns the id to the leaf node so that we can append the phrase to it.
     fn find_or_insert_internal(&mut self, syllables: &[Syllable]) -> usize {
         let mut node_id = 0;
-        'next: for &syl in syllables {
+        'next: for unit in syllables.to_u16_units() {
             for &child_node_id in &self.arena[node_id].children {
-                if self.arena[child_node_id].syllable == Some(syl) {
+                if self.arena[child_node_id].syllable == Some(unit) {
                     node_id = child_node_id;
                     continue 'next;
                 }
             }
             // We didn't find the child node so insert a new one
-            let next_id = self.alloc_internal(syl);
+            let next_id = self.alloc_internal(unit);
             self.arena[node_id].children.push(next_id);
             node_id = next_id;
         }
@@ -946,7 +1288,7 @@ impl TrieBuilder {
                 // An internal node has an associated syllable. The root node is
                 // a special case with no syllable.
                 if node.syllable.is_some() || id == ROOT_ID {
-                    let syllable_u16 = node.syllable.map_or(0, |v| v.to_u16());
+                    let syllable_u16 = node.syllable.unwrap_or(0);
                     let child_len =
                         node.children.len() + if node.leaf_id.is_some() { 1 } else { 0 };
                     dict_buf.write_all(&(child_begin as u32).to_be_bytes())?;
@@ -1205,7 +1547,9 @@ mod tests {
         zhuyin::Bopomofo,
     };
 
-    use super::{Trie, TrieBuilder};
+    use super::{
+        Trie, TrieBuilder, TrieCorruption, TrieLeafView, TrieNodeView, read_partial_document,
+    };
 
     #[test]
     fn test_tree_construction() -> Result<(), Box<dyn std::error::Error>> {
@@ -1235,14 +1579,14 @@ mod tests {
                 },
                 TrieBuilderNode {
                     id: 1,
-                    syllable: Some(syl![Bopomofo::C, Bopomofo::E, Bopomofo::TONE4]),
+                    syllable: Some(syl![Bopomofo::C, Bopomofo::E, Bopomofo::TONE4].to_u16()),
                     children: vec![2, 4],
                     leaf_id: None,
                     phrases: vec![]
                 },
                 TrieBuilderNode {
                     id: 2,
-                    syllable: Some(syl![Bopomofo::SH, Bopomofo::TONE4]),
+                    syllable: Some(syl![Bopomofo::SH, Bopomofo::TONE4].to_u16()),
                     children: vec![],
                     leaf_id: NonZeroUsize::new(3),
                     phrases: vec![]
@@ -1256,7 +1600,9 @@ mod tests {
                 },
                 TrieBuilderNode {
                     id: 4,
-                    syllable: Some(syl![Bopomofo::S, Bopomofo::U, Bopomofo::O, Bopomofo::TONE3]),
+                    syllable: Some(
+                        syl![Bopomofo::S, Bopomofo::U, Bopomofo::O, Bopomofo::TONE3].to_u16()
+                    ),
                     children: vec![],
                     leaf_id: NonZeroUsize::new(5),
                     phrases: vec![]
@@ -1696,4 +2042,163 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn tree_lookup_extended_syllables() -> Result<(), Box<dyn std::error::Error>> {
+        let chiah = syl![
+            Bopomofo::J,
+            Bopomofo::I,
+            Bopomofo::A,
+            Bopomofo::FINAL_H,
+            Bopomofo::TONE5
+        ];
+        let pa = syl![Bopomofo::B, Bopomofo::A, Bopomofo::TONE4];
+        let mut builder = TrieBuilder::new();
+        builder.insert(&[chiah, pa], ("食飽", 1).into())?;
+        builder.insert(&[chiah], ("食", 1).into())?;
+        builder.insert(&[pa], ("爸", 1).into())?;
+        let mut cursor = Cursor::new(vec![]);
+        builder.write(&mut cursor)?;
+        cursor.rewind()?;
+        let dict = Trie::new(&mut cursor)?;
+        assert_eq!(
+            vec![Phrase::new("食飽", 1)],
+            dict.lookup_all_phrases(&[chiah, pa], LookupStrategy::Standard)
+        );
+        assert_eq!(
+            vec![Phrase::new("食", 1)],
+            dict.lookup_all_phrases(
+                &[syl![Bopomofo::J, Bopomofo::I, Bopomofo::A]],
+                LookupStrategy::FuzzyPartialPrefix
+            )
+        );
+        assert_eq!(
+            vec![
+                (vec![pa], Phrase::new("爸", 1)),
+                (vec![chiah, pa], Phrase::new("食飽", 1)),
+                (vec![chiah], Phrase::new("食", 1)),
+            ],
+            dict.entries().collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    fn salvage_test_dict() -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut builder = TrieBuilder::new();
+        builder.insert(
+            &[syl![Bopomofo::C, Bopomofo::E, Bopomofo::TONE4]],
+            ("測", 1).into(),
+        )?;
+        builder.insert(
+            &[
+                syl![Bopomofo::C, Bopomofo::E, Bopomofo::TONE4],
+                syl![Bopomofo::SH, Bopomofo::TONE4],
+            ],
+            ("測試", 2).into(),
+        )?;
+        builder.insert(
+            &[
+                syl![Bopomofo::C, Bopomofo::E, Bopomofo::TONE4],
+                syl![Bopomofo::SH, Bopomofo::TONE4],
+                syl![Bopomofo::CH, Bopomofo::ENG, Bopomofo::TONE2],
+                syl![Bopomofo::G, Bopomofo::U, Bopomofo::ENG],
+            ],
+            ("測試成功", 3).into(),
+        )?;
+        let mut bytes = vec![];
+        builder.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    #[test]
+    fn section_sizes_match_file() -> Result<(), Box<dyn std::error::Error>> {
+        let bytes = salvage_test_dict()?;
+        let sizes = Trie::new(bytes.as_slice())?.section_sizes();
+        assert_eq!(bytes.len(), sizes.total());
+        assert!(sizes.index > 0 && sizes.phrases > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn verify_intact_dictionary() -> Result<(), Box<dyn std::error::Error>> {
+        let bytes = salvage_test_dict()?;
+        assert_eq!(
+            Vec::<TrieCorruption>::new(),
+            Trie::verify(bytes.as_slice())?
+        );
+        let salvage = Trie::salvage(bytes.as_slice())?;
+        let mut expected = Trie::new(bytes.as_slice())?.entries().collect::<Vec<_>>();
+        let mut entries = salvage.entries;
+        expected.sort();
+        entries.sort();
+        assert_eq!(expected, entries);
+        Ok(())
+    }
+
+    #[test]
+    fn salvage_truncated_dictionary() -> Result<(), Box<dyn std::error::Error>> {
+        let mut bytes = salvage_test_dict()?;
+        bytes.truncate(bytes.len() - 4);
+        assert!(Trie::new(bytes.as_slice()).is_err());
+        let salvage = Trie::salvage(bytes.as_slice())?;
+        assert!(matches!(
+            salvage.problems[0],
+            TrieCorruption::InvalidDocument(_)
+        ));
+        assert_eq!(2, salvage.entries.len());
+        Ok(())
+    }
+
+    /// Returns the offset and the id of the leaf whose phrases end last in
+    /// the data.
+    fn last_leaf(bytes: &[u8]) -> (usize, usize) {
+        let (_, index, _) = read_partial_document(bytes).unwrap();
+        let offset = index.as_ptr() as usize - bytes.as_ptr() as usize;
+        let id = (1..index.len() / TrieLeafView::SIZE)
+            .filter(|id| {
+                let leaf = &index[id * TrieLeafView::SIZE..(id + 1) * TrieLeafView::SIZE];
+                TrieNodeView(leaf).syllable() == 0
+            })
+            .max_by_key(|id| {
+                let leaf = &index[id * TrieLeafView::SIZE..(id + 1) * TrieLeafView::SIZE];
+                TrieLeafView(leaf).data_end()
+            })
+            .unwrap();
+        (offset + id * TrieLeafView::SIZE, id)
+    }
+
+    #[test]
+    fn salvage_leaf_end_out_of_range() -> Result<(), Box<dyn std::error::Error>> {
+        let mut bytes = salvage_test_dict()?;
+        let (offset, id) = last_leaf(&bytes);
+        bytes[offset + 4..offset + 6].copy_from_slice(&u16::MAX.to_be_bytes());
+        let salvage = Trie::salvage(bytes.as_slice())?;
+        assert_eq!(vec![TrieCorruption::LeafOutOfRange(id)], salvage.problems);
+        assert_eq!(3, salvage.entries.len());
+        Ok(())
+    }
+
+    #[test]
+    fn salvage_leaf_begin_out_of_range() -> Result<(), Box<dyn std::error::Error>> {
+        let mut bytes = salvage_test_dict()?;
+        let (offset, id) = last_leaf(&bytes);
+        bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let salvage = Trie::salvage(bytes.as_slice())?;
+        assert_eq!(vec![TrieCorruption::LeafOutOfRange(id)], salvage.problems);
+        assert_eq!(2, salvage.entries.len());
+        Ok(())
+    }
+
+    #[test]
+    fn verify_index_out_of_range() -> Result<(), Box<dyn std::error::Error>> {
+        let mut bytes = salvage_test_dict()?;
+        let (_, index, _) = read_partial_document(&bytes).unwrap();
+        let offset = index.as_ptr() as usize - bytes.as_ptr() as usize;
+        bytes[offset..offset + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            vec![TrieCorruption::NodeOutOfRange(0)],
+            Trie::verify(bytes.as_slice())?
+        );
+        Ok(())
+    }
 }
@@ -152,6 +152,26 @@ impl TrieBuf {
         phrases
     }
 
+    pub(crate) fn has_prefix(
+        &self,
+        syllables: &dyn SyllableSlice,
+        strategy: LookupStrategy,
+    ) -> bool {
+        let prefix = syllables.to_slice();
+        let min_key = (
+            Cow::from(prefix.clone().into_owned()),
+            Cow::from(MIN_PHRASE),
+        );
+        self.trie
+            .as_ref()
+            .is_some_and(|trie| trie.has_prefix(syllables, strategy))
+            || self
+                .btree
+                .range(min_key..)
+                .next()
+                .is_some_and(|(key, _)| key.0.starts_with(&prefix))
+    }
+
     pub(crate) fn entries(&self) -> Entries<'_> {
         Box::new(self.entries_iter())
     }
@@ -169,13 +189,13 @@ impl TrieBuf {
             return Err(UpdateDictionaryError { source: None });
         }
 
-        self.btree.insert(
-            (
-                Cow::from(syllable_slice.into_owned()),
-                Cow::from(phrase.phrase.into_string()),
-            ),
-            (phrase.freq, phrase.last_used.unwrap_or_default()),
+        let key = (
+            Cow::from(syllable_slice.into_owned()),
+            Cow::from(phrase.phrase.into_string()),
         );
+        self.graveyard.remove(&key);
+        self.btree
+            .insert(key, (phrase.freq, phrase.last_used.unwrap_or_default()));
         self.dirty = true;
 
         Ok(())
@@ -188,13 +208,12 @@ impl TrieBuf {
         user_freq: u32,
         time: u64,
     ) -> Result<(), UpdateDictionaryError> {
-        self.btree.insert(
-            (
-                Cow::from(syllables.to_slice().into_owned()),
-                Cow::from(phrase.phrase.into_string()),
-            ),
-            (user_freq, time),
+        let key = (
+            Cow::from(syllables.to_slice().into_owned()),
+            Cow::from(phrase.phrase.into_string()),
         );
+        self.graveyard.remove(&key);
+        self.btree.insert(key, (user_freq, time));
         self.dirty = true;
 
         Ok(())
@@ -307,6 +326,10 @@ impl Dictionary for TrieBuf {
         TrieBuf::lookup_first_n_phrases(self, syllables, first, strategy)
     }
 
+    fn has_prefix(&self, syllables: &dyn SyllableSlice, strategy: LookupStrategy) -> bool {
+        TrieBuf::has_prefix(self, syllables, strategy)
+    }
+
     fn entries(&self) -> Entries<'_> {
         TrieBuf::entries(self)
     }
@@ -464,4 +487,22 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn add_removed_phrase() -> Result<(), Box<dyn Error>> {
+        let mut dict = TrieBuf::new_in_memory();
+        let syllables = [syl![Z, TONE4], syl![D, I, AN, TONE3]];
+        dict.add_phrase(&syllables, ("dict", 1, 2).into())?;
+        dict.remove_phrase(&syllables, "dict")?;
+        assert_eq!(
+            None,
+            dict.lookup_first_phrase(&syllables, LookupStrategy::Standard)
+        );
+        dict.add_phrase(&syllables, ("dict", 3, 4).into())?;
+        assert_eq!(
+            Some(("dict", 3, 4).into()),
+            dict.lookup_first_phrase(&syllables, LookupStrategy::Standard)
+        );
+        Ok(())
+    }
 }
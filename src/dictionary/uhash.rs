@@ -177,7 +177,10 @@ mod tests {
         input.extend_from_slice(&1_u16.to_ne_bytes());
         input.push(1);
         input.extend_from_slice(b"P");
-        input.extend(iter::repeat(0).take(BIN_FIELD_SIZE - input.len() + 4 + size_of::<c_int>()));
+        input.extend(iter::repeat_n(
+            0,
+            BIN_FIELD_SIZE - input.len() + 4 + size_of::<c_int>(),
+        ));
         let phrases = try_load_bin(&input[..]).unwrap();
         assert_eq!(
             vec![(
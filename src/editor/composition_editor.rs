@@ -4,7 +4,11 @@ use std::cmp::min;
 
 use log::warn;
 
-use crate::conversion::{Composition, Gap, Interval, Symbol};
+use crate::{
+    conversion::{Composition, Gap, Interval, Symbol},
+    dictionary::{Dictionary, LookupStrategy},
+    zhuyin::{Bopomofo, Syllable},
+};
 
 /// TODO
 #[derive(Debug, Default, Clone)]
@@ -111,6 +115,40 @@ impl CompositionEditor {
         self.inner.insert(self.cursor, sym);
         self.cursor += 1;
     }
+    pub(crate) fn insert_readings(&mut self, readings: &[Syllable]) {
+        self.inner.insert_readings(self.cursor, readings);
+        self.cursor += 1;
+    }
+    /// Sets the syllable before the cursor to its reading in the tone.
+    ///
+    /// Returns false if the symbol before the cursor is not a syllable with a
+    /// reading in the tone.
+    pub(crate) fn amend_tone(&mut self, tone: Bopomofo) -> bool {
+        let Some(index) = self.cursor.checked_sub(1) else {
+            return false;
+        };
+        let Some(&reading) = self
+            .inner
+            .readings(index)
+            .iter()
+            .find(|syl| syl.tone().unwrap_or(Bopomofo::TONE1) == tone)
+        else {
+            return false;
+        };
+        self.inner.replace(index, Symbol::from(reading));
+        true
+    }
+    pub(crate) fn is_ambiguous(&self) -> bool {
+        self.inner.is_ambiguous(0, self.inner.len())
+    }
+    pub(crate) fn resolve_readings(
+        &mut self,
+        dict: &dyn Dictionary,
+        intervals: &[Interval],
+        strategy: LookupStrategy,
+    ) {
+        self.inner.resolve_readings(dict, intervals, strategy);
+    }
     pub(crate) fn insert_glue(&mut self) {
         if self.is_end_of_buffer() {
             warn!("cannot set glue at the end of buffer");
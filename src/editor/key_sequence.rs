@@ -0,0 +1,609 @@
+//! Key sequences that type a text
+//!
+//! Finds the key events that make the [`Editor`] produce a given text with a
+//! keyboard layout. The readings are resolved through the dictionaries and
+//! the conversion is checked by typing the keys into the editor, so the keys
+//! that select the right candidates are included when the default conversion
+//! is wrong.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+};
+
+use crate::{
+    conversion::{Annotator, Symbol, special_symbol_input},
+    dictionary::{Dictionary, Layered, LookupStrategy},
+    zhuyin::Syllable,
+};
+
+use super::{
+    BasicEditor, Editor, Selecting, Selector,
+    keyboard::{KeyCode, KeyEvent, KeyboardLayout, Modifiers},
+    known_readings,
+    zhuyin_layout::{HINT_KEYS, KeyBehavior, KeyboardLayoutCompat, SyllableEditor},
+};
+
+/// The most keys tried to type a single syllable.
+const MAX_SYLLABLE_KEYS: usize = 8;
+
+const SELECT_KEYS: [KeyCode; 10] = {
+    use KeyCode::*;
+    [N1, N2, N3, N4, N5, N6, N7, N8, N9, N0]
+};
+
+/// Errors when finding the keys to type a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySequenceError {
+    /// The character has no reading in the dictionaries and is not a symbol
+    /// that can be typed.
+    NoReading(char),
+    /// The syllable cannot be typed with the keyboard layout.
+    Untypable(Syllable),
+    /// The editor did not produce the expected text.
+    Mismatch {
+        /// The text that should be committed.
+        expected: String,
+        /// The text the editor produced.
+        actual: String,
+    },
+}
+
+impl Display for KeySequenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySequenceError::NoReading(ch) => write!(f, "no reading for character {ch}"),
+            KeySequenceError::Untypable(syl) => {
+                write!(f, "syllable {syl} cannot be typed with the layout")
+            }
+            KeySequenceError::Mismatch { expected, actual } => {
+                write!(f, "expected {expected} but the editor produced {actual}")
+            }
+        }
+    }
+}
+
+impl Error for KeySequenceError {}
+
+#[derive(Debug)]
+enum Reading {
+    Syllables(Vec<Syllable>),
+    Symbol(KeyEvent),
+}
+
+/// A part of the text that is typed as a unit.
+#[derive(Debug)]
+struct Segment {
+    text: String,
+    reading: Reading,
+}
+
+/// Returns the key that inserts the symbol.
+///
+/// Unshifted keys are only used if the layout does not take them as the
+/// start of a syllable.
+fn symbol_key(
+    keyboard: &dyn KeyboardLayout,
+    layout: &dyn SyllableEditor,
+    ch: char,
+) -> Option<KeyEvent> {
+    [Modifiers::shift(), Modifiers::default()]
+        .into_iter()
+        .flat_map(|modifiers| HINT_KEYS.map(|code| keyboard.map_with_mod(code, modifiers)))
+        .find(|&ev| {
+            special_symbol_input(ev.unicode) == Some(ch)
+                && (ev.modifiers.shift
+                    || SyllableEditor::clone(layout).key_press(ev) != KeyBehavior::Absorb)
+        })
+}
+
+/// Splits the text into phrases from the dictionary and symbols.
+fn segment(
+    dict: &dyn Dictionary,
+    keyboard: &dyn KeyboardLayout,
+    layout: &dyn SyllableEditor,
+    text: &str,
+) -> Result<Vec<Segment>, KeySequenceError> {
+    Annotator::for_text(dict, text)
+        .annotate(text)
+        .into_iter()
+        .map(|annotation| {
+            let reading = if annotation.has_reading() {
+                Reading::Syllables(annotation.syllables)
+            } else {
+                let ch = annotation
+                    .text
+                    .chars()
+                    .next()
+                    .expect("should have one char");
+                let key =
+                    symbol_key(keyboard, layout, ch).ok_or(KeySequenceError::NoReading(ch))?;
+                Reading::Symbol(key)
+            };
+            Ok(Segment {
+                text: annotation.text,
+                reading,
+            })
+        })
+        .collect()
+}
+
+/// Finds the shortest keys that type each of the syllables.
+///
+/// Searches the keys accepted by the layout breadth first until all
+/// syllables are found. A syllable is found if it is one of the readings the
+/// editor keeps for the keys. Among keys of the same length, the keys that
+/// type the letters of the syllable in order are preferred.
+fn find_syllable_keys(
+    dict: &Layered,
+    keyboard: &dyn KeyboardLayout,
+    layout: &dyn SyllableEditor,
+    syllables: &HashSet<Syllable>,
+) -> HashMap<Syllable, Vec<KeyCode>> {
+    let mut found = HashMap::new();
+    let mut seen = HashSet::new();
+    // Partial syllables as (keys out of order, editor, keys)
+    let mut level = vec![(0, SyllableEditor::clone(layout), vec![])];
+    for len in 1..=MAX_SYLLABLE_KEYS {
+        if level.is_empty() || syllables.iter().all(|syl| found.contains_key(syl)) {
+            break;
+        }
+        let mut next_level = vec![];
+        let mut commits = vec![];
+        for (disorder, editor, keys) in &level {
+            for code in editor.next_keys(keyboard, None) {
+                // These keys are handled by the editor before a syllable starts
+                if len == 1 && matches!(code, KeyCode::Space | KeyCode::Grave) {
+                    continue;
+                }
+                let mut next = SyllableEditor::clone(editor.as_ref());
+                let behavior = next.key_press(keyboard.map(code));
+                let disorder = disorder + usize::from(!next.read().starts_with(editor.read()));
+                let mut next_keys = keys.clone();
+                next_keys.push(code);
+                match behavior {
+                    KeyBehavior::Absorb => next_level.push((disorder, next, next_keys)),
+                    KeyBehavior::Commit if len > 1 => {
+                        for syl in known_readings(dict, next.as_ref(), LookupStrategy::Standard) {
+                            commits.push((disorder, syl, next_keys.clone()));
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        commits.sort_by_key(|&(disorder, _, _)| disorder);
+        for (_, syl, keys) in commits {
+            found.entry(syl).or_insert(keys);
+        }
+        next_level.sort_by_key(|&(disorder, _, _)| disorder);
+        next_level.retain(|(_, editor, _)| seen.insert((editor.read(), editor.key_seq())));
+        level = next_level;
+    }
+    found
+}
+
+impl Editor {
+    /// Returns the key events that type and commit the text with the
+    /// keyboard layout.
+    ///
+    /// The readings of the text are resolved through the dictionaries. The
+    /// keys are checked by typing them into this editor, and keys that select
+    /// the right candidates are added where the default conversion is wrong.
+    /// The enter key is pressed before the auto commit threshold is reached.
+    ///
+    /// The sequence assumes an empty editor with the current options. The
+    /// editor is cleared afterwards, the syllable editor is restored, and no
+    /// phrases are learned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chewing::{
+    ///     conversion::ChewingEngine,
+    ///     dictionary::{Layered, TrieBuf},
+    ///     editor::{AbbrevTable, Editor, LaxUserFreqEstimate, SymbolSelector},
+    ///     editor::{keyboard::KeyCode, zhuyin_layout::KeyboardLayoutCompat},
+    ///     syl,
+    ///     zhuyin::Bopomofo::*,
+    /// };
+    ///
+    /// let dict = TrieBuf::from([(vec![syl![C, E, TONE4]], vec![("冊", 100)])]);
+    /// let dict = Layered::new(vec![Box::new(dict)], Box::new(TrieBuf::new_in_memory()));
+    /// let mut editor = Editor::new(
+    ///     Box::new(ChewingEngine::new()),
+    ///     dict,
+    ///     LaxUserFreqEstimate::new(0),
+    ///     AbbrevTable::new(),
+    ///     SymbolSelector::default(),
+    /// );
+    ///
+    /// let keys = editor.key_sequence("冊", KeyboardLayoutCompat::Default)?;
+    /// let codes: Vec<_> = keys.iter().map(|ev| ev.code).collect();
+    /// assert_eq!(vec![KeyCode::H, KeyCode::K, KeyCode::N4, KeyCode::Enter], codes);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn key_sequence(
+        &mut self,
+        text: &str,
+        layout: KeyboardLayoutCompat,
+    ) -> Result<Vec<KeyEvent>, KeySequenceError> {
+        let keyboard = layout.keyboard();
+        let syllable_editor = layout.syllable_editor();
+        let segments = segment(&self.shared.dict, &keyboard, syllable_editor.as_ref(), text)?;
+        let syllables: Vec<Syllable> = segments
+            .iter()
+            .filter_map(|segment| match &segment.reading {
+                Reading::Syllables(syllables) => Some(syllables.iter().copied()),
+                Reading::Symbol(_) => None,
+            })
+            .flatten()
+            .collect();
+        let syllable_keys = find_syllable_keys(
+            &self.shared.dict,
+            &keyboard,
+            syllable_editor.as_ref(),
+            &syllables.iter().copied().collect(),
+        );
+        if let Some(&syl) = syllables
+            .iter()
+            .find(|syl| !syllable_keys.contains_key(syl))
+        {
+            return Err(KeySequenceError::Untypable(syl));
+        }
+
+        let options = self.shared.options;
+        let syl = self.shared.syl.clone();
+        self.clear();
+        self.shared.options.disable_auto_learn_phrase = true;
+        self.set_syllable_editor(syllable_editor);
+
+        let mut typist = Typist {
+            editor: self,
+            keyboard: &keyboard,
+            keys: vec![],
+        };
+        let result = typist.type_segments(&segments, &syllable_keys);
+        let keys = typist.keys;
+
+        self.clear();
+        self.shared.options = options;
+        self.shared.syl = syl;
+        result.map(|_| keys)
+    }
+
+    /// Converts the syllables and finds the key events that correct the
+    /// conversion to the expected text.
+    ///
+    /// The keys move the cursor and select candidates, like
+    /// [`key_sequence`][Editor::key_sequence] does after typing the
+    /// syllables. The final enter key is not included. The keys are typed
+    /// with the keyboard layout.
+    ///
+    /// The editor is cleared afterwards and no phrases are learned.
+    pub fn correct_conversion(
+        &mut self,
+        syllables: &[Syllable],
+        expected: &str,
+        layout: KeyboardLayoutCompat,
+    ) -> Correction {
+        let options = self.shared.options;
+        let syl = self.shared.syl.clone();
+        self.clear();
+        self.shared.options.disable_auto_learn_phrase = true;
+        self.shared.options.auto_commit_threshold =
+            options.auto_commit_threshold.max(syllables.len() + 1);
+        self.set_syllable_editor(layout.syllable_editor());
+        for &syl in syllables {
+            self.shared.com.insert(Symbol::from(syl));
+        }
+        let conversion = self.display();
+
+        let keyboard = layout.keyboard();
+        let mut typist = Typist {
+            editor: self,
+            keyboard: &keyboard,
+            keys: vec![],
+        };
+        let result = typist.fix_conversion(expected);
+        let keys = typist.keys;
+
+        self.clear();
+        self.shared.options = options;
+        self.shared.syl = syl;
+        Correction {
+            conversion,
+            keys: result.ok().map(|_| keys),
+        }
+    }
+}
+
+/// The conversion of syllables and the keys that correct it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    /// The text converted by the editor.
+    pub conversion: String,
+    /// The key events that correct the conversion, or `None` if the expected
+    /// text cannot be selected.
+    pub keys: Option<Vec<KeyEvent>>,
+}
+
+/// Types keys into the editor and records them.
+struct Typist<'a> {
+    editor: &'a mut Editor,
+    keyboard: &'a dyn KeyboardLayout,
+    keys: Vec<KeyEvent>,
+}
+
+impl Typist<'_> {
+    fn press(&mut self, code: KeyCode) {
+        self.press_event(self.keyboard.map(code));
+    }
+
+    fn press_event(&mut self, ev: KeyEvent) {
+        self.editor.process_keyevent(ev);
+        self.keys.push(ev);
+    }
+
+    fn type_segments(
+        &mut self,
+        segments: &[Segment],
+        syllable_keys: &HashMap<Syllable, Vec<KeyCode>>,
+    ) -> Result<(), KeySequenceError> {
+        let threshold = self.editor.shared.options.auto_commit_threshold.max(1);
+        let mut rest = segments;
+        while !rest.is_empty() {
+            // Commit before the editor commits by itself
+            let mut len = 0;
+            let count = rest
+                .iter()
+                .take_while(|segment| {
+                    len += segment.text.chars().count();
+                    len <= threshold
+                })
+                .count()
+                .max(1);
+            let (chunk, next) = rest.split_at(count);
+            rest = next;
+
+            for segment in chunk {
+                match &segment.reading {
+                    Reading::Syllables(syllables) => {
+                        for syl in syllables {
+                            for &code in &syllable_keys[syl] {
+                                self.press(code);
+                            }
+                        }
+                    }
+                    Reading::Symbol(ev) => self.press_event(*ev),
+                }
+            }
+            let expected: String = chunk.iter().map(|segment| segment.text.as_str()).collect();
+            self.fix_conversion(&expected)?;
+            self.press(KeyCode::Enter);
+            if self.editor.display_commit() != expected {
+                return Err(KeySequenceError::Mismatch {
+                    expected,
+                    actual: self.editor.display_commit().to_owned(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Selects candidates until the editor displays the expected text.
+    fn fix_conversion(&mut self, expected: &str) -> Result<(), KeySequenceError> {
+        let expected: Vec<char> = expected.chars().collect();
+        let mismatch = |editor: &Editor| KeySequenceError::Mismatch {
+            expected: expected.iter().collect(),
+            actual: editor.display(),
+        };
+        // Every selection fixes at least one more character
+        for _ in 0..=expected.len() {
+            let display: Vec<char> = self.editor.display().chars().collect();
+            if display.len() != expected.len() {
+                return Err(mismatch(self.editor));
+            }
+            let Some(pos) = (0..expected.len()).find(|&i| display[i] != expected[i]) else {
+                return Ok(());
+            };
+            self.move_cursor(pos);
+            if !self.select_phrase(&expected, pos) {
+                return Err(mismatch(self.editor));
+            }
+        }
+        Err(mismatch(self.editor))
+    }
+
+    fn move_cursor(&mut self, pos: usize) {
+        let cursor = self.editor.cursor();
+        if pos < cursor && pos + 1 < cursor - pos {
+            self.press(KeyCode::Home);
+            (0..pos).for_each(|_| self.press(KeyCode::Right));
+        } else if pos < cursor {
+            (pos..cursor).for_each(|_| self.press(KeyCode::Left));
+        } else {
+            (cursor..pos).for_each(|_| self.press(KeyCode::Right));
+        }
+    }
+
+    /// Selects a candidate that covers the position and matches the
+    /// expected text.
+    ///
+    /// Returns false if none of the candidates match.
+    fn select_phrase(&mut self, expected: &[char], pos: usize) -> bool {
+        self.press(KeyCode::Down);
+        let mut first = None;
+        loop {
+            let Some(Selecting {
+                page_no,
+                sel: Selector::Phrase(sel),
+                ..
+            }) = self.editor.state.as_any().downcast_ref::<Selecting>()
+            else {
+                return false;
+            };
+            let state = (*page_no, sel.begin(), self.editor.all_candidates().ok());
+            match &first {
+                // Down cycles through the pages and then shorter phrases
+                Some(first) if *first == state => break,
+                Some(_) => (),
+                None => first = Some(state),
+            }
+            let page = self.editor.paginated_candidates().unwrap_or_default();
+            let per_page = self.editor.shared.options.candidates_per_page;
+            let found = page
+                .iter()
+                .take(per_page.min(SELECT_KEYS.len()))
+                .position(|phrase| {
+                    let interval = sel.interval(phrase.as_str());
+                    interval.start <= pos
+                        && pos < interval.end
+                        && expected
+                            .get(interval.start..interval.end)
+                            .is_some_and(|text| text.iter().copied().eq(phrase.chars()))
+                });
+            match found {
+                Some(n) => {
+                    self.press(SELECT_KEYS[n]);
+                    return true;
+                }
+                None => self.press(KeyCode::Down),
+            }
+        }
+        self.press(KeyCode::Esc);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        conversion::ChewingEngine,
+        dictionary::{Layered, TrieBuf},
+        editor::{
+            AbbrevTable, BasicEditor, Editor, LaxUserFreqEstimate, SymbolSelector,
+            keyboard::{
+                AnyKeyboardLayout,
+                KeyCode::{self, Down, Home, N1},
+                KeyboardLayout,
+            },
+            zhuyin_layout::KeyboardLayoutCompat,
+        },
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::KeySequenceError;
+
+    fn editor() -> Editor {
+        let dict = TrieBuf::from([
+            (vec![syl![C, E, TONE4]], vec![("冊", 100), ("測", 200)]),
+            (vec![syl![SH, U]], vec![("書", 100)]),
+            (vec![syl![C, E, TONE4], syl![SH, U]], vec![("測書", 100)]),
+            (vec![syl![H, AU, TONE3]], vec![("好", 100)]),
+        ]);
+        let dict = Layered::new(vec![Box::new(dict)], Box::new(TrieBuf::new_in_memory()));
+        Editor::new(
+            Box::new(ChewingEngine::new()),
+            dict,
+            LaxUserFreqEstimate::new(0),
+            AbbrevTable::new(),
+            SymbolSelector::default(),
+        )
+    }
+
+    fn key_string(editor: &mut Editor, text: &str, layout: KeyboardLayoutCompat) -> String {
+        editor
+            .key_sequence(text, layout)
+            .unwrap()
+            .iter()
+            .map(|ev| format!("{:?} ", ev.code))
+            .collect()
+    }
+
+    #[test]
+    fn default_conversion() {
+        let mut editor = editor();
+        assert_eq!(
+            "H K N4 C L N3 Enter ",
+            key_string(&mut editor, "測好", KeyboardLayoutCompat::Default)
+        );
+    }
+
+    #[test]
+    fn select_wrong_conversion() {
+        let mut editor = editor();
+        assert_eq!(
+            "H K N4 G J Space Home Down Down N1 Enter ",
+            key_string(&mut editor, "冊書", KeyboardLayoutCompat::Default)
+        );
+        assert!(editor.is_empty());
+    }
+
+    #[test]
+    fn other_layouts() {
+        let mut editor = editor();
+        assert_eq!(
+            "C E N4 S H U N1 Enter ",
+            key_string(&mut editor, "測書", KeyboardLayoutCompat::HanyuPinyin)
+        );
+    }
+
+    #[test]
+    fn symbols() {
+        let mut editor = editor();
+        assert_eq!(
+            "C L N3 Comma Enter ",
+            key_string(&mut editor, "好，", KeyboardLayoutCompat::Default)
+        );
+    }
+
+    #[test]
+    fn correct_conversion() {
+        let mut editor = editor();
+        let syllables = [syl![C, E, TONE4], syl![SH, U]];
+        let correction =
+            editor.correct_conversion(&syllables, "測書", KeyboardLayoutCompat::Default);
+        assert_eq!("測書", correction.conversion);
+        assert_eq!(Some(vec![]), correction.keys);
+
+        let correction =
+            editor.correct_conversion(&syllables, "冊書", KeyboardLayoutCompat::Default);
+        assert_eq!("測書", correction.conversion);
+        let codes: Vec<_> = correction.keys.unwrap().iter().map(|ev| ev.code).collect();
+        assert_eq!(vec![Home, Down, Down, N1], codes);
+        assert!(editor.is_empty());
+
+        let correction = editor.correct_conversion(&syllables, "冊", KeyboardLayoutCompat::Default);
+        assert_eq!(None, correction.keys);
+    }
+
+    #[test]
+    fn correct_conversion_with_layout() {
+        let mut editor = editor();
+        let syllables = [syl![C, E, TONE4], syl![SH, U]];
+        let correction =
+            editor.correct_conversion(&syllables, "冊書", KeyboardLayoutCompat::Dvorak);
+        let dvorak = AnyKeyboardLayout::dvorak();
+        assert_eq!(
+            Some([Home, Down, Down, N1].map(|code| dvorak.map(code)).to_vec()),
+            correction.keys
+        );
+
+        // The syllable editor of the editor is kept
+        editor.correct_conversion(&syllables, "冊書", KeyboardLayoutCompat::Hsu);
+        editor.process_keyevent(AnyKeyboardLayout::qwerty().map(KeyCode::H));
+        assert_eq!(syl![C], editor.syllable_buffer());
+    }
+
+    #[test]
+    fn no_reading() {
+        let mut editor = editor();
+        assert_eq!(
+            Err(KeySequenceError::NoReading('字')),
+            editor.key_sequence("好字", KeyboardLayoutCompat::Default)
+        );
+    }
+}
@@ -3,6 +3,7 @@
 mod abbrev;
 mod composition_editor;
 mod estimate;
+mod key_sequence;
 pub mod keyboard;
 mod selection;
 pub mod zhuyin_layout;
@@ -16,6 +17,7 @@ use std::{
 
 pub use self::{abbrev::AbbrevTable, selection::symbol::SymbolSelector};
 pub use estimate::{LaxUserFreqEstimate, UserFreqEstimate};
+pub use key_sequence::{Correction, KeySequenceError};
 use log::{debug, info, trace, warn};
 
 use crate::{
@@ -25,7 +27,7 @@ use crate::{
     },
     dictionary::{
         Dictionary, DictionaryMut, Layered, LookupStrategy, SystemDictionaryLoader,
-        UpdateDictionaryError, UserDictionaryLoader,
+        UpdateDictionaryError, UserDictionaryLoader, UserDictionaryLock,
     },
     editor::keyboard::KeyCode,
     zhuyin::{Syllable, SyllableSlice},
@@ -33,9 +35,9 @@ use crate::{
 
 use self::{
     composition_editor::CompositionEditor,
-    keyboard::KeyEvent,
+    keyboard::{KeyEvent, KeyboardLayout},
     selection::{phrase::PhraseSelector, symbol::SpecialSymbolSelector},
-    zhuyin_layout::{KeyBehavior, Standard, SyllableEditor},
+    zhuyin_layout::{DirectInput, KeyBehavior, Standard, SyllableCheck, SyllableEditor},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -79,6 +81,7 @@ pub struct EditorOptions {
     pub lookup_strategy: LookupStrategy,
     pub conversion_engine: ConversionEngineKind,
     pub enable_fullwidth_toggle_key: bool,
+    pub syllable_check: SyllableCheck,
 }
 
 impl Default for EditorOptions {
@@ -99,6 +102,7 @@ impl Default for EditorOptions {
             // FIXME may be out of sync with the engine used
             conversion_engine: ConversionEngineKind::ChewingEngine,
             enable_fullwidth_toggle_key: true,
+            syllable_check: SyllableCheck::Off,
         }
     }
 }
@@ -150,6 +154,8 @@ pub enum EditorKeyBehavior {
 pub struct Editor {
     shared: SharedState,
     state: Box<dyn State>,
+    /// Marks the user dictionary as in use while the editor is alive
+    _lock: Option<UserDictionaryLock>,
 }
 
 /// All different errors that may happen when changing editor state.
@@ -204,15 +210,25 @@ impl Editor {
 
     pub fn new(
         conv: Box<dyn ConversionEngine>,
-        dict: Layered,
+        mut dict: Layered,
         estimate: LaxUserFreqEstimate,
         abbr: AbbrevTable,
         sym_sel: SymbolSelector,
     ) -> Editor {
+        let _lock =
+            dict.user_dict()
+                .path()
+                .and_then(|path| match UserDictionaryLock::acquire(path) {
+                    Ok(lock) => Some(lock),
+                    Err(error) => {
+                        warn!("Failed to lock {}: {error}", path.display());
+                        None
+                    }
+                });
         Editor {
             shared: SharedState {
                 com: CompositionEditor::default(),
-                syl: Box::new(Standard::new()),
+                syl: Box::new(DirectInput::new(Box::new(Standard::new()))),
                 conv,
                 dict,
                 abbr,
@@ -226,11 +242,19 @@ impl Editor {
                 notice_buffer: String::new(),
             },
             state: Box::new(Entering),
+            _lock,
         }
     }
 
+    /// Sets the layout used to build syllables.
+    ///
+    /// Bopomofo characters sent with [`KeyCode::Unknown`] are accepted
+    /// regardless of the layout.
     pub fn set_syllable_editor(&mut self, syl: Box<dyn SyllableEditor>) {
-        self.shared.syl = syl;
+        self.shared.syl = Box::new(DirectInput::new(syl));
+        self.shared
+            .syl
+            .set_syllable_check(self.shared.options.syllable_check);
         info!("Set syllable editor: {:?}", self.shared.syl);
     }
     pub fn set_conversion_engine(&mut self, engine: Box<dyn ConversionEngine>) {
@@ -259,6 +283,9 @@ impl Editor {
         if self.shared.options.language_mode != options.language_mode {
             self.shared.syl.clear();
         }
+        if self.shared.options.syllable_check != options.syllable_check {
+            self.shared.syl.set_syllable_check(options.syllable_check);
+        }
         self.shared.options = options;
     }
     pub fn entering_syllable(&self) -> bool {
@@ -273,6 +300,16 @@ impl Editor {
             .key_seq()
             .unwrap_or_else(|| self.shared.syl.read().to_string())
     }
+    /// Returns the keys that can continue the syllable being entered.
+    ///
+    /// If `in_dictionary` is true, only keys leading to syllables with
+    /// phrases in the loaded dictionaries are returned.
+    pub fn next_keys(&self, keyboard: &dyn KeyboardLayout, in_dictionary: bool) -> Vec<KeyCode> {
+        let dict: &dyn Dictionary = &self.shared.dict;
+        self.shared
+            .syl
+            .next_keys(keyboard, in_dictionary.then_some(dict))
+    }
     pub fn symbols(&self) -> &[Symbol] {
         self.shared.com.symbols()
     }
@@ -343,6 +380,7 @@ impl Editor {
             }
             Transition::Spin(behavior) => self.shared.last_key_behavior = behavior,
         }
+        self.shared.resolve_readings();
         if self.shared.last_key_behavior == EditorKeyBehavior::Absorb {
             self.shared.try_auto_commit();
         }
@@ -523,6 +561,15 @@ impl SharedState {
     fn intervals(&self) -> impl Iterator<Item = Interval> {
         self.conversion().into_iter()
     }
+    /// Sets ambiguous syllables to the readings picked by the conversion.
+    fn resolve_readings(&mut self) {
+        if !self.com.is_ambiguous() {
+            return;
+        }
+        let intervals = self.conversion();
+        self.com
+            .resolve_readings(&self.dict, &intervals, self.options.lookup_strategy);
+    }
     fn snapshot(&mut self) {
         // for interval in self.intervals() {
         //     self.com.select(interval);
@@ -731,6 +778,21 @@ impl SharedState {
     }
 }
 
+/// Returns the readings of the syllable editor that have phrases in the
+/// dictionary.
+pub(crate) fn known_readings(
+    dict: &dyn Dictionary,
+    syl: &dyn SyllableEditor,
+    strategy: LookupStrategy,
+) -> Vec<Syllable> {
+    let mut readings = syl.readings();
+    if readings.is_empty() {
+        readings.push(syl.read());
+    }
+    readings.retain(|&reading| dict.lookup_first_phrase(&[reading], strategy).is_some());
+    readings
+}
+
 #[rustfmt::skip]
 fn is_break_word(word: &str) -> bool {
     ["是", "的", "了", "不",
@@ -762,6 +824,7 @@ impl BasicEditor for Editor {
             }
             Transition::Spin(behavior) => self.shared.last_key_behavior = behavior,
         }
+        self.shared.resolve_readings();
 
         if self.is_entering() && self.shared.last_key_behavior == EditorKeyBehavior::Absorb {
             self.shared.try_auto_commit();
@@ -1002,6 +1065,13 @@ impl State for Entering {
                 if shared.nth_conversion != 0 {
                     shared.snapshot();
                 }
+                if shared.options.language_mode == LanguageMode::Chinese && ev.modifiers.is_none() {
+                    if let Some(tone) = shared.syl.trailing_tone(ev) {
+                        if shared.com.amend_tone(tone) {
+                            return self.spin_absorb();
+                        }
+                    }
+                }
                 match shared.options.language_mode {
                     LanguageMode::Chinese if ev.code == Grave && ev.modifiers.is_none() => {
                         self.start_symbol_input(shared)
@@ -1191,15 +1261,13 @@ impl State for EnteringSyllable {
                         self.spin_absorb()
                     }
                     KeyBehavior::Commit => {
-                        if shared
-                            .dict
-                            .lookup_first_phrase(
-                                &[shared.syl.read()],
-                                shared.options.lookup_strategy,
-                            )
-                            .is_some()
-                        {
-                            shared.com.insert(Symbol::from(shared.syl.read()));
+                        let readings = known_readings(
+                            &shared.dict,
+                            shared.syl.as_ref(),
+                            shared.options.lookup_strategy,
+                        );
+                        if !readings.is_empty() {
+                            shared.com.insert_readings(&readings);
                             shared.syl.clear();
                             if shared.options.conversion_engine
                                 == ConversionEngineKind::SimpleEngine
@@ -1539,9 +1607,13 @@ mod tests {
 
     use crate::{
         conversion::ChewingEngine,
-        dictionary::{Layered, TrieBuf},
+        dictionary::{Layered, LookupStrategy, TrieBuf},
         editor::{
-            EditorKeyBehavior, SymbolSelector, abbrev::AbbrevTable, estimate, keyboard::Modifiers,
+            EditorKeyBehavior, EditorOptions, SymbolSelector,
+            abbrev::AbbrevTable,
+            estimate,
+            keyboard::Modifiers,
+            zhuyin_layout::{DoublePinyin, Hsu, Keypad, SyllableCheck, SyllableEditor},
         },
         syl,
         zhuyin::Bopomofo,
@@ -1549,7 +1621,8 @@ mod tests {
 
     use super::{
         BasicEditor, Editor,
-        keyboard::{KeyCode, KeyboardLayout, Qwerty},
+        keyboard::{KeyCode, KeyEvent, KeyIndex, KeyboardLayout, Qwerty},
+        known_readings,
     };
 
     #[test]
@@ -1805,4 +1878,270 @@ mod tests {
 
     #[test]
     fn editing_mode_input_symbol() {}
+
+    #[test]
+    fn editing_mode_input_double_pinyin() {
+        let keyboard = Qwerty;
+        let dict = TrieBuf::from([
+            (
+                vec![crate::syl![Bopomofo::ZH, Bopomofo::U, Bopomofo::ENG]],
+                vec![("中", 100)],
+            ),
+            (
+                vec![crate::syl![
+                    Bopomofo::ZH,
+                    Bopomofo::U,
+                    Bopomofo::ENG,
+                    Bopomofo::TONE4
+                ]],
+                vec![("重", 10)],
+            ),
+            (vec![crate::syl![Bopomofo::SH]], vec![("詩", 10)]),
+            (
+                vec![crate::syl![Bopomofo::SH, Bopomofo::TONE4]],
+                vec![("是", 100)],
+            ),
+        ]);
+        let dict = Layered::new(vec![Box::new(dict)], Box::new(TrieBuf::new_in_memory()));
+        let conversion_engine = Box::new(ChewingEngine::new());
+        let estimate = LaxUserFreqEstimate::new(0);
+        let abbrev = AbbrevTable::new();
+        let sym_sel = SymbolSelector::default();
+        let mut editor = Editor::new(conversion_engine, dict, estimate, abbrev, sym_sel);
+        editor.set_syllable_editor(Box::new(DoublePinyin::microsoft()));
+
+        let keys = [KeyCode::V, KeyCode::S, KeyCode::U, KeyCode::I];
+        let key_behaviors: Vec<_> = keys
+            .into_iter()
+            .map(|key| keyboard.map(key))
+            .map(|ev| editor.process_keyevent(ev))
+            .collect();
+
+        assert_eq!(vec![EditorKeyBehavior::Absorb; 4], key_behaviors);
+        assert!(editor.syllable_buffer().is_empty());
+        assert_eq!("中是", editor.display());
+
+        // A trailing tone key sets the tone of the previous syllable
+        let keys = [KeyCode::V, KeyCode::S, KeyCode::N4];
+        let key_behaviors: Vec<_> = keys
+            .into_iter()
+            .map(|key| keyboard.map(key))
+            .map(|ev| editor.process_keyevent(ev))
+            .collect();
+
+        assert_eq!(vec![EditorKeyBehavior::Absorb; 3], key_behaviors);
+        assert_eq!("中是重", editor.display());
+
+        // Without a reading in the tone the key is typed as is
+        editor.process_keyevent(keyboard.map(KeyCode::N3));
+        assert_eq!("中是重3", editor.display());
+    }
+
+    #[test]
+    fn known_readings_keeps_typed_syllable_of_unambiguous_layouts() {
+        let keyboard = Qwerty;
+        let dict = TrieBuf::from([
+            (vec![crate::syl![Bopomofo::C]], vec![("ㄘ", 1)]),
+            (vec![crate::syl![Bopomofo::EI]], vec![("ㄟ", 100)]),
+        ]);
+        let mut hsu = Hsu::new();
+        hsu.key_press(keyboard.map(KeyCode::A));
+        assert_eq!(crate::syl![Bopomofo::C], hsu.read());
+        assert_eq!(
+            vec![crate::syl![Bopomofo::C]],
+            known_readings(&dict, &hsu, LookupStrategy::Standard)
+        );
+    }
+
+    #[test]
+    fn editing_mode_input_keypad_picks_best_reading() {
+        let keyboard = Qwerty;
+        let dict = TrieBuf::from([
+            (
+                vec![crate::syl![Bopomofo::SH, Bopomofo::U, Bopomofo::ANG]],
+                vec![("雙", 10)],
+            ),
+            (
+                vec![crate::syl![Bopomofo::ZH, Bopomofo::U, Bopomofo::ENG]],
+                vec![("中", 50)],
+            ),
+            (
+                vec![crate::syl![
+                    Bopomofo::ZH,
+                    Bopomofo::U,
+                    Bopomofo::ENG,
+                    Bopomofo::TONE4
+                ]],
+                vec![("重", 100)],
+            ),
+        ]);
+        let dict = Layered::new(vec![Box::new(dict)], Box::new(TrieBuf::new_in_memory()));
+        let conversion_engine = Box::new(ChewingEngine::new());
+        let estimate = LaxUserFreqEstimate::new(0);
+        let abbrev = AbbrevTable::new();
+        let sym_sel = SymbolSelector::default();
+        let mut editor = Editor::new(conversion_engine, dict, estimate, abbrev, sym_sel);
+        editor.set_syllable_editor(Box::new(Keypad::new()));
+
+        let keys = [KeyCode::N5, KeyCode::N7, KeyCode::N0, KeyCode::Space];
+        let key_behaviors: Vec<_> = keys
+            .into_iter()
+            .map(|key| keyboard.map(key))
+            .map(|ev| editor.process_keyevent(ev))
+            .collect();
+
+        assert_eq!(vec![EditorKeyBehavior::Absorb; 4], key_behaviors);
+        assert!(editor.syllable_buffer().is_empty());
+        assert_eq!("重", editor.display());
+        assert_eq!(
+            vec![crate::syl![
+                Bopomofo::ZH,
+                Bopomofo::U,
+                Bopomofo::ENG,
+                Bopomofo::TONE4
+            ]],
+            editor
+                .symbols()
+                .iter()
+                .filter_map(|sym| sym.to_syllable())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn editing_mode_input_keypad_picks_reading_by_phrase() {
+        let keyboard = Qwerty;
+        let dict = TrieBuf::from([
+            (
+                vec![crate::syl![Bopomofo::SH, Bopomofo::U, Bopomofo::ANG]],
+                vec![("雙", 10)],
+            ),
+            (
+                vec![crate::syl![Bopomofo::ZH, Bopomofo::U, Bopomofo::ENG]],
+                vec![("中", 50)],
+            ),
+            (
+                vec![crate::syl![
+                    Bopomofo::ZH,
+                    Bopomofo::U,
+                    Bopomofo::ENG,
+                    Bopomofo::TONE4
+                ]],
+                vec![("重", 100)],
+            ),
+            (
+                vec![crate::syl![Bopomofo::SH, Bopomofo::OU, Bopomofo::TONE3]],
+                vec![("手", 100)],
+            ),
+            (
+                vec![
+                    crate::syl![Bopomofo::SH, Bopomofo::U, Bopomofo::ANG],
+                    crate::syl![Bopomofo::SH, Bopomofo::OU, Bopomofo::TONE3],
+                ],
+                vec![("雙手", 10)],
+            ),
+        ]);
+        let dict = Layered::new(vec![Box::new(dict)], Box::new(TrieBuf::new_in_memory()));
+        let conversion_engine = Box::new(ChewingEngine::new());
+        let estimate = LaxUserFreqEstimate::new(0);
+        let abbrev = AbbrevTable::new();
+        let sym_sel = SymbolSelector::default();
+        let mut editor = Editor::new(conversion_engine, dict, estimate, abbrev, sym_sel);
+        editor.set_syllable_editor(Box::new(Keypad::new()));
+
+        for key in [KeyCode::N5, KeyCode::N7, KeyCode::N0, KeyCode::Space] {
+            editor.process_keyevent(keyboard.map(key));
+        }
+        assert_eq!("重", editor.display());
+
+        for key in [KeyCode::N5, KeyCode::N9, KeyCode::Space] {
+            editor.process_keyevent(keyboard.map(key));
+        }
+        assert_eq!("雙手", editor.display());
+        assert_eq!(
+            vec![
+                crate::syl![Bopomofo::SH, Bopomofo::U, Bopomofo::ANG],
+                crate::syl![Bopomofo::SH, Bopomofo::OU, Bopomofo::TONE3]
+            ],
+            editor
+                .symbols()
+                .iter()
+                .filter_map(|sym| sym.to_syllable())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn editing_mode_input_bopomofo_characters() {
+        let dict = TrieBuf::from([(
+            vec![crate::syl![Bopomofo::C, Bopomofo::E, Bopomofo::TONE4]],
+            vec![("冊", 100)],
+        )]);
+        let dict = Layered::new(vec![Box::new(dict)], Box::new(TrieBuf::new_in_memory()));
+        let conversion_engine = Box::new(ChewingEngine::new());
+        let estimate = LaxUserFreqEstimate::new(0);
+        let abbrev = AbbrevTable::new();
+        let sym_sel = SymbolSelector::default();
+        let mut editor = Editor::new(conversion_engine, dict, estimate, abbrev, sym_sel);
+
+        let key_behaviors: Vec<_> = ['ㄘ', 'ㄜ', 'ˋ']
+            .into_iter()
+            .map(|unicode| KeyEvent {
+                index: KeyIndex::K0,
+                code: KeyCode::Unknown,
+                unicode,
+                modifiers: Modifiers::default(),
+            })
+            .map(|ev| editor.process_keyevent(ev))
+            .collect();
+
+        assert_eq!(vec![EditorKeyBehavior::Absorb; 3], key_behaviors);
+        assert!(editor.syllable_buffer().is_empty());
+        assert_eq!("冊", editor.display());
+    }
+
+    #[test]
+    fn editing_mode_reject_invalid_syllable() {
+        let dict = TrieBuf::from([(
+            vec![crate::syl![Bopomofo::F, Bopomofo::U, Bopomofo::TONE3]],
+            vec![("府", 100)],
+        )]);
+        let dict = Layered::new(vec![Box::new(dict)], Box::new(TrieBuf::new_in_memory()));
+        let conversion_engine = Box::new(ChewingEngine::new());
+        let estimate = LaxUserFreqEstimate::new(0);
+        let abbrev = AbbrevTable::new();
+        let sym_sel = SymbolSelector::default();
+        let mut editor = Editor::new(conversion_engine, dict, estimate, abbrev, sym_sel);
+        editor.set_editor_options(EditorOptions {
+            syllable_check: SyllableCheck::Reject,
+            ..Default::default()
+        });
+
+        let keyboard = Qwerty;
+        let key_behaviors: Vec<_> = [KeyCode::Z, KeyCode::U, KeyCode::N3]
+            .into_iter()
+            .map(|code| editor.process_keyevent(keyboard.map(code)))
+            .collect();
+
+        assert_eq!(
+            vec![
+                EditorKeyBehavior::Absorb,
+                EditorKeyBehavior::Absorb,
+                EditorKeyBehavior::Bell
+            ],
+            key_behaviors
+        );
+        assert_eq!(
+            crate::syl![Bopomofo::F, Bopomofo::I],
+            editor.syllable_buffer()
+        );
+
+        editor.set_editor_options(EditorOptions {
+            syllable_check: SyllableCheck::AutoCorrect,
+            ..Default::default()
+        });
+        editor.process_keyevent(keyboard.map(KeyCode::N3));
+        assert!(editor.syllable_buffer().is_empty());
+        assert_eq!("府", editor.display());
+    }
 }
@@ -1,4 +1,7 @@
-use std::cmp::min;
+use std::{
+    cmp::{Reverse, min},
+    collections::BTreeSet,
+};
 
 use crate::{
     conversion::{Composition, Gap, Interval},
@@ -46,15 +49,11 @@ impl PhraseSelector {
             self.begin = self.after_previous_break_point(cursor);
         }
         loop {
-            let syllables = &self.com.symbols()[self.begin..self.end];
             debug_assert!(
-                !syllables.is_empty(),
+                self.begin < self.end,
                 "should not enter here if there's no syllable in range"
             );
-            if dict
-                .lookup_first_phrase(&syllables, self.lookup_strategy)
-                .is_some()
-            {
+            if self.has_phrase(dict, self.begin, self.end) {
                 break;
             }
             if self.forward_select {
@@ -89,11 +88,7 @@ impl PhraseSelector {
                     return None;
                 }
             }
-            let syllables = &self.com.symbols()[begin..end];
-            if dict
-                .lookup_first_phrase(&syllables, self.lookup_strategy)
-                .is_some()
-            {
+            if self.has_phrase(dict, begin, end) {
                 return Some((begin, end));
             }
         }
@@ -118,11 +113,7 @@ impl PhraseSelector {
                     return None;
                 }
             }
-            let syllables = &self.com.symbols()[begin..end];
-            if dict
-                .lookup_first_phrase(&syllables, self.lookup_strategy)
-                .is_some()
-            {
+            if self.has_phrase(dict, begin, end) {
                 return Some((begin, end));
             }
         }
@@ -174,16 +165,23 @@ impl PhraseSelector {
                     self.begin = self.after_previous_break_point(self.begin);
                 }
             }
-            let syllables = &self.com.symbols()[self.begin..self.end];
-            if dict
-                .lookup_first_phrase(&syllables, self.lookup_strategy)
-                .is_some()
-            {
+            if self.has_phrase(dict, self.begin, self.end) {
                 break;
             }
         }
     }
 
+    fn has_phrase<D: Dictionary>(&self, dict: &D, begin: usize, end: usize) -> bool {
+        if self.com.is_ambiguous(begin, end) {
+            return !self
+                .com
+                .lookup_phrases(dict, begin, end, self.lookup_strategy)
+                .is_empty();
+        }
+        dict.lookup_first_phrase(&&self.com.symbols()[begin..end], self.lookup_strategy)
+            .is_some()
+    }
+
     fn next_break_point(&self, mut cursor: usize) -> usize {
         loop {
             if self.com.len() == cursor {
@@ -222,27 +220,22 @@ impl PhraseSelector {
     }
 
     pub(crate) fn candidates(&self, editor: &SharedState, dict: &Layered) -> Vec<String> {
-        let mut candidates = dict
-            .lookup_all_phrases(
-                &&self.com.symbols()[self.begin..self.end],
-                self.lookup_strategy,
-            )
-            .into_iter()
-            .map(|phrase| phrase.into())
-            .collect::<Vec<String>>();
-        if self.end - self.begin == 1 {
-            let alt = editor
-                .syl
-                .alt_syllables(self.com.symbol(self.begin).unwrap().to_syllable().unwrap());
-            for &syl in alt {
-                candidates.extend(
-                    dict.lookup_all_phrases(&[syl], self.lookup_strategy)
-                        .into_iter()
-                        .map(|ph| ph.into()),
-                )
+        let mut candidates =
+            self.com
+                .lookup_phrases(dict, self.begin, self.end, self.lookup_strategy);
+        if self.com.is_ambiguous(self.begin, self.end) {
+            // Rank the candidates of all readings together
+            candidates.sort_by_key(|phrase| Reverse(phrase.freq()));
+            let mut seen = BTreeSet::new();
+            candidates.retain(|phrase| seen.insert(phrase.to_string()));
+        } else if self.end - self.begin == 1 {
+            let syl = self.com.symbol(self.begin).unwrap().to_syllable().unwrap();
+            let alt = editor.syl.alt_syllables(syl);
+            for &alt_syl in alt.iter().filter(|&&alt_syl| alt_syl != syl) {
+                candidates.extend(dict.lookup_all_phrases(&[alt_syl], self.lookup_strategy));
             }
         }
-        candidates
+        candidates.into_iter().map(|phrase| phrase.into()).collect()
     }
 
     pub(crate) fn interval(&self, phrase: impl Into<Box<str>>) -> Interval {
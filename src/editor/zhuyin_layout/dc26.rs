@@ -5,12 +5,13 @@ use crate::{
     zhuyin::{Bopomofo, Syllable},
 };
 
-use super::{KeyBehavior, SyllableEditor};
+use super::{KeyBehavior, SyllableCheck, SyllableEditor};
 
 /// TODO: docs
 #[derive(Debug, Clone, Copy)]
 pub struct DaiChien26 {
     syllable: Syllable,
+    check: SyllableCheck,
 }
 
 impl DaiChien26 {
@@ -18,6 +19,7 @@ impl DaiChien26 {
     pub fn new() -> DaiChien26 {
         DaiChien26 {
             syllable: Default::default(),
+            check: SyllableCheck::default(),
         }
     }
     fn is_end_key(&self, key: KeyIndex) -> bool {
@@ -65,6 +67,10 @@ impl SyllableEditor for DaiChien26 {
                     self.syllable.remove_tone();
                 }
             };
+            if !self.check.check(&mut self.syllable) {
+                self.syllable.remove_tone();
+                return KeyBehavior::InvalidSyllable;
+            }
             return KeyBehavior::Commit;
         }
         let bopomofo = match key.index {
@@ -177,6 +183,21 @@ impl SyllableEditor for DaiChien26 {
         self.syllable.clear();
     }
 
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+    }
+
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        match key.index {
+            KeyIndex::K17 => Some(Bopomofo::TONE2),
+            KeyIndex::K18 => Some(Bopomofo::TONE3),
+            KeyIndex::K29 => Some(Bopomofo::TONE4),
+            KeyIndex::K20 => Some(Bopomofo::TONE5),
+            KeyIndex::K48 => Some(Bopomofo::TONE1),
+            _ => None,
+        }
+    }
+
     fn read(&self) -> Syllable {
         self.syllable
     }
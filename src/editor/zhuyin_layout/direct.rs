@@ -0,0 +1,278 @@
+//! Direct bopomofo input
+//!
+//! On-screen and accessibility keyboards often send the bopomofo characters
+//! themselves, such as `ㄅ` or `ˊ`, with [`KeyCode::Unknown`]. These key events
+//! don't map to any key of a physical layout, so they are handled here before
+//! falling back to the configured layout.
+
+use crate::{
+    dictionary::Dictionary,
+    editor::keyboard::{KeyCode, KeyEvent, KeyboardLayout},
+    zhuyin::{Bopomofo, BopomofoKind, Syllable},
+};
+
+use super::{KeyBehavior, SyllableCheck, SyllableEditor, is_known};
+
+/// Wraps a layout to also accept bopomofo characters and tone marks.
+#[derive(Debug)]
+pub(crate) struct DirectInput {
+    inner: Box<dyn SyllableEditor>,
+    syllable: Syllable,
+    check: SyllableCheck,
+}
+
+impl DirectInput {
+    pub(crate) fn new(inner: Box<dyn SyllableEditor>) -> DirectInput {
+        DirectInput {
+            inner,
+            syllable: Syllable::new(),
+            check: SyllableCheck::default(),
+        }
+    }
+
+    fn direct_key_press(&mut self, key: KeyEvent) -> Option<KeyBehavior> {
+        let bopomofo = match key.code {
+            KeyCode::Unknown => Bopomofo::try_from(key.unicode).ok(),
+            _ => None,
+        };
+        let Some(bopomofo) = bopomofo else {
+            if self.syllable.is_empty() {
+                return None;
+            }
+            // Space completes the syllable with the first tone
+            if key.code == KeyCode::Space {
+                return Some(self.commit(Bopomofo::TONE1));
+            }
+            // The tone keys of the layout also complete the syllable
+            return match self.inner.tone_key(key) {
+                Some(tone) => Some(self.commit(tone)),
+                None => Some(KeyBehavior::KeyError),
+            };
+        };
+        if !self.inner.is_empty() {
+            return Some(KeyBehavior::KeyError);
+        }
+        if bopomofo.kind() == BopomofoKind::Tone {
+            if self.syllable.is_empty() {
+                return Some(KeyBehavior::KeyError);
+            }
+            return Some(self.commit(bopomofo));
+        }
+        self.syllable.remove_tone();
+        self.syllable.update(bopomofo);
+        Some(KeyBehavior::Absorb)
+    }
+
+    fn commit(&mut self, tone: Bopomofo) -> KeyBehavior {
+        let mut syllable = self.syllable;
+        if tone != Bopomofo::TONE1 {
+            syllable.update(tone);
+        }
+        if !self.check.check(&mut syllable) {
+            return KeyBehavior::InvalidSyllable;
+        }
+        self.syllable = syllable;
+        KeyBehavior::Commit
+    }
+}
+
+impl SyllableEditor for DirectInput {
+    fn key_press(&mut self, key: KeyEvent) -> KeyBehavior {
+        self.direct_key_press(key)
+            .unwrap_or_else(|| self.inner.key_press(key))
+    }
+
+    fn fuzzy_key_press(&mut self, key: KeyEvent) -> KeyBehavior {
+        self.direct_key_press(key)
+            .unwrap_or_else(|| self.inner.fuzzy_key_press(key))
+    }
+
+    fn remove_last(&mut self) {
+        if self.syllable.is_empty() {
+            self.inner.remove_last();
+        } else {
+            self.syllable.pop();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.syllable.clear();
+        self.inner.clear();
+    }
+
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+        self.inner.set_syllable_check(check);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.syllable.is_empty() && self.inner.is_empty()
+    }
+
+    fn read(&self) -> Syllable {
+        if self.syllable.is_empty() {
+            self.inner.read()
+        } else {
+            self.syllable
+        }
+    }
+
+    fn key_seq(&self) -> Option<String> {
+        if self.syllable.is_empty() {
+            self.inner.key_seq()
+        } else {
+            None
+        }
+    }
+
+    fn alt_syllables(&self, syl: Syllable) -> &[Syllable] {
+        self.inner.alt_syllables(syl)
+    }
+
+    fn trailing_tone(&self, key: KeyEvent) -> Option<Bopomofo> {
+        if self.syllable.is_empty() {
+            self.inner.trailing_tone(key)
+        } else {
+            None
+        }
+    }
+
+    fn readings(&self) -> Vec<Syllable> {
+        // A directly typed syllable is exactly what the user meant
+        if self.syllable.is_empty() {
+            self.inner.readings()
+        } else {
+            vec![]
+        }
+    }
+
+    fn next_keys(
+        &self,
+        keyboard: &dyn KeyboardLayout,
+        dict: Option<&dyn Dictionary>,
+    ) -> Vec<KeyCode> {
+        if self.syllable.is_empty() {
+            return self.inner.next_keys(keyboard, dict);
+        }
+        if is_known(self.syllable, dict) {
+            vec![KeyCode::Space]
+        } else {
+            vec![]
+        }
+    }
+
+    fn clone(&self) -> Box<dyn SyllableEditor> {
+        Box::new(DirectInput {
+            inner: SyllableEditor::clone(self.inner.as_ref()),
+            syllable: self.syllable,
+            check: self.check,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        editor::{
+            keyboard::{KeyCode, KeyEvent, KeyIndex, KeyboardLayout, Modifiers, Qwerty},
+            zhuyin_layout::{Hsu, KeyBehavior, Pinyin, Standard, SyllableEditor},
+        },
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::DirectInput;
+
+    fn unicode(ch: char) -> KeyEvent {
+        KeyEvent {
+            index: KeyIndex::K0,
+            code: KeyCode::Unknown,
+            unicode: ch,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[test]
+    fn bopomofo_and_tone_marks() {
+        let mut editor = DirectInput::new(Box::new(Pinyin::hanyu()));
+
+        assert_eq!(KeyBehavior::Absorb, editor.key_press(unicode('ㄓ')));
+        assert_eq!(KeyBehavior::Absorb, editor.key_press(unicode('ㄨ')));
+        assert_eq!(KeyBehavior::Absorb, editor.key_press(unicode('ㄥ')));
+        assert_eq!(None, editor.key_seq());
+        assert_eq!(KeyBehavior::Commit, editor.key_press(unicode('ˋ')));
+        assert_eq!(syl![ZH, U, ENG, TONE4], editor.read());
+    }
+
+    #[test]
+    fn space_completes_first_tone() {
+        let keyboard = Qwerty;
+        let mut editor = DirectInput::new(Box::new(Standard::new()));
+
+        assert_eq!(KeyBehavior::KeyError, editor.key_press(unicode('ˊ')));
+        assert_eq!(KeyBehavior::Absorb, editor.key_press(unicode('ㄅ')));
+        assert_eq!(KeyBehavior::Absorb, editor.key_press(unicode('ㄚ')));
+        assert_eq!(
+            KeyBehavior::KeyError,
+            editor.key_press(keyboard.map(KeyCode::A))
+        );
+        assert_eq!(
+            KeyBehavior::Commit,
+            editor.key_press(keyboard.map(KeyCode::Space))
+        );
+        assert_eq!(syl![B, A], editor.read());
+    }
+
+    #[test]
+    fn layout_tone_keys_complete_syllable() {
+        let keyboard = Qwerty;
+        let mut editor = DirectInput::new(Box::new(Standard::new()));
+
+        editor.key_press(unicode('ㄓ'));
+        editor.key_press(unicode('ㄨ'));
+        editor.key_press(unicode('ㄥ'));
+        assert_eq!(
+            KeyBehavior::Commit,
+            editor.key_press(keyboard.map(KeyCode::N4))
+        );
+        assert_eq!(syl![ZH, U, ENG, TONE4], editor.read());
+
+        let mut editor = DirectInput::new(Box::new(Pinyin::hanyu()));
+        editor.key_press(unicode('ㄕ'));
+        assert_eq!(
+            KeyBehavior::Commit,
+            editor.key_press(keyboard.map(KeyCode::N2))
+        );
+        assert_eq!(syl![SH, TONE2], editor.read());
+
+        let mut editor = DirectInput::new(Box::new(Hsu::new()));
+        editor.key_press(unicode('ㄇ'));
+        editor.key_press(unicode('ㄚ'));
+        assert_eq!(
+            KeyBehavior::Commit,
+            editor.key_press(keyboard.map(KeyCode::F))
+        );
+        assert_eq!(syl![M, A, TONE3], editor.read());
+    }
+
+    #[test]
+    fn layout_keys_fall_back() {
+        let keyboard = Qwerty;
+        let mut editor = DirectInput::new(Box::new(Standard::new()));
+
+        assert_eq!(
+            KeyBehavior::Absorb,
+            editor.key_press(keyboard.map(KeyCode::N1))
+        );
+        assert_eq!(KeyBehavior::KeyError, editor.key_press(unicode('ㄚ')));
+        assert_eq!(
+            KeyBehavior::Absorb,
+            editor.key_press(keyboard.map(KeyCode::N8))
+        );
+        assert_eq!(syl![B, A], editor.read());
+
+        editor.remove_last();
+        editor.remove_last();
+        assert!(editor.is_empty());
+    }
+}
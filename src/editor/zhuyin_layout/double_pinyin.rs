@@ -0,0 +1,690 @@
+//! Double pinyin (雙拼)
+//!
+//! Double pinyin schemes type every syllable with exactly two keys. The first
+//! key selects the initial and the second key selects the final. Syllables
+//! without an initial use scheme specific key pairs.
+//!
+//! The syllable is committed by the second key without a tone, and the
+//! conversion picks the tone that forms the best phrase. A tone key from 1 to
+//! 5 typed right after the syllable sets its tone instead.
+
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    dictionary::Dictionary,
+    editor::keyboard::{KeyCode, KeyEvent, KeyboardLayout},
+    zhuyin::{Bopomofo, PinyinVariant, Syllable, resolve_pinyin},
+};
+
+use super::{HINT_KEYS, KeyBehavior, SyllableCheck, SyllableEditor, is_known};
+
+/// Returns the tone selected by the key, or `None` for the first tone.
+fn tone_key(code: KeyCode) -> Option<Option<Bopomofo>> {
+    match code {
+        KeyCode::Space | KeyCode::N1 => Some(None),
+        KeyCode::N2 => Some(Some(Bopomofo::TONE2)),
+        KeyCode::N3 => Some(Some(Bopomofo::TONE3)),
+        KeyCode::N4 => Some(Some(Bopomofo::TONE4)),
+        KeyCode::N5 => Some(Some(Bopomofo::TONE5)),
+        _ => None,
+    }
+}
+
+/// Letters that are pinyin initials on their own.
+const LETTER_INITIALS: &str = "bpmfdtnlgkhjqxrzcsyw";
+
+const MICROSOFT: &str = "
+[initials]
+v zh
+i ch
+u sh
+
+[finals]
+q iu
+w ia ua
+r uan er
+t ue
+y v uai
+u u
+i i
+o uo o
+p un
+a a
+s iong ong
+d iang uang
+f en
+g eng
+h ang
+j an
+k ao
+l ai
+; ing
+z ei
+x ie
+c iao
+v ve ui
+b ou
+n in
+m ian
+
+[zero]
+oa a
+oo o
+oe e
+ol ai
+oz ei
+ok ao
+ob ou
+oj an
+of en
+oh ang
+og eng
+or er
+";
+
+const ZIRANMA: &str = "
+[initials]
+v zh
+i ch
+u sh
+
+[finals]
+q iu
+w ia ua
+r uan
+t ve
+y ing uai
+u u
+i i
+o uo o
+p un
+a a
+s iong ong
+d iang uang
+f en
+g eng
+h ang
+j an
+k ao
+l ai
+z ei
+x ie
+c iao
+v v ui
+b ou
+n in
+m ian
+
+[zero]
+aa a
+ai ai
+an an
+ah ang
+ao ao
+ee e
+ei ei
+en en
+eg eng
+er er
+oo o
+ou ou
+";
+
+const XIAOHE: &str = "
+[initials]
+v zh
+i ch
+u sh
+
+[finals]
+q iu
+w ei
+r uan
+t ve
+y un
+u u
+i i
+o uo o
+p ie
+a a
+s iong ong
+d ai
+f en
+g eng
+h ang
+j an
+k ing uai
+l iang uang
+z ou
+x ia ua
+c ao
+v v ui
+b in
+n iao
+m ian
+
+[zero]
+aa a
+ai ai
+an an
+ah ang
+ao ao
+ee e
+ei ei
+en en
+eg eng
+er er
+oo o
+ou ou
+";
+
+/// A double pinyin key table.
+///
+/// A scheme can be parsed from a plain text table with three sections. Lines
+/// starting with `#` are comments.
+///
+/// ```text
+/// [initials]
+/// # key  initial
+/// v zh
+///
+/// [finals]
+/// # key  finals, tried in order
+/// s iong ong
+///
+/// [zero]
+/// # keys  final of syllables without initial
+/// oa a
+/// ```
+///
+/// Letters that are pinyin initials on their own map to themselves unless
+/// they are listed in the `[initials]` section. Finals are written in Hanyu
+/// Pinyin with `v` for `ü`. When a key has multiple finals, the first one
+/// that forms a valid syllable with the initial is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoublePinyinScheme {
+    initials: Vec<(char, String)>,
+    finals: Vec<(char, Vec<String>)>,
+    zero: Vec<(String, String)>,
+}
+
+impl DoublePinyinScheme {
+    /// The Microsoft double pinyin scheme (微軟雙拼).
+    pub fn microsoft() -> DoublePinyinScheme {
+        MICROSOFT.parse().expect("built-in scheme should be valid")
+    }
+    /// The Ziranma double pinyin scheme (自然碼).
+    pub fn ziranma() -> DoublePinyinScheme {
+        ZIRANMA.parse().expect("built-in scheme should be valid")
+    }
+    /// The Xiaohe double pinyin scheme (小鶴雙拼).
+    pub fn xiaohe() -> DoublePinyinScheme {
+        XIAOHE.parse().expect("built-in scheme should be valid")
+    }
+    fn is_first_key(&self, key: char) -> bool {
+        self.initial(key).is_some() || self.zero.iter().any(|(keys, _)| keys.starts_with(key))
+    }
+    fn is_final_key(&self, key: char) -> bool {
+        self.finals.iter().any(|(k, _)| *k == key)
+            || self.zero.iter().any(|(keys, _)| keys.ends_with(key))
+    }
+    fn initial(&self, key: char) -> Option<&str> {
+        match self.initials.iter().find(|(k, _)| *k == key) {
+            Some((_, initial)) => Some(initial),
+            None => LETTER_INITIALS
+                .find(key)
+                .map(|index| &LETTER_INITIALS[index..index + 1]),
+        }
+    }
+    /// Resolves a pair of keys to toneless syllables, in the order of the
+    /// finals assigned to the second key.
+    fn candidates(&self, first: char, second: char) -> Vec<Syllable> {
+        if let Some((_, fina)) = self
+            .zero
+            .iter()
+            .find(|(keys, _)| keys.chars().eq([first, second]))
+        {
+            return resolve_pinyin(fina, PinyinVariant::HanyuPinyin, true)
+                .map(|syl| syl.0)
+                .into_iter()
+                .collect();
+        }
+        let Some(initial) = self.initial(first) else {
+            return vec![];
+        };
+        let Some((_, finals)) = self.finals.iter().find(|(k, _)| *k == second) else {
+            return vec![];
+        };
+        finals
+            .iter()
+            .filter_map(|fina| {
+                let spelling = format!("{initial}{fina}");
+                resolve_pinyin(&spelling, PinyinVariant::HanyuPinyin, true).map(|syl| syl.0)
+            })
+            .collect()
+    }
+    /// Resolves a pair of keys to a valid toneless syllable.
+    fn syllable(&self, first: char, second: char) -> Option<Syllable> {
+        self.candidates(first, second)
+            .into_iter()
+            .find(Syllable::is_valid)
+    }
+}
+
+/// Errors when parsing a double pinyin scheme table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDoublePinyinSchemeError {
+    line: usize,
+}
+
+impl ParseDoublePinyinSchemeError {
+    /// Returns the line number, starting from 1, of the invalid line.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl Display for ParseDoublePinyinSchemeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid double pinyin scheme at line {}", self.line)
+    }
+}
+
+impl Error for ParseDoublePinyinSchemeError {}
+
+impl std::str::FromStr for DoublePinyinScheme {
+    type Err = ParseDoublePinyinSchemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[derive(PartialEq)]
+        enum Section {
+            None,
+            Initials,
+            Finals,
+            Zero,
+        }
+        let mut section = Section::None;
+        let mut scheme = DoublePinyinScheme {
+            initials: vec![],
+            finals: vec![],
+            zero: vec![],
+        };
+        for (index, line) in s.lines().enumerate() {
+            let error = ParseDoublePinyinSchemeError { line: index + 1 };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line {
+                "[initials]" => section = Section::Initials,
+                "[finals]" => section = Section::Finals,
+                "[zero]" => section = Section::Zero,
+                _ => {
+                    let mut fields = line.split_whitespace();
+                    let keys = fields.next().ok_or(error.clone())?;
+                    let values: Vec<String> = fields.map(str::to_string).collect();
+                    if values.is_empty() {
+                        return Err(error);
+                    }
+                    let mut chars = keys.chars();
+                    match (&section, chars.next(), chars.next(), chars.next()) {
+                        (Section::Initials, Some(key), None, None) if values.len() == 1 => {
+                            scheme.initials.push((key, values[0].clone()));
+                        }
+                        (Section::Finals, Some(key), None, None) => {
+                            scheme.finals.push((key, values));
+                        }
+                        (Section::Zero, Some(_), Some(_), None) if values.len() == 1 => {
+                            scheme.zero.push((keys.to_string(), values[0].clone()));
+                        }
+                        _ => return Err(error),
+                    }
+                }
+            }
+        }
+        Ok(scheme)
+    }
+}
+
+/// Double pinyin layout
+///
+/// Built-in schemes: [`DoublePinyinScheme::microsoft`],
+/// [`DoublePinyinScheme::ziranma`], and [`DoublePinyinScheme::xiaohe`].
+#[derive(Debug, Clone)]
+pub struct DoublePinyin {
+    scheme: DoublePinyinScheme,
+    key_seq: String,
+    syllable: Syllable,
+    check: SyllableCheck,
+}
+
+impl DoublePinyin {
+    /// Creates a double pinyin layout using the scheme.
+    pub fn new(scheme: DoublePinyinScheme) -> DoublePinyin {
+        DoublePinyin {
+            scheme,
+            key_seq: String::new(),
+            syllable: Syllable::new(),
+            check: SyllableCheck::default(),
+        }
+    }
+    /// Creates a layout using the Microsoft scheme.
+    pub fn microsoft() -> DoublePinyin {
+        DoublePinyin::new(DoublePinyinScheme::microsoft())
+    }
+    /// Creates a layout using the Ziranma scheme.
+    pub fn ziranma() -> DoublePinyin {
+        DoublePinyin::new(DoublePinyinScheme::ziranma())
+    }
+    /// Creates a layout using the Xiaohe scheme.
+    pub fn xiaohe() -> DoublePinyin {
+        DoublePinyin::new(DoublePinyinScheme::xiaohe())
+    }
+}
+
+impl SyllableEditor for DoublePinyin {
+    fn key_press(&mut self, key: KeyEvent) -> KeyBehavior {
+        let unicode = key.unicode.to_ascii_lowercase();
+        match self.key_seq.chars().next() {
+            None => {
+                if !key.code.is_atoz() || !self.scheme.is_first_key(unicode) {
+                    return KeyBehavior::KeyError;
+                }
+                self.syllable.clear();
+                self.key_seq.push(unicode);
+                KeyBehavior::Absorb
+            }
+            Some(first) => {
+                let candidates = self.scheme.candidates(first, unicode);
+                let Some(&fallback) = candidates.first() else {
+                    return KeyBehavior::KeyError;
+                };
+                let mut syllable = candidates
+                    .into_iter()
+                    .find(Syllable::is_valid)
+                    .unwrap_or(fallback);
+                if !self.check.check(&mut syllable) {
+                    return KeyBehavior::InvalidSyllable;
+                }
+                self.key_seq.clear();
+                self.syllable = syllable;
+                KeyBehavior::Commit
+            }
+        }
+    }
+
+    fn fuzzy_key_press(&mut self, key: KeyEvent) -> KeyBehavior {
+        self.key_press(key)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.key_seq.is_empty()
+    }
+
+    fn remove_last(&mut self) {
+        self.key_seq.pop();
+        self.syllable.clear();
+    }
+
+    fn clear(&mut self) {
+        self.key_seq.clear();
+        self.syllable.clear();
+    }
+
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+    }
+
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        tone_key(key.code).map(|tone| tone.unwrap_or(Bopomofo::TONE1))
+    }
+
+    fn trailing_tone(&self, key: KeyEvent) -> Option<Bopomofo> {
+        if key.code == KeyCode::Space || !self.key_seq.is_empty() {
+            return None;
+        }
+        self.tone_key(key)
+    }
+
+    fn read(&self) -> Syllable {
+        self.syllable
+    }
+
+    fn readings(&self) -> Vec<Syllable> {
+        if self.syllable.is_empty() {
+            return vec![];
+        }
+        let mut readings = vec![self.syllable];
+        for tone in [
+            Bopomofo::TONE2,
+            Bopomofo::TONE3,
+            Bopomofo::TONE4,
+            Bopomofo::TONE5,
+        ] {
+            let mut syl = self.syllable;
+            syl.update(tone);
+            readings.push(syl);
+        }
+        readings
+    }
+
+    fn key_seq(&self) -> Option<String> {
+        Some(self.key_seq.clone())
+    }
+
+    fn next_keys(
+        &self,
+        keyboard: &dyn KeyboardLayout,
+        dict: Option<&dyn Dictionary>,
+    ) -> Vec<KeyCode> {
+        let completes = |first: char, second: char| {
+            self.scheme
+                .syllable(first, second)
+                .is_some_and(|syl| is_known(syl, dict))
+        };
+        match self.key_seq.chars().next() {
+            Some(first) => HINT_KEYS
+                .into_iter()
+                .filter(|&code| {
+                    let unicode = keyboard.map(code).unicode.to_ascii_lowercase();
+                    self.scheme.is_final_key(unicode) && completes(first, unicode)
+                })
+                .collect(),
+            None => HINT_KEYS
+                .into_iter()
+                .filter(|code| code.is_atoz())
+                .filter(|&code| {
+                    let unicode = keyboard.map(code).unicode.to_ascii_lowercase();
+                    self.scheme.is_first_key(unicode)
+                        && HINT_KEYS.into_iter().any(|second| {
+                            let second = keyboard.map(second).unicode.to_ascii_lowercase();
+                            self.scheme.is_final_key(second) && completes(unicode, second)
+                        })
+                })
+                .collect(),
+        }
+    }
+
+    fn clone(&self) -> Box<dyn SyllableEditor> {
+        Box::new(Clone::clone(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dictionary::TrieBuf,
+        editor::{
+            keyboard::{KeyCode, KeyboardLayout, Qwerty},
+            zhuyin_layout::{KeyBehavior, SyllableCheck, SyllableEditor},
+        },
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::{DoublePinyin, DoublePinyinScheme};
+
+    fn type_keys(editor: &mut DoublePinyin, keys: [KeyCode; 2]) -> KeyBehavior {
+        let keyboard = Qwerty;
+        editor.key_press(keyboard.map(keys[0]));
+        editor.key_press(keyboard.map(keys[1]))
+    }
+
+    #[test]
+    fn microsoft() {
+        let mut editor = DoublePinyin::microsoft();
+        assert_eq!(
+            KeyBehavior::Commit,
+            type_keys(&mut editor, [KeyCode::V, KeyCode::S])
+        );
+        assert_eq!(syl![ZH, U, ENG], editor.read());
+        type_keys(&mut editor, [KeyCode::J, KeyCode::S]);
+        assert_eq!(syl![J, IU, ENG], editor.read());
+        type_keys(&mut editor, [KeyCode::L, KeyCode::Y]);
+        assert_eq!(syl![L, IU], editor.read());
+        type_keys(&mut editor, [KeyCode::G, KeyCode::Y]);
+        assert_eq!(syl![G, U, AI], editor.read());
+        type_keys(&mut editor, [KeyCode::U, KeyCode::I]);
+        assert_eq!(syl![SH], editor.read());
+        type_keys(&mut editor, [KeyCode::O, KeyCode::H]);
+        assert_eq!(syl![ANG], editor.read());
+        type_keys(&mut editor, [KeyCode::B, KeyCode::O]);
+        assert_eq!(syl![B, O], editor.read());
+        type_keys(&mut editor, [KeyCode::D, KeyCode::V]);
+        assert_eq!(syl![D, U, EI], editor.read());
+    }
+
+    #[test]
+    fn tone_keys() {
+        let keyboard = Qwerty;
+        let mut editor = DoublePinyin::microsoft();
+        assert_eq!(
+            KeyBehavior::Absorb,
+            editor.key_press(keyboard.map(KeyCode::U))
+        );
+        assert_eq!(None, editor.trailing_tone(keyboard.map(KeyCode::N4)));
+        assert_eq!(
+            KeyBehavior::Commit,
+            editor.key_press(keyboard.map(KeyCode::I))
+        );
+        assert_eq!(syl![SH], editor.read());
+        assert_eq!(
+            vec![
+                syl![SH],
+                syl![SH, TONE2],
+                syl![SH, TONE3],
+                syl![SH, TONE4],
+                syl![SH, TONE5]
+            ],
+            editor.readings()
+        );
+        assert!(editor.is_empty());
+
+        assert_eq!(Some(TONE4), editor.trailing_tone(keyboard.map(KeyCode::N4)));
+        assert_eq!(Some(TONE1), editor.trailing_tone(keyboard.map(KeyCode::N1)));
+        assert_eq!(None, editor.trailing_tone(keyboard.map(KeyCode::Space)));
+        assert_eq!(
+            KeyBehavior::KeyError,
+            editor.key_press(keyboard.map(KeyCode::N4))
+        );
+    }
+
+    #[test]
+    fn ziranma_and_xiaohe() {
+        let mut editor = DoublePinyin::ziranma();
+        type_keys(&mut editor, [KeyCode::X, KeyCode::Y]);
+        assert_eq!(syl![X, I, ENG], editor.read());
+        type_keys(&mut editor, [KeyCode::A, KeyCode::H]);
+        assert_eq!(syl![ANG], editor.read());
+
+        let mut editor = DoublePinyin::xiaohe();
+        type_keys(&mut editor, [KeyCode::H, KeyCode::K]);
+        assert_eq!(syl![H, U, AI], editor.read());
+        type_keys(&mut editor, [KeyCode::N, KeyCode::L]);
+        assert_eq!(syl![N, I, ANG], editor.read());
+        type_keys(&mut editor, [KeyCode::E, KeyCode::G]);
+        assert_eq!(syl![ENG], editor.read());
+    }
+
+    #[test]
+    fn invalid_keys() {
+        let keyboard = Qwerty;
+        let mut editor = DoublePinyin::microsoft();
+        assert_eq!(
+            KeyBehavior::KeyError,
+            editor.key_press(keyboard.map(KeyCode::A))
+        );
+        assert_eq!(
+            KeyBehavior::KeyError,
+            editor.key_press(keyboard.map(KeyCode::N1))
+        );
+        assert_eq!(
+            KeyBehavior::Absorb,
+            editor.key_press(keyboard.map(KeyCode::F))
+        );
+        editor.remove_last();
+        assert!(editor.is_empty());
+        assert_eq!(
+            KeyBehavior::Absorb,
+            editor.key_press(keyboard.map(KeyCode::F))
+        );
+        editor.set_syllable_check(SyllableCheck::Reject);
+        assert_eq!(
+            KeyBehavior::InvalidSyllable,
+            editor.key_press(keyboard.map(KeyCode::X))
+        );
+        editor.remove_last();
+        assert!(editor.is_empty());
+    }
+
+    #[test]
+    fn parse_scheme() {
+        let scheme: DoublePinyinScheme = "[finals]\nk ao\n[zero]\nok ao\n".parse().unwrap();
+        let mut editor = DoublePinyin::new(scheme);
+        type_keys(&mut editor, [KeyCode::H, KeyCode::K]);
+        assert_eq!(syl![H, AU], editor.read());
+        type_keys(&mut editor, [KeyCode::O, KeyCode::K]);
+        assert_eq!(syl![AU], editor.read());
+
+        let error = "[initials]\nvv zh\n"
+            .parse::<DoublePinyinScheme>()
+            .unwrap_err();
+        assert_eq!(2, error.line());
+    }
+
+    #[test]
+    fn next_keys() {
+        let keyboard = Qwerty;
+        let dict = TrieBuf::from([
+            (vec![syl![ZH, U, ENG]], vec![("中", 100)]),
+            (vec![syl![ZH, U, ENG, TONE4]], vec![("重", 100)]),
+        ]);
+        let mut editor = DoublePinyin::microsoft();
+
+        assert_eq!(vec![KeyCode::V], editor.next_keys(&keyboard, Some(&dict)));
+        assert!(editor.next_keys(&keyboard, None).contains(&KeyCode::B));
+        editor.key_press(keyboard.map(KeyCode::V));
+        assert_eq!(vec![KeyCode::S], editor.next_keys(&keyboard, Some(&dict)));
+        editor.key_press(keyboard.map(KeyCode::S));
+        assert_eq!(vec![KeyCode::V], editor.next_keys(&keyboard, Some(&dict)));
+    }
+
+    #[test]
+    fn next_keys_of_non_letter_finals() {
+        let keyboard = Qwerty;
+        let dict = TrieBuf::from([(vec![syl![J, I, ENG]], vec![("經", 100)])]);
+        let mut editor = DoublePinyin::microsoft();
+
+        assert_eq!(vec![KeyCode::J], editor.next_keys(&keyboard, Some(&dict)));
+        editor.key_press(keyboard.map(KeyCode::J));
+        assert_eq!(
+            vec![KeyCode::SColon],
+            editor.next_keys(&keyboard, Some(&dict))
+        );
+        assert_eq!(
+            KeyBehavior::Commit,
+            editor.key_press(keyboard.map(KeyCode::SColon))
+        );
+        assert_eq!(syl![J, I, ENG], editor.read());
+    }
+}
@@ -6,12 +6,13 @@ use crate::{
     zhuyin::{Bopomofo, BopomofoKind, Syllable},
 };
 
-use super::{KeyBehavior, SyllableEditor};
+use super::{KeyBehavior, SyllableCheck, SyllableEditor};
 
 /// TODO: docs
 #[derive(Debug, Clone, Copy)]
 pub struct Et26 {
     syllable: Syllable,
+    check: SyllableCheck,
 }
 
 impl Et26 {
@@ -19,6 +20,7 @@ impl Et26 {
     pub fn new() -> Et26 {
         Et26 {
             syllable: Default::default(),
+            check: SyllableCheck::default(),
         }
     }
     fn is_end_key(&self, key: KeyCode) -> bool {
@@ -106,6 +108,10 @@ impl SyllableEditor for Et26 {
                     self.syllable.remove_tone();
                 }
             };
+            if !self.check.check(&mut self.syllable) {
+                self.syllable.remove_tone();
+                return KeyBehavior::InvalidSyllable;
+            }
             KeyBehavior::Commit
         } else {
             let bopomofo = match key.code {
@@ -233,6 +239,21 @@ impl SyllableEditor for Et26 {
         self.syllable.clear();
     }
 
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+    }
+
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        match key.code {
+            KeyCode::F => Some(Bopomofo::TONE2),
+            KeyCode::J => Some(Bopomofo::TONE3),
+            KeyCode::K => Some(Bopomofo::TONE4),
+            KeyCode::D => Some(Bopomofo::TONE5),
+            KeyCode::Space => Some(Bopomofo::TONE1),
+            _ => None,
+        }
+    }
+
     fn read(&self) -> Syllable {
         self.syllable
     }
@@ -0,0 +1,393 @@
+//! Taiwanese Phonetic Symbols (臺灣方音符號) layout
+//!
+//! Types Taiwanese Hokkien and Hakka bopomofo on the standard (大千) layout.
+//! Mandarin letters are on the same keys as the standard layout. The extended
+//! letters are typed by pressing the key of a similar Mandarin letter again:
+//!
+//! | Key | Letters  | Key | Letters  | Key | Letters  |
+//! |-----|----------|-----|----------|-----|----------|
+//! | ㄅ  | ㄅㆠ     | ㄧ  | ㄧㆪㆳ   | ㄞ  | ㄞㆮ     |
+//! | ㄍ  | ㄍㆣㆸㆼ | ㄨ  | ㄨㆫ     | ㄠ  | ㄠㆯ     |
+//! | ㄎ  | ㄎㆽ     | ㄚ  | ㄚㆩㆿ   | ㄢ  | ㄢㆰ     |
+//! | ㄗ  | ㄗㆡㆺ   | ㄛ  | ㄛㆦㆧㆾ | ㄣ  | ㄣㆬ     |
+//! | ㄐ  | ㄐㆢ     | ㄝ  | ㄝㆤㆥ   | ㄤ  | ㄤㆲ     |
+//! | ㄈ  | ㄈㄪ     | ㄜ  | ㄜㆨ     | ㄡ  | ㄡㆱ     |
+//! | ㄋ  | ㄋㄫㄬ   | ㄌ  | ㄌㆹ     | ㄥ  | ㄥㆭ     |
+//!
+//! After a medial or a rime, the keys of ㄆㄊㄎㄏㄍ type the stop finals
+//! ㆴㆵㆶㆷㆻ. The `[` and `]` keys are the tone marks ˪ and ˫. Checked
+//! syllables in the 4th tone and syllables in the 1st tone are completed with
+//! the space key.
+
+use crate::{
+    dictionary::{Dictionary, LookupStrategy},
+    editor::keyboard::{KeyCode, KeyEvent, KeyIndex, KeyboardLayout},
+    zhuyin::{Bopomofo, BopomofoKind, Syllable},
+};
+
+use super::{HINT_KEYS, KeyBehavior, SyllableCheck, SyllableEditor, is_known, leads_to_known};
+
+use Bopomofo::*;
+
+const CYCLES: [&[Bopomofo]; 21] = [
+    &[B, BU],
+    &[G, GU, GH, GW],
+    &[K, KW],
+    &[Z, ZI, ZY],
+    &[J, JI],
+    &[F, V],
+    &[N, NG, GN],
+    &[L, LH],
+    &[I, INN, INNN],
+    &[U, UNN],
+    &[A, ANN, AH],
+    &[O, OO, ONN, OE],
+    &[EH, EE, ENN],
+    &[E, IR],
+    &[AI, AINN],
+    &[AU, AUNN],
+    &[AN, AM],
+    &[EN, IM],
+    &[ANG, ONG],
+    &[OU, OM],
+    &[ENG, NGG],
+];
+
+const CODAS: [(Bopomofo, Bopomofo); 5] = [
+    (P, FINAL_P),
+    (T, FINAL_T),
+    (K, FINAL_K),
+    (H, FINAL_H),
+    (G, FINAL_G),
+];
+
+/// The most letters typed by one key.
+const MAX_CYCLE_LEN: usize = 4;
+
+/// Returns the letter after `last` typed by pressing the key of `base` again.
+fn next_in_cycle(base: Bopomofo, last: Bopomofo) -> Option<Bopomofo> {
+    let cycle = CYCLES.iter().find(|cycle| cycle[0] == base)?;
+    let pos = cycle.iter().position(|&b| b == last)?;
+    Some(cycle[(pos + 1) % cycle.len()])
+}
+
+/// Returns true if the syllable has at least one phrase in the dictionary.
+///
+/// Extended syllables are not in the syllable inventory, so without a
+/// dictionary any of them is accepted.
+fn is_known_extended(syl: Syllable, dict: Option<&dyn Dictionary>) -> bool {
+    if !syl.is_extended() {
+        return is_known(syl, dict);
+    }
+    dict.is_none_or(|dict| {
+        dict.lookup_first_phrase(&[syl], LookupStrategy::FuzzyPartialPrefix)
+            .is_some()
+    })
+}
+
+/// Returns true if the partial syllable can be completed to a Mandarin
+/// syllable, or to an extended syllable in the dictionary.
+fn leads_to_known_extended(partial: Syllable, dict: Option<&dyn Dictionary>) -> bool {
+    if !partial.is_extended() && leads_to_known(partial, dict) {
+        return true;
+    }
+    match dict {
+        Some(dict) => dict
+            .lookup_first_phrase(&[partial], LookupStrategy::FuzzyPartialPrefix)
+            .is_some(),
+        None => partial.is_extended(),
+    }
+}
+
+fn remove(syllable: &mut Syllable, bopomofo: Bopomofo) {
+    match bopomofo.kind() {
+        BopomofoKind::Initial => syllable.remove_initial(),
+        BopomofoKind::Medial => syllable.remove_medial(),
+        BopomofoKind::Rime => syllable.remove_rime(),
+        BopomofoKind::Coda => syllable.remove_coda(),
+        BopomofoKind::Tone => syllable.remove_tone(),
+    };
+}
+
+/// Taiwanese Phonetic Symbols layout
+#[derive(Debug, Clone, Copy)]
+pub struct Fangyin {
+    syllable: Syllable,
+    last: Option<(KeyIndex, Bopomofo)>,
+    check: SyllableCheck,
+}
+
+impl Fangyin {
+    /// Creates a new Taiwanese Phonetic Symbols layout.
+    pub fn new() -> Fangyin {
+        Fangyin {
+            syllable: Syllable::new(),
+            last: None,
+            check: SyllableCheck::default(),
+        }
+    }
+}
+
+impl Default for Fangyin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyllableEditor for Fangyin {
+    fn key_press(&mut self, key: KeyEvent) -> KeyBehavior {
+        let base = match key.index {
+            KeyIndex::K1 => B,
+            KeyIndex::K2 => D,
+            KeyIndex::K3 => TONE3,
+            KeyIndex::K4 => TONE4,
+            KeyIndex::K5 => ZH,
+            KeyIndex::K6 => TONE2,
+            KeyIndex::K7 => TONE5,
+            KeyIndex::K8 => A,
+            KeyIndex::K9 => AI,
+            KeyIndex::K10 => AN,
+            KeyIndex::K11 => ER,
+            KeyIndex::K15 => P,
+            KeyIndex::K16 => T,
+            KeyIndex::K17 => G,
+            KeyIndex::K18 => J,
+            KeyIndex::K19 => CH,
+            KeyIndex::K20 => Z,
+            KeyIndex::K21 => I,
+            KeyIndex::K22 => O,
+            KeyIndex::K23 => EI,
+            KeyIndex::K24 => EN,
+            KeyIndex::K25 => TONE_YIN_DEPARTING,
+            KeyIndex::K26 => TONE_YANG_DEPARTING,
+            KeyIndex::K27 => M,
+            KeyIndex::K28 => N,
+            KeyIndex::K29 => K,
+            KeyIndex::K30 => Q,
+            KeyIndex::K31 => SH,
+            KeyIndex::K32 => C,
+            KeyIndex::K33 => U,
+            KeyIndex::K34 => E,
+            KeyIndex::K35 => AU,
+            KeyIndex::K36 => ANG,
+            KeyIndex::K38 => F,
+            KeyIndex::K39 => L,
+            KeyIndex::K40 => H,
+            KeyIndex::K41 => X,
+            KeyIndex::K42 => R,
+            KeyIndex::K43 => S,
+            KeyIndex::K44 => IU,
+            KeyIndex::K45 => EH,
+            KeyIndex::K46 => OU,
+            KeyIndex::K47 => ENG,
+            KeyIndex::K48 => TONE1,
+            _ => return KeyBehavior::KeyError,
+        };
+        if base.kind() == BopomofoKind::Tone {
+            if self.syllable.is_empty() {
+                return KeyBehavior::KeyError;
+            }
+            if base != TONE1 {
+                self.syllable.update(base);
+            }
+            if !self.check.check(&mut self.syllable) {
+                self.syllable.remove_tone();
+                return KeyBehavior::InvalidSyllable;
+            }
+            self.last = None;
+            return KeyBehavior::Commit;
+        }
+        self.syllable.remove_tone();
+
+        let next = match self.last {
+            Some((index, last)) if index == key.index => next_in_cycle(base, last),
+            _ => None,
+        };
+        let bopomofo = if let Some(next) = next {
+            // Replace the letter typed by the previous press of the same key
+            remove(&mut self.syllable, self.last.unwrap().1);
+            next
+        } else if self.syllable.has_medial() || self.syllable.has_rime() {
+            CODAS
+                .iter()
+                .find(|(initial, _)| *initial == base)
+                .map_or(base, |&(_, coda)| coda)
+        } else {
+            base
+        };
+        self.syllable.update(bopomofo);
+        self.last = Some((key.index, bopomofo));
+        KeyBehavior::Absorb
+    }
+
+    fn is_empty(&self) -> bool {
+        self.syllable.is_empty()
+    }
+
+    fn remove_last(&mut self) {
+        self.syllable.pop();
+        self.last = None;
+    }
+
+    fn clear(&mut self) {
+        self.syllable.clear();
+        self.last = None;
+    }
+
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+    }
+
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        match key.index {
+            KeyIndex::K3 => Some(TONE3),
+            KeyIndex::K4 => Some(TONE4),
+            KeyIndex::K6 => Some(TONE2),
+            KeyIndex::K7 => Some(TONE5),
+            KeyIndex::K25 => Some(TONE_YIN_DEPARTING),
+            KeyIndex::K26 => Some(TONE_YANG_DEPARTING),
+            KeyIndex::K48 => Some(TONE1),
+            _ => None,
+        }
+    }
+
+    fn read(&self) -> Syllable {
+        self.syllable
+    }
+
+    /// Returns the keys that continue the syllable, including the keys
+    /// that only lead to an extended letter when pressed again.
+    fn next_keys(
+        &self,
+        keyboard: &dyn KeyboardLayout,
+        dict: Option<&dyn Dictionary>,
+    ) -> Vec<KeyCode> {
+        HINT_KEYS
+            .into_iter()
+            .filter(|&code| {
+                let key = keyboard.map(code);
+                let mut editor = *self;
+                (0..MAX_CYCLE_LEN).any(|_| match editor.key_press(key) {
+                    KeyBehavior::Commit => is_known_extended(editor.read(), dict),
+                    KeyBehavior::Absorb => leads_to_known_extended(editor.read(), dict),
+                    _ => false,
+                })
+            })
+            .collect()
+    }
+
+    fn clone(&self) -> Box<dyn SyllableEditor> {
+        Box::new(Clone::clone(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Seek};
+
+    use crate::{
+        dictionary::{DictionaryBuilder, TrieBuilder, TrieOpenOptions},
+        editor::{
+            keyboard::{KeyCode, KeyboardLayout, Qwerty},
+            zhuyin_layout::{KeyBehavior, SyllableEditor},
+        },
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::Fangyin;
+
+    fn type_keys(editor: &mut Fangyin, keys: &[KeyCode]) -> KeyBehavior {
+        let keyboard = Qwerty;
+        keys.iter()
+            .map(|&key| editor.key_press(keyboard.map(key)))
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn mandarin_syllable() {
+        let mut editor = Fangyin::new();
+
+        let behavior = type_keys(&mut editor, &[KeyCode::N5, KeyCode::J, KeyCode::Slash]);
+        assert_eq!(KeyBehavior::Absorb, behavior);
+        assert_eq!(syl![ZH, U, ENG], editor.read());
+    }
+
+    #[test]
+    fn cycle_extended_letters() {
+        let mut editor = Fangyin::new();
+
+        type_keys(&mut editor, &[KeyCode::E, KeyCode::E]);
+        assert_eq!(syl![GU], editor.read());
+        type_keys(&mut editor, &[KeyCode::E, KeyCode::E, KeyCode::E]);
+        assert_eq!(syl![G], editor.read());
+        type_keys(&mut editor, &[KeyCode::U, KeyCode::U]);
+        assert_eq!(syl![G, INN], editor.read());
+
+        let behavior = type_keys(&mut editor, &[KeyCode::RBracket]);
+        assert_eq!(KeyBehavior::Commit, behavior);
+        assert_eq!(syl![G, INN, TONE_YANG_DEPARTING], editor.read());
+    }
+
+    #[test]
+    fn stop_finals() {
+        let mut editor = Fangyin::new();
+
+        type_keys(&mut editor, &[KeyCode::W, KeyCode::N8, KeyCode::Q]);
+        assert_eq!(syl![T, A, FINAL_P], editor.read());
+        assert_eq!(Some(4), editor.read().hokkien_tone());
+
+        let behavior = type_keys(&mut editor, &[KeyCode::N7]);
+        assert_eq!(KeyBehavior::Commit, behavior);
+        assert_eq!(Some(8), editor.read().hokkien_tone());
+    }
+
+    #[test]
+    fn backspace_resets_cycle() {
+        let mut editor = Fangyin::new();
+
+        type_keys(&mut editor, &[KeyCode::N1, KeyCode::N1]);
+        editor.remove_last();
+        assert!(editor.is_empty());
+        type_keys(&mut editor, &[KeyCode::N1]);
+        assert_eq!(syl![B], editor.read());
+    }
+
+    #[test]
+    fn next_keys_offer_extended_letters() -> Result<(), Box<dyn std::error::Error>> {
+        let keyboard = Qwerty;
+        let mut builder = TrieBuilder::new();
+        builder.insert(&[syl![G, INN, TONE_YANG_DEPARTING]], ("見", 1).into())?;
+        builder.insert(&[syl![T, A, FINAL_P]], ("踏", 1).into())?;
+        let mut cursor = Cursor::new(vec![]);
+        builder.write(&mut cursor)?;
+        cursor.rewind()?;
+        let dict = TrieOpenOptions::new().read_from(&mut cursor)?;
+        let mut editor = Fangyin::new();
+
+        let keys = editor.next_keys(&keyboard, Some(&dict));
+        assert!(keys.contains(&KeyCode::E));
+        assert!(keys.contains(&KeyCode::W));
+        assert!(!keys.contains(&KeyCode::N1));
+
+        // ㆪ is typed by pressing the key of ㄧ twice
+        type_keys(&mut editor, &[KeyCode::E]);
+        let keys = editor.next_keys(&keyboard, Some(&dict));
+        assert!(keys.contains(&KeyCode::U));
+        assert!(!keys.contains(&KeyCode::N8));
+        type_keys(&mut editor, &[KeyCode::U, KeyCode::U]);
+        let keys = editor.next_keys(&keyboard, Some(&dict));
+        assert!(keys.contains(&KeyCode::RBracket));
+        assert!(!keys.contains(&KeyCode::LBracket));
+
+        editor.clear();
+        type_keys(&mut editor, &[KeyCode::W, KeyCode::N8]);
+        assert!(
+            editor
+                .next_keys(&keyboard, Some(&dict))
+                .contains(&KeyCode::Q)
+        );
+        Ok(())
+    }
+}
@@ -7,12 +7,13 @@ use crate::{
     zhuyin::{Bopomofo, BopomofoKind, Syllable},
 };
 
-use super::{KeyBehavior, SyllableEditor};
+use super::{KeyBehavior, SyllableCheck, SyllableEditor};
 
 /// TODO: docs
 #[derive(Debug, Clone, Copy)]
 pub struct GinYieh {
     syllable: Syllable,
+    check: SyllableCheck,
 }
 
 impl GinYieh {
@@ -20,6 +21,7 @@ impl GinYieh {
     pub fn new() -> GinYieh {
         GinYieh {
             syllable: Syllable::new(),
+            check: SyllableCheck::default(),
         }
     }
 }
@@ -81,6 +83,10 @@ impl SyllableEditor for GinYieh {
                 if bopomofo != Bopomofo::TONE1 {
                     self.syllable.update(bopomofo);
                 }
+                if !self.check.check(&mut self.syllable) {
+                    self.syllable.remove_tone();
+                    return KeyBehavior::InvalidSyllable;
+                }
                 return KeyBehavior::Commit;
             }
         } else {
@@ -108,6 +114,21 @@ impl SyllableEditor for GinYieh {
         self.syllable.clear()
     }
 
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+    }
+
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        match key.index {
+            KeyIndex::K1 => Some(Bopomofo::TONE5),
+            KeyIndex::K15 => Some(Bopomofo::TONE2),
+            KeyIndex::K27 => Some(Bopomofo::TONE3),
+            KeyIndex::K38 => Some(Bopomofo::TONE4),
+            KeyIndex::K48 => Some(Bopomofo::TONE1),
+            _ => None,
+        }
+    }
+
     fn read(&self) -> Syllable {
         self.syllable
     }
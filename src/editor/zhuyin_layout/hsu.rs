@@ -6,12 +6,13 @@ use crate::{
     zhuyin::{Bopomofo, BopomofoKind, Syllable},
 };
 
-use super::{KeyBehavior, KeyEvent, SyllableEditor};
+use super::{KeyBehavior, KeyEvent, SyllableCheck, SyllableEditor};
 
 /// TODO: docs
 #[derive(Debug, Clone, Copy)]
 pub struct Hsu {
     syllable: Syllable,
+    check: SyllableCheck,
 }
 
 impl Hsu {
@@ -19,6 +20,7 @@ impl Hsu {
     pub fn new() -> Hsu {
         Hsu {
             syllable: Default::default(),
+            check: SyllableCheck::default(),
         }
     }
 
@@ -136,6 +138,10 @@ impl SyllableEditor for Hsu {
                     self.syllable.remove_tone();
                 }
             };
+            if !self.check.check(&mut self.syllable) {
+                self.syllable.remove_tone();
+                return KeyBehavior::InvalidSyllable;
+            }
             KeyBehavior::Commit
         } else {
             let bopomofo = match key.code {
@@ -277,6 +283,21 @@ impl SyllableEditor for Hsu {
         self.syllable.clear();
     }
 
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+    }
+
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        match key.code {
+            KeyCode::D => Some(Bopomofo::TONE2),
+            KeyCode::F => Some(Bopomofo::TONE3),
+            KeyCode::J => Some(Bopomofo::TONE4),
+            KeyCode::S => Some(Bopomofo::TONE5),
+            KeyCode::Space => Some(Bopomofo::TONE1),
+            _ => None,
+        }
+    }
+
     fn read(&self) -> Syllable {
         self.syllable
     }
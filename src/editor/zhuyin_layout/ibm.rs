@@ -7,12 +7,13 @@ use crate::{
     zhuyin::{Bopomofo, BopomofoKind, Syllable},
 };
 
-use super::{KeyBehavior, SyllableEditor};
+use super::{KeyBehavior, SyllableCheck, SyllableEditor};
 
 /// TODO: docs
 #[derive(Debug, Clone, Copy)]
 pub struct Ibm {
     syllable: Syllable,
+    check: SyllableCheck,
 }
 
 impl Ibm {
@@ -20,6 +21,7 @@ impl Ibm {
     pub fn new() -> Ibm {
         Ibm {
             syllable: Syllable::new(),
+            check: SyllableCheck::default(),
         }
     }
 }
@@ -82,6 +84,10 @@ impl SyllableEditor for Ibm {
                 if bopomofo != Bopomofo::TONE1 {
                     self.syllable.update(bopomofo);
                 }
+                if !self.check.check(&mut self.syllable) {
+                    self.syllable.remove_tone();
+                    return KeyBehavior::InvalidSyllable;
+                }
                 return KeyBehavior::Commit;
             }
         } else {
@@ -109,6 +115,21 @@ impl SyllableEditor for Ibm {
         self.syllable.clear()
     }
 
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+    }
+
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        match key.index {
+            KeyIndex::K44 => Some(Bopomofo::TONE2),
+            KeyIndex::K45 => Some(Bopomofo::TONE3),
+            KeyIndex::K46 => Some(Bopomofo::TONE4),
+            KeyIndex::K47 => Some(Bopomofo::TONE5),
+            KeyIndex::K48 => Some(Bopomofo::TONE1),
+            _ => None,
+        }
+    }
+
     fn read(&self) -> Syllable {
         self.syllable
     }
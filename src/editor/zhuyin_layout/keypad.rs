@@ -0,0 +1,322 @@
+//! Phone keypad (9-key) layout
+//!
+//! Each digit key covers a group of bopomofo, so a key sequence can stand
+//! for several syllables. The layout only records the key sequence; the
+//! editor keeps every reading in the composition and the conversion engine
+//! picks the one that forms the best phrase with its neighbors.
+//!
+//! | Key | Bopomofo   |
+//! |-----|------------|
+//! | 1   | ㄅㄆㄇㄈ   |
+//! | 2   | ㄉㄊㄋㄌ   |
+//! | 3   | ㄍㄎㄏ     |
+//! | 4   | ㄐㄑㄒ     |
+//! | 5   | ㄓㄔㄕㄖ   |
+//! | 6   | ㄗㄘㄙ     |
+//! | 7   | ㄧㄨㄩ     |
+//! | 8   | ㄚㄛㄜㄝ   |
+//! | 9   | ㄞㄟㄠㄡ   |
+//! | 0   | ㄢㄣㄤㄥㄦ |
+//!
+//! The space key completes the syllable. There are no tone keys, so every
+//! tone of a syllable is one of its readings.
+
+use std::sync::LazyLock;
+
+use crate::{
+    dictionary::Dictionary,
+    editor::keyboard::{KeyCode, KeyEvent, KeyboardLayout},
+    zhuyin::{Bopomofo, BopomofoKind, Syllable, valid_syllables},
+};
+
+use super::{HINT_KEYS, KeyBehavior, SyllableEditor, is_known};
+
+const KEY_GROUPS: [(char, &str); 10] = [
+    ('1', "ㄅㄆㄇㄈ"),
+    ('2', "ㄉㄊㄋㄌ"),
+    ('3', "ㄍㄎㄏ"),
+    ('4', "ㄐㄑㄒ"),
+    ('5', "ㄓㄔㄕㄖ"),
+    ('6', "ㄗㄘㄙ"),
+    ('7', "ㄧㄨㄩ"),
+    ('8', "ㄚㄛㄜㄝ"),
+    ('9', "ㄞㄟㄠㄡ"),
+    ('0', "ㄢㄣㄤㄥㄦ"),
+];
+
+/// Every valid syllable in every tone, sorted by key sequence.
+static SYLLABLES: LazyLock<Vec<Syllable>> = LazyLock::new(|| {
+    let mut syllables: Vec<_> = valid_syllables()
+        .iter()
+        .flat_map(|&syl| {
+            [
+                None,
+                Some(Bopomofo::TONE2),
+                Some(Bopomofo::TONE3),
+                Some(Bopomofo::TONE4),
+                Some(Bopomofo::TONE5),
+            ]
+            .map(|tone| {
+                let mut syl = syl;
+                if let Some(tone) = tone {
+                    syl.update(tone);
+                }
+                syl
+            })
+        })
+        .collect();
+    syllables.sort_by_key(|&syl| (key_seq_of(syl), syl.to_u16()));
+    syllables
+});
+
+fn group_kind(key: char) -> Option<BopomofoKind> {
+    KEY_GROUPS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .and_then(|(_, group)| group.chars().next())
+        .and_then(|ch| Bopomofo::try_from(ch).ok())
+        .map(|bopomofo| bopomofo.kind())
+}
+
+fn key_of(bopomofo: Bopomofo) -> char {
+    let ch = char::from(bopomofo);
+    KEY_GROUPS
+        .iter()
+        .find(|(_, group)| group.contains(ch))
+        .map(|(key, _)| *key)
+        .expect("all initials, medials, and rimes should be in a key group")
+}
+
+fn key_seq_of(syl: Syllable) -> String {
+    [syl.initial(), syl.medial(), syl.rime()]
+        .into_iter()
+        .flatten()
+        .map(key_of)
+        .collect()
+}
+
+/// Returns all syllables typed with the key sequence.
+fn readings(key_seq: &str) -> &'static [Syllable] {
+    let start = SYLLABLES.partition_point(|&syl| key_seq_of(syl).as_str() < key_seq);
+    let len = SYLLABLES[start..]
+        .iter()
+        .take_while(|&&syl| key_seq_of(syl) == key_seq)
+        .count();
+    &SYLLABLES[start..start + len]
+}
+
+/// Phone keypad layout
+#[derive(Debug, Clone, Default)]
+pub struct Keypad {
+    key_seq: String,
+}
+
+impl Keypad {
+    /// Creates a new phone keypad layout.
+    pub fn new() -> Keypad {
+        Default::default()
+    }
+}
+
+impl SyllableEditor for Keypad {
+    fn key_press(&mut self, key: KeyEvent) -> KeyBehavior {
+        if key.code == KeyCode::Space {
+            if self.key_seq.is_empty() {
+                return KeyBehavior::KeyError;
+            }
+            if readings(&self.key_seq).is_empty() {
+                return KeyBehavior::InvalidSyllable;
+            }
+            return KeyBehavior::Commit;
+        }
+        let Some(kind) = group_kind(key.unicode) else {
+            return KeyBehavior::KeyError;
+        };
+        let in_order = match self.key_seq.chars().last().and_then(group_kind) {
+            None => true,
+            Some(BopomofoKind::Initial) => kind != BopomofoKind::Initial,
+            Some(BopomofoKind::Medial) => kind == BopomofoKind::Rime,
+            Some(_) => false,
+        };
+        if !in_order {
+            return KeyBehavior::KeyError;
+        }
+        self.key_seq.push(key.unicode);
+        KeyBehavior::Absorb
+    }
+
+    fn fuzzy_key_press(&mut self, key: KeyEvent) -> KeyBehavior {
+        self.key_press(key)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.key_seq.is_empty()
+    }
+
+    fn remove_last(&mut self) {
+        self.key_seq.pop();
+    }
+
+    fn clear(&mut self) {
+        self.key_seq.clear();
+    }
+
+    fn read(&self) -> Syllable {
+        readings(&self.key_seq).first().copied().unwrap_or_default()
+    }
+
+    fn key_seq(&self) -> Option<String> {
+        Some(self.key_seq.clone())
+    }
+
+    fn readings(&self) -> Vec<Syllable> {
+        readings(&self.key_seq).to_vec()
+    }
+
+    fn next_keys(
+        &self,
+        keyboard: &dyn KeyboardLayout,
+        dict: Option<&dyn Dictionary>,
+    ) -> Vec<KeyCode> {
+        HINT_KEYS
+            .into_iter()
+            .filter(|&code| {
+                if code == KeyCode::Space {
+                    return readings(&self.key_seq)
+                        .iter()
+                        .any(|&syl| is_known(syl, dict));
+                }
+                let unicode = keyboard.map(code).unicode;
+                if group_kind(unicode).is_none() {
+                    return false;
+                }
+                let mut key_seq = self.key_seq.clone();
+                key_seq.push(unicode);
+                SYLLABLES
+                    .iter()
+                    .any(|&syl| key_seq_of(syl).starts_with(&key_seq) && is_known(syl, dict))
+            })
+            .collect()
+    }
+
+    fn clone(&self) -> Box<dyn SyllableEditor> {
+        Box::new(Clone::clone(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dictionary::TrieBuf,
+        editor::{
+            keyboard::{KeyCode, KeyboardLayout, Qwerty},
+            zhuyin_layout::{KeyBehavior, SyllableEditor},
+        },
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::Keypad;
+
+    #[test]
+    fn readings_of_key_sequence() {
+        let keyboard = Qwerty;
+        let mut keypad = Keypad::new();
+
+        assert_eq!(
+            KeyBehavior::Absorb,
+            keypad.key_press(keyboard.map(KeyCode::N5))
+        );
+        assert_eq!(
+            KeyBehavior::Absorb,
+            keypad.key_press(keyboard.map(KeyCode::N7))
+        );
+        assert_eq!(
+            KeyBehavior::Absorb,
+            keypad.key_press(keyboard.map(KeyCode::N0))
+        );
+        assert_eq!(Some("570".to_string()), keypad.key_seq());
+
+        let readings = keypad.readings();
+        assert!(readings.contains(&keypad.read()));
+        assert!(readings.contains(&syl![ZH, U, ENG]));
+        assert!(readings.contains(&syl![SH, U, ANG]));
+        assert!(readings.contains(&syl![R, U, AN]));
+        assert!(readings.contains(&syl![ZH, U, ENG, TONE4]));
+        assert!(!readings.contains(&syl![R, IU, AN]));
+
+        assert_eq!(
+            KeyBehavior::Commit,
+            keypad.key_press(keyboard.map(KeyCode::Space))
+        );
+    }
+
+    #[test]
+    fn out_of_order_keys() {
+        let keyboard = Qwerty;
+        let mut keypad = Keypad::new();
+
+        assert_eq!(
+            KeyBehavior::KeyError,
+            keypad.key_press(keyboard.map(KeyCode::Space))
+        );
+        assert_eq!(
+            KeyBehavior::Absorb,
+            keypad.key_press(keyboard.map(KeyCode::N8))
+        );
+        assert_eq!(
+            KeyBehavior::KeyError,
+            keypad.key_press(keyboard.map(KeyCode::N1))
+        );
+        assert_eq!(
+            KeyBehavior::KeyError,
+            keypad.key_press(keyboard.map(KeyCode::N7))
+        );
+        assert_eq!(
+            KeyBehavior::KeyError,
+            keypad.key_press(keyboard.map(KeyCode::A))
+        );
+        assert_eq!(syl![A], keypad.read());
+
+        keypad.remove_last();
+        assert!(keypad.is_empty());
+    }
+
+    #[test]
+    fn readings_in_every_tone() {
+        let keyboard = Qwerty;
+        let mut keypad = Keypad::new();
+        keypad.key_press(keyboard.map(KeyCode::N1));
+        keypad.key_press(keyboard.map(KeyCode::N8));
+
+        let readings = keypad.readings();
+        assert!(readings.contains(&syl![B, A]));
+        assert!(readings.contains(&syl![P, A, TONE2]));
+        assert!(readings.contains(&syl![M, A, TONE3]));
+        assert!(readings.contains(&syl![B, A, TONE4]));
+    }
+
+    #[test]
+    fn next_keys() {
+        let keyboard = Qwerty;
+        let dict = TrieBuf::from([
+            (vec![syl![SH, U, ANG]], vec![("雙", 10)]),
+            (vec![syl![ZH, U, ENG]], vec![("中", 50)]),
+            (vec![syl![ZH, U, ENG, TONE4]], vec![("重", 100)]),
+        ]);
+        let mut keypad = Keypad::new();
+
+        assert_eq!(vec![KeyCode::N5], keypad.next_keys(&keyboard, Some(&dict)));
+        keypad.key_press(keyboard.map(KeyCode::N5));
+        assert_eq!(vec![KeyCode::N7], keypad.next_keys(&keyboard, Some(&dict)));
+        keypad.key_press(keyboard.map(KeyCode::N7));
+        keypad.key_press(keyboard.map(KeyCode::N0));
+        assert_eq!(
+            vec![KeyCode::Space],
+            keypad.next_keys(&keyboard, Some(&dict))
+        );
+
+        let keys = Keypad::new().next_keys(&keyboard, None);
+        assert_eq!(10, keys.len());
+    }
+}
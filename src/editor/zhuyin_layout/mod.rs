@@ -13,34 +13,46 @@
 //! combinations, to reduce the total keys required.
 //!
 //! Chewing currently supports the default layout, Hsu's layout, ET26 layout,
-//! DaChen CP26 layout, and the Pinyin layout.
+//! DaChen CP26 layout, the Pinyin layout, double pinyin layouts, and a phone
+//! keypad layout.
 
 use std::{
     fmt::{Debug, Display},
     str::FromStr,
 };
 
-use crate::zhuyin::Syllable;
+use crate::{
+    dictionary::{Dictionary, LookupStrategy},
+    zhuyin::{Bopomofo, Syllable, inventory},
+};
 
+pub(crate) use self::direct::DirectInput;
 pub use self::{
     dc26::DaiChien26,
+    double_pinyin::{DoublePinyin, DoublePinyinScheme, ParseDoublePinyinSchemeError},
     et::Et,
     et26::Et26,
+    fangyin::Fangyin,
     ginyieh::GinYieh,
     hsu::Hsu,
     ibm::Ibm,
+    keypad::Keypad,
     pinyin::{Pinyin, PinyinVariant},
     standard::Standard,
 };
 
-use super::keyboard::KeyEvent;
+use super::keyboard::{AnyKeyboardLayout, KeyCode, KeyEvent, KeyboardLayout};
 
 mod dc26;
+mod direct;
+mod double_pinyin;
 mod et;
 mod et26;
+mod fangyin;
 mod ginyieh;
 mod hsu;
 mod ibm;
+mod keypad;
 mod pinyin;
 mod standard;
 
@@ -82,6 +94,16 @@ pub enum KeyboardLayoutCompat {
     Workman,
     /// TODO: docs
     Colemak,
+    /// Microsoft double pinyin
+    MsDoublePinyin,
+    /// Ziranma double pinyin
+    ZiranmaDoublePinyin,
+    /// Xiaohe double pinyin
+    XiaoheDoublePinyin,
+    /// Phone keypad (9-key) layout
+    Keypad,
+    /// Taiwanese Phonetic Symbols (臺灣方音符號) layout
+    Fangyin,
 }
 
 #[derive(Debug)]
@@ -115,6 +137,11 @@ impl FromStr for KeyboardLayoutCompat {
             "KB_COLEMAK_DH_ANSI" => Self::ColemakDhAnsi,
             "KB_COLEMAK_DH_ORTH" => Self::ColemakDhOrth,
             "KB_WORKMAN" => Self::Workman,
+            "KB_MS_DOUBLE_PINYIN" => Self::MsDoublePinyin,
+            "KB_ZIRANMA_DOUBLE_PINYIN" => Self::ZiranmaDoublePinyin,
+            "KB_XIAOHE_DOUBLE_PINYIN" => Self::XiaoheDoublePinyin,
+            "KB_KEYPAD" => Self::Keypad,
+            "KB_FANGYIN" => Self::Fangyin,
             _ => return Err(ParseKeyboardLayoutError),
         };
         Ok(layout)
@@ -141,6 +168,11 @@ impl Display for KeyboardLayoutCompat {
             KeyboardLayoutCompat::ColemakDhAnsi => f.write_str("KB_COLEMAK_DH_ANSI"),
             KeyboardLayoutCompat::ColemakDhOrth => f.write_str("KB_COLEMAK_DH_ORTH"),
             KeyboardLayoutCompat::Workman => f.write_str("KB_WORKMAN"),
+            KeyboardLayoutCompat::MsDoublePinyin => f.write_str("KB_MS_DOUBLE_PINYIN"),
+            KeyboardLayoutCompat::ZiranmaDoublePinyin => f.write_str("KB_ZIRANMA_DOUBLE_PINYIN"),
+            KeyboardLayoutCompat::XiaoheDoublePinyin => f.write_str("KB_XIAOHE_DOUBLE_PINYIN"),
+            KeyboardLayoutCompat::Keypad => f.write_str("KB_KEYPAD"),
+            KeyboardLayoutCompat::Fangyin => f.write_str("KB_FANGYIN"),
         }
     }
 }
@@ -167,14 +199,63 @@ impl TryFrom<u8> for KeyboardLayoutCompat {
             14 => Self::ColemakDhOrth,
             15 => Self::Workman,
             16 => Self::Colemak,
+            17 => Self::MsDoublePinyin,
+            18 => Self::ZiranmaDoublePinyin,
+            19 => Self::XiaoheDoublePinyin,
+            20 => Self::Keypad,
+            21 => Self::Fangyin,
             _ => return Err(()),
         })
     }
 }
 
+impl KeyboardLayoutCompat {
+    /// Returns the keyboard layout used to map key codes for this layout.
+    pub fn keyboard(self) -> AnyKeyboardLayout {
+        use KeyboardLayoutCompat as KB;
+        match self {
+            KB::Dvorak => AnyKeyboardLayout::dvorak(),
+            KB::DvorakHsu => AnyKeyboardLayout::dvorak_on_qwerty(),
+            KB::Colemak => AnyKeyboardLayout::colemak(),
+            KB::ColemakDhAnsi => AnyKeyboardLayout::colemak_dh_ansi(),
+            KB::ColemakDhOrth => AnyKeyboardLayout::colemak_dh_orth(),
+            KB::Workman => AnyKeyboardLayout::workman(),
+            _ => AnyKeyboardLayout::qwerty(),
+        }
+    }
+    /// Returns a new syllable editor for this layout.
+    pub fn syllable_editor(self) -> Box<dyn SyllableEditor> {
+        use KeyboardLayoutCompat as KB;
+        match self {
+            KB::Default
+            | KB::Dvorak
+            | KB::Carpalx
+            | KB::Colemak
+            | KB::ColemakDhAnsi
+            | KB::ColemakDhOrth
+            | KB::Workman => Box::new(Standard::new()),
+            KB::Hsu | KB::DvorakHsu => Box::new(Hsu::new()),
+            KB::Ibm => Box::new(Ibm::new()),
+            KB::GinYieh => Box::new(GinYieh::new()),
+            KB::Et => Box::new(Et::new()),
+            KB::Et26 => Box::new(Et26::new()),
+            KB::DachenCp26 => Box::new(DaiChien26::new()),
+            KB::HanyuPinyin => Box::new(Pinyin::hanyu()),
+            KB::ThlPinyin => Box::new(Pinyin::thl()),
+            KB::Mps2Pinyin => Box::new(Pinyin::mps2()),
+            KB::MsDoublePinyin => Box::new(DoublePinyin::microsoft()),
+            KB::ZiranmaDoublePinyin => Box::new(DoublePinyin::ziranma()),
+            KB::XiaoheDoublePinyin => Box::new(DoublePinyin::xiaohe()),
+            KB::Keypad => Box::new(Keypad::new()),
+            KB::Fangyin => Box::new(Fangyin::new()),
+        }
+    }
+}
+
 /// TODO: docs
 /// TODO: move this to the editor module
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum KeyBehavior {
     /// TODO: docs
     Ignore,
@@ -192,6 +273,88 @@ pub enum KeyBehavior {
     OpenSymbolTable,
     /// Fuzzed Syllable
     Fuzzy(Syllable),
+    /// The completed syllable is not a valid Mandarin syllable
+    InvalidSyllable,
+}
+
+/// How layouts handle completed syllables that are not valid Mandarin
+/// syllables.
+///
+/// See [`Syllable::is_valid`]. Syllables using the Taiwanese Hokkien and
+/// Hakka extensions are always accepted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SyllableCheck {
+    /// Accept every syllable.
+    #[default]
+    Off,
+    /// Keep the syllable in the buffer and return
+    /// [`KeyBehavior::InvalidSyllable`].
+    Reject,
+    /// Replace the syllable with the nearest valid syllable. See
+    /// [`Syllable::nearest_valid`].
+    AutoCorrect,
+}
+
+impl SyllableCheck {
+    /// Checks a completed syllable, correcting it if enabled.
+    ///
+    /// Returns false if the syllable is rejected.
+    fn check(self, syl: &mut Syllable) -> bool {
+        match self {
+            SyllableCheck::Off => true,
+            _ if syl.is_valid() || syl.is_extended() => true,
+            SyllableCheck::Reject => false,
+            SyllableCheck::AutoCorrect => match syl.nearest_valid() {
+                Some(valid) => {
+                    *syl = valid;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+/// Keys that may be part of a syllable.
+#[rustfmt::skip]
+pub(super) const HINT_KEYS: [KeyCode; 48] = {
+    use KeyCode::*;
+    [
+        N1, N2, N3, N4, N5, N6, N7, N8, N9, N0, Minus, Equal, BSlash, Grave,
+        Q, W, E, R, T, Y, U, I, O, P, LBracket, RBracket,
+        A, S, D, F, G, H, J, K, L, SColon, Quote,
+        Z, X, C, V, B, N, M, Comma, Dot, Slash, Space,
+    ]
+};
+
+/// Returns true if the syllable is valid and, when a dictionary is given,
+/// has at least one phrase.
+fn is_known(syl: Syllable, dict: Option<&dyn Dictionary>) -> bool {
+    inventory::is_valid(syl)
+        && dict.is_none_or(|dict| {
+            dict.lookup_first_phrase(&[syl], LookupStrategy::FuzzyPartialPrefix)
+                .is_some()
+        })
+}
+
+/// Returns true if the partial syllable can be completed to a known syllable.
+fn leads_to_known(partial: Syllable, dict: Option<&dyn Dictionary>) -> bool {
+    if !partial.has_initial() && !partial.has_medial() && !partial.has_rime() {
+        return false;
+    }
+    let Some(dict) = dict else {
+        return inventory::is_valid_partial(partial);
+    };
+    inventory::valid_syllables()
+        .iter()
+        .filter(|&&syl| inventory::is_completed_by(partial, syl))
+        .any(|&syl| {
+            let mut syl = syl;
+            if let Some(tone) = partial.tone() {
+                syl.update(tone);
+            }
+            is_known(syl, Some(dict))
+        })
 }
 
 /// TODO: docs
@@ -235,11 +398,77 @@ pub trait SyllableEditor: Debug {
     fn key_seq(&self) -> Option<String> {
         None
     }
+    /// Sets how completed syllables that are not valid Mandarin syllables
+    /// are handled.
+    ///
+    /// Layouts that only produce valid syllables ignore this setting.
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        let _ = check;
+    }
     /// Returns the alternative syllable, if supported by the layout.
     fn alt_syllables(&self, syl: Syllable) -> &[Syllable] {
         let _ = syl;
         &[]
     }
+    /// Returns every syllable the typed keys can be read as, if the layout
+    /// cannot tell them apart.
+    ///
+    /// The editor keeps all readings in the composition and the conversion
+    /// picks the one that forms the best phrase. An empty list means the keys
+    /// are read as [`read`][SyllableEditor::read] only.
+    fn readings(&self) -> Vec<Syllable> {
+        vec![]
+    }
+    /// Returns the tone typed by the key after a complete syllable, if the
+    /// layout has tone keys.
+    ///
+    /// The first tone is returned as [`Bopomofo::TONE1`].
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        let _ = key;
+        None
+    }
+    /// Returns the tone typed by the key right after a committed syllable,
+    /// if the layout lets a trailing tone key amend the previous syllable.
+    ///
+    /// The editor replaces the previous syllable with its reading in that
+    /// tone. The first tone is returned as [`Bopomofo::TONE1`].
+    fn trailing_tone(&self, key: KeyEvent) -> Option<Bopomofo> {
+        let _ = key;
+        None
+    }
+    /// Returns the keys that can continue the current syllable.
+    ///
+    /// A key is included if it leads to a valid syllable. When `dict` is
+    /// given, the syllable must also have at least one phrase in the
+    /// dictionary. Syllables without tone match phrases of any tone.
+    ///
+    /// The default implementation checks the syllable returned by
+    /// [`read`][SyllableEditor::read] after each key, so layouts that buffer
+    /// keys before building a syllable should override it.
+    fn next_keys(
+        &self,
+        keyboard: &dyn KeyboardLayout,
+        dict: Option<&dyn Dictionary>,
+    ) -> Vec<KeyCode> {
+        HINT_KEYS
+            .into_iter()
+            .filter(|&code| {
+                let mut editor = self.clone();
+                match editor.key_press(keyboard.map(code)) {
+                    KeyBehavior::Commit => {
+                        let syl = editor.read();
+                        is_known(syl, dict)
+                            || editor
+                                .alt_syllables(syl)
+                                .iter()
+                                .any(|&alt| is_known(alt, dict))
+                    }
+                    KeyBehavior::Absorb => leads_to_known(editor.read(), dict),
+                    _ => false,
+                }
+            })
+            .collect()
+    }
     // Returns a copy of the SyllableEditor
     fn clone(&self) -> Box<dyn SyllableEditor>;
 }
@@ -8,12 +8,13 @@ use crate::{
     zhuyin::{Bopomofo, BopomofoKind, Syllable},
 };
 
-use super::{KeyBehavior, SyllableEditor};
+use super::{KeyBehavior, SyllableCheck, SyllableEditor};
 
 /// TODO: docs
 #[derive(Debug, Clone, Copy)]
 pub struct Standard {
     syllable: Syllable,
+    check: SyllableCheck,
 }
 
 impl Standard {
@@ -21,6 +22,7 @@ impl Standard {
     pub fn new() -> Standard {
         Standard {
             syllable: Syllable::new(),
+            check: SyllableCheck::default(),
         }
     }
 }
@@ -83,6 +85,10 @@ impl SyllableEditor for Standard {
                 if bopomofo != Bopomofo::TONE1 {
                     self.syllable.update(bopomofo);
                 }
+                if !self.check.check(&mut self.syllable) {
+                    self.syllable.remove_tone();
+                    return KeyBehavior::InvalidSyllable;
+                }
                 return KeyBehavior::Commit;
             }
         } else {
@@ -110,6 +116,21 @@ impl SyllableEditor for Standard {
         self.syllable.clear()
     }
 
+    fn set_syllable_check(&mut self, check: SyllableCheck) {
+        self.check = check;
+    }
+
+    fn tone_key(&self, key: KeyEvent) -> Option<Bopomofo> {
+        match key.index {
+            KeyIndex::K3 => Some(Bopomofo::TONE3),
+            KeyIndex::K4 => Some(Bopomofo::TONE4),
+            KeyIndex::K6 => Some(Bopomofo::TONE2),
+            KeyIndex::K7 => Some(Bopomofo::TONE5),
+            KeyIndex::K48 => Some(Bopomofo::TONE1),
+            _ => None,
+        }
+    }
+
     fn read(&self) -> Syllable {
         self.syllable
     }
@@ -125,9 +146,14 @@ impl SyllableEditor for Standard {
 
 #[cfg(test)]
 mod test {
-    use crate::editor::{
-        keyboard::{KeyCode, KeyboardLayout, Modifiers, Qwerty},
-        zhuyin_layout::{KeyBehavior, SyllableEditor},
+    use crate::{
+        dictionary::TrieBuf,
+        editor::{
+            keyboard::{KeyCode, KeyboardLayout, Modifiers, Qwerty},
+            zhuyin_layout::{KeyBehavior, SyllableCheck, SyllableEditor},
+        },
+        syl,
+        zhuyin::Bopomofo,
     };
 
     use super::Standard;
@@ -140,4 +166,56 @@ mod test {
             editor.key_press(keyboard.map_with_mod(KeyCode::Space, Modifiers::default()));
         assert_eq!(KeyBehavior::KeyError, behavior);
     }
+
+    #[test]
+    fn next_keys() {
+        let mut editor = Standard::new();
+        let keyboard = Qwerty;
+        editor.key_press(keyboard.map(KeyCode::N1));
+
+        let keys = editor.next_keys(&keyboard, None);
+        assert!(keys.contains(&KeyCode::N8));
+        assert!(keys.contains(&KeyCode::U));
+        assert!(!keys.contains(&KeyCode::M));
+        assert!(!keys.contains(&KeyCode::N4));
+        assert!(!keys.contains(&KeyCode::Space));
+
+        let dict = TrieBuf::from([(vec![syl![Bopomofo::B, Bopomofo::A]], vec![("八", 100)])]);
+        let keys = editor.next_keys(&keyboard, Some(&dict));
+        assert!(keys.contains(&KeyCode::N8));
+        assert!(!keys.contains(&KeyCode::U));
+
+        editor.key_press(keyboard.map(KeyCode::N8));
+        let keys = editor.next_keys(&keyboard, Some(&dict));
+        assert!(keys.contains(&KeyCode::Space));
+    }
+
+    #[test]
+    fn reject_invalid_syllable() {
+        let mut editor = Standard::new();
+        let keyboard = Qwerty;
+        editor.set_syllable_check(SyllableCheck::Reject);
+        editor.key_press(keyboard.map(KeyCode::Z));
+        editor.key_press(keyboard.map(KeyCode::U));
+
+        let behavior = editor.key_press(keyboard.map(KeyCode::N3));
+        assert_eq!(KeyBehavior::InvalidSyllable, behavior);
+        assert_eq!(syl![Bopomofo::F, Bopomofo::I], editor.read());
+    }
+
+    #[test]
+    fn auto_correct_invalid_syllable() {
+        let mut editor = Standard::new();
+        let keyboard = Qwerty;
+        editor.set_syllable_check(SyllableCheck::AutoCorrect);
+        editor.key_press(keyboard.map(KeyCode::Z));
+        editor.key_press(keyboard.map(KeyCode::U));
+
+        let behavior = editor.key_press(keyboard.map(KeyCode::N3));
+        assert_eq!(KeyBehavior::Commit, behavior);
+        assert_eq!(
+            syl![Bopomofo::F, Bopomofo::U, Bopomofo::TONE3],
+            editor.read()
+        );
+    }
 }
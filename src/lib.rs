@@ -70,6 +70,15 @@
 //!
 //! Other required files `swkb.dat` and `symbols.dat` can be copied directly to
 //! the dictionary folder.
+//!
+//! Dictionaries can be signed with `chewing-cli sign` using a [minisign] key.
+//! When the `CHEWING_SIGNATURE_POLICY` environment variable is `warn` or
+//! `enforce`, the loader checks the `.minisig` signature of each system and
+//! extra dictionary against the public keys in the `keys.d` folder of the
+//! system data directory, or in the folder of the `CHEWING_KEYS_DIR`
+//! environment variable. See [SignaturePolicy][dictionary::SignaturePolicy].
+//!
+//! [minisign]: https://jedisct1.github.io/minisign/
 pub mod conversion;
 pub mod dictionary;
 pub mod editor;
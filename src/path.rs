@@ -18,6 +18,7 @@ const SEARCH_PATH_SEP: char = ';';
 const SEARCH_PATH_SEP: char = ':';
 
 const DICT_FOLDER: &str = "dictionary.d";
+const KEYS_FOLDER: &str = "keys.d";
 
 pub(crate) fn sys_path_from_env_var() -> String {
     let chewing_path = env::var("CHEWING_PATH");
@@ -79,6 +80,43 @@ pub(crate) fn find_extra_dat_by_path(search_path: &str) -> Vec<PathBuf> {
     results
 }
 
+/// Returns the folder of the public keys trusted to sign dictionaries.
+///
+/// The folder is `keys.d` in the system data directory, or the folder in
+/// the `CHEWING_KEYS_DIR` environment variable. A folder inside any other
+/// entry of the search path is not trusted, because whoever can add
+/// dictionaries there can also add keys that sign them.
+pub(crate) fn trusted_keys_dir(search_path: &str) -> Option<PathBuf> {
+    let sys_datadir = Path::new(UNIX_SYS_PATH.unwrap_or(DEFAULT_UNIX_SYS_PATH));
+    let keys_dir = match env::var_os("CHEWING_KEYS_DIR") {
+        Some(keys_dir) => PathBuf::from(keys_dir),
+        None => sys_datadir.join(KEYS_FOLDER),
+    };
+    if !keys_dir.is_dir() || !is_trusted_keys_dir(&keys_dir, sys_datadir, search_path) {
+        return None;
+    }
+    info!("Found public keys folder {}", keys_dir.display());
+    Some(keys_dir)
+}
+
+fn is_trusted_keys_dir(keys_dir: &Path, sys_datadir: &Path, search_path: &str) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let keys_dir = canonical(keys_dir);
+    let sys_datadir = canonical(sys_datadir);
+    for path in search_path.split(SEARCH_PATH_SEP) {
+        let prefix = canonical(Path::new(path));
+        if prefix != sys_datadir && keys_dir.starts_with(&prefix) {
+            warn!(
+                "Ignored public keys folder {} in dictionary path {}",
+                keys_dir.display(),
+                prefix.display()
+            );
+            return false;
+        }
+    }
+    true
+}
+
 /// Returns the path to the user's default chewing data directory.
 ///
 /// The returned value depends on the operating system and is either a
@@ -148,7 +186,7 @@ fn project_data_dir() -> Option<PathBuf> {
         if let Ok(path) = env::var("XDG_DATA_HOME") {
             return Some(PathBuf::from(path).join("chewing"));
         }
-        return env::home_dir().map(|path| path.join(".local").join("share").join("chewing"));
+        env::home_dir().map(|path| path.join(".local").join("share").join("chewing"))
     }
 }
 
@@ -177,13 +215,34 @@ pub fn userphrase_path() -> Option<PathBuf> {
 
 #[cfg(test)]
 mod tests {
-    use super::{data_dir, project_data_dir};
+    use tempfile::tempdir;
+
+    use super::{SEARCH_PATH_SEP, data_dir, is_trusted_keys_dir, project_data_dir};
 
     #[test]
     fn support_project_data_dir() {
         assert!(project_data_dir().is_some());
     }
 
+    #[test]
+    fn trust_keys_outside_dictionary_path() {
+        let dir = tempdir().unwrap();
+        let user_dir = dir.path().join("user");
+        let sys_dir = dir.path().join("sys");
+        let search_path = format!(
+            "{}{SEARCH_PATH_SEP}{}",
+            user_dir.display(),
+            sys_dir.display()
+        );
+
+        let sys_keys = sys_dir.join("keys.d");
+        assert!(is_trusted_keys_dir(&sys_keys, &sys_dir, &search_path));
+        let user_keys = user_dir.join("keys.d");
+        assert!(!is_trusted_keys_dir(&user_keys, &sys_dir, &search_path));
+        let other_keys = dir.path().join("keys.d");
+        assert!(is_trusted_keys_dir(&other_keys, &sys_dir, &search_path));
+    }
+
     #[test]
     fn resolve_data_dir() {
         if project_data_dir().is_some() {
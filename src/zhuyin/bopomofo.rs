@@ -13,21 +13,33 @@ use std::{
 /// 2. Medial glides: ㄧㄨㄩ
 /// 3. Rimes: ㄚㄛㄜㄝㄞㄟㄠㄡㄢㄣㄤㄥㄦ
 /// 4. Tonal marks: ˙ˊˇˋ
+///
+/// The Taiwanese Hokkien and Hakka extensions add more initials and rimes,
+/// the stop finals of checked syllables (entering tones), and two more tone
+/// marks:
+///
+/// 1. Initial sounds: ㄪㄫㄬㆠㆡㆢㆣㆸㆹㆺㆼㆽ
+/// 2. Rimes: ㆤㆥㆦㆧㆨㆩㆪㆫㆬㆭㆮㆯㆰㆱㆲㆳㆾㆿ
+/// 3. Codas: ㆴㆵㆶㆷㆻ
+/// 4. Tonal marks: ˪˫
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BopomofoKind {
-    /// Initial sounds: ㄅㄆㄇㄈㄉㄊㄋㄌㄍㄎㄏㄐㄑㄒㄓㄔㄕㄖㄗㄘㄙ
+    /// Initial sounds: ㄅㄆㄇㄈㄉㄊㄋㄌㄍㄎㄏㄐㄑㄒㄓㄔㄕㄖㄗㄘㄙㄪㄫㄬㆠㆡㆢㆣㆸㆹㆺㆼㆽ
     Initial,
     /// Medial glides: ㄧㄨㄩ
     Medial,
-    /// Rimes: ㄚㄛㄜㄝㄞㄟㄠㄡㄢㄣㄤㄥㄦ
+    /// Rimes: ㄚㄛㄜㄝㄞㄟㄠㄡㄢㄣㄤㄥㄦㆤㆥㆦㆧㆨㆩㆪㆫㆬㆭㆮㆯㆰㆱㆲㆳㆾㆿ
     Rime,
-    /// Tonal marks: ˙ˊˇˋ
+    /// Stop finals of checked syllables: ㆴㆵㆶㆷㆻ
+    Coda,
+    /// Tonal marks: ˙ˊˇˋ˪˫
     Tone,
 }
 
 /// Zhuyin Fuhao, often shortened as zhuyin and commonly called bopomofo
 ///
 /// <https://simple.m.wikipedia.org/wiki/Zhuyin>
+#[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Bopomofo {
     /// Zhuyin Fuhao: ㄅ
@@ -114,16 +126,109 @@ pub enum Bopomofo {
     TONE4,
     /// Tonal mark: ˉ
     TONE1,
+    /// Zhuyin Fuhao: ㄪ
+    V,
+    /// Zhuyin Fuhao: ㄫ
+    NG,
+    /// Zhuyin Fuhao: ㄬ
+    GN,
+    /// Extended Zhuyin Fuhao: ㆠ
+    BU,
+    /// Extended Zhuyin Fuhao: ㆡ
+    ZI,
+    /// Extended Zhuyin Fuhao: ㆢ
+    JI,
+    /// Extended Zhuyin Fuhao: ㆣ
+    GU,
+    /// Extended Zhuyin Fuhao: ㆸ
+    GH,
+    /// Extended Zhuyin Fuhao: ㆹ
+    LH,
+    /// Extended Zhuyin Fuhao: ㆺ
+    ZY,
+    /// Extended Zhuyin Fuhao: ㆼ
+    GW,
+    /// Extended Zhuyin Fuhao: ㆽ
+    KW,
+    /// Extended Zhuyin Fuhao: ㆤ
+    EE,
+    /// Extended Zhuyin Fuhao: ㆥ
+    ENN,
+    /// Extended Zhuyin Fuhao: ㆦ
+    OO,
+    /// Extended Zhuyin Fuhao: ㆧ
+    ONN,
+    /// Extended Zhuyin Fuhao: ㆨ
+    IR,
+    /// Extended Zhuyin Fuhao: ㆩ
+    ANN,
+    /// Extended Zhuyin Fuhao: ㆪ
+    INN,
+    /// Extended Zhuyin Fuhao: ㆫ
+    UNN,
+    /// Extended Zhuyin Fuhao: ㆬ
+    IM,
+    /// Extended Zhuyin Fuhao: ㆭ
+    NGG,
+    /// Extended Zhuyin Fuhao: ㆮ
+    AINN,
+    /// Extended Zhuyin Fuhao: ㆯ
+    AUNN,
+    /// Extended Zhuyin Fuhao: ㆰ
+    AM,
+    /// Extended Zhuyin Fuhao: ㆱ
+    OM,
+    /// Extended Zhuyin Fuhao: ㆲ
+    ONG,
+    /// Extended Zhuyin Fuhao: ㆳ
+    INNN,
+    /// Extended Zhuyin Fuhao: ㆾ
+    OE,
+    /// Extended Zhuyin Fuhao: ㆿ
+    AH,
+    /// Extended Zhuyin Fuhao final: ㆴ
+    FINAL_P,
+    /// Extended Zhuyin Fuhao final: ㆵ
+    FINAL_T,
+    /// Extended Zhuyin Fuhao final: ㆶ
+    FINAL_K,
+    /// Extended Zhuyin Fuhao final: ㆷ
+    FINAL_H,
+    /// Extended Zhuyin Fuhao final: ㆻ
+    FINAL_G,
+    /// Tonal mark: ˪
+    TONE_YIN_DEPARTING,
+    /// Tonal mark: ˫
+    TONE_YANG_DEPARTING,
 }
 
 use Bopomofo::*;
 
-const INITIAL_MAP: [Bopomofo; 21] = [
-    B, P, M, F, D, T, N, L, G, K, H, J, Q, X, ZH, CH, SH, R, Z, C, S,
+const INITIAL_MAP: [Bopomofo; 33] = [
+    B, P, M, F, D, T, N, L, G, K, H, J, Q, X, ZH, CH, SH, R, Z, C, S, V, NG, GN, BU, ZI, JI, GU,
+    GH, LH, ZY, GW, KW,
 ];
 const MEDIAL_MAP: [Bopomofo; 3] = [I, U, IU];
-const RIME_MAP: [Bopomofo; 13] = [A, O, E, EH, AI, EI, AU, OU, AN, EN, ANG, ENG, ER];
-const TONE_MAP: [Bopomofo; 4] = [TONE5, TONE2, TONE3, TONE4];
+const RIME_MAP: [Bopomofo; 31] = [
+    A, O, E, EH, AI, EI, AU, OU, AN, EN, ANG, ENG, ER, EE, ENN, OO, ONN, IR, ANN, INN, UNN, IM,
+    NGG, AINN, AUNN, AM, OM, ONG, INNN, OE, AH,
+];
+const CODA_MAP: [Bopomofo; 5] = [FINAL_P, FINAL_T, FINAL_K, FINAL_H, FINAL_G];
+// A syllable completed with TONE1 is read back as having no tone.
+const TONE_MAP: [Option<Bopomofo>; 7] = [
+    Some(TONE5),
+    Some(TONE2),
+    Some(TONE3),
+    Some(TONE4),
+    None,
+    Some(TONE_YIN_DEPARTING),
+    Some(TONE_YANG_DEPARTING),
+];
+
+/// The number of initials, rimes, and tones used by Mandarin.
+pub(super) const MANDARIN_INITIALS: u16 = 21;
+pub(super) const MANDARIN_RIMES: u16 = 13;
+pub(super) const MANDARIN_TONES: u16 = 5;
 
 impl Bopomofo {
     /// Returns [`BopomofoKind`] of the [`Bopomofo`] symbol. See [`BopomofoKind`] to know more about
@@ -131,17 +236,34 @@ impl Bopomofo {
     pub const fn kind(&self) -> BopomofoKind {
         match self {
             B | P | M | F | D | T | N | L | G | K | H | J | Q | X | ZH | CH | SH | R | Z | C
-            | S => BopomofoKind::Initial,
+            | S | V | NG | GN | BU | ZI | JI | GU | GH | LH | ZY | GW | KW => BopomofoKind::Initial,
             I | U | IU => BopomofoKind::Medial,
-            A | O | E | EH | AI | EI | AU | OU | AN | EN | ANG | ENG | ER => BopomofoKind::Rime,
-            TONE1 | TONE2 | TONE3 | TONE4 | TONE5 => BopomofoKind::Tone,
+            A | O | E | EH | AI | EI | AU | OU | AN | EN | ANG | ENG | ER | EE | ENN | OO | ONN
+            | IR | ANN | INN | UNN | IM | NGG | AINN | AUNN | AM | OM | ONG | INNN | OE | AH => {
+                BopomofoKind::Rime
+            }
+            FINAL_P | FINAL_T | FINAL_K | FINAL_H | FINAL_G => BopomofoKind::Coda,
+            TONE1 | TONE2 | TONE3 | TONE4 | TONE5 | TONE_YIN_DEPARTING | TONE_YANG_DEPARTING => {
+                BopomofoKind::Tone
+            }
+        }
+    }
+    /// Returns whether the symbol is only used by the Taiwanese Hokkien and
+    /// Hakka extensions.
+    pub const fn is_extended(&self) -> bool {
+        match self.kind() {
+            BopomofoKind::Initial => self.index() > MANDARIN_INITIALS,
+            BopomofoKind::Medial => false,
+            BopomofoKind::Rime => self.index() > MANDARIN_RIMES,
+            BopomofoKind::Coda => true,
+            BopomofoKind::Tone => self.index() > MANDARIN_TONES,
         }
     }
     /// Returns a [`Bopomofo`] that is categorized as initial sounds based on the index. It will
-    /// return [`None`] if the index is larger than 20. The index order is listed below starting
+    /// return [`None`] if the index is larger than 32. The index order is listed below starting
     /// from 0.
     ///
-    /// - Initial sounds: ㄅㄆㄇㄈㄉㄊㄋㄌㄍㄎㄏㄐㄑㄒㄓㄔㄕㄖㄗㄘㄙ
+    /// - Initial sounds: ㄅㄆㄇㄈㄉㄊㄋㄌㄍㄎㄏㄐㄑㄒㄓㄔㄕㄖㄗㄘㄙㄪㄫㄬㆠㆡㆢㆣㆸㆹㆺㆼㆽ
     pub(super) const fn from_initial(index: u16) -> Option<Bopomofo> {
         if index as usize >= INITIAL_MAP.len() {
             return None;
@@ -160,50 +282,73 @@ impl Bopomofo {
         Some(MEDIAL_MAP[index as usize])
     }
     /// Returns a [`Bopomofo`] that is categorized as rimes based on the index. It will
-    /// return [`None`] if the index is larger than 12. The index order is listed below starting
+    /// return [`None`] if the index is larger than 30. The index order is listed below starting
     /// from 0.
     ///
-    /// - Rimes: ㄚㄛㄜㄝㄞㄟㄠㄡㄢㄣㄤㄥㄦ
+    /// - Rimes: ㄚㄛㄜㄝㄞㄟㄠㄡㄢㄣㄤㄥㄦㆤㆥㆦㆧㆨㆩㆪㆫㆬㆭㆮㆯㆰㆱㆲㆳㆾㆿ
     pub(super) const fn from_rime(index: u16) -> Option<Bopomofo> {
         if index as usize >= RIME_MAP.len() {
             return None;
         }
         Some(RIME_MAP[index as usize])
     }
-    /// Returns a [`Bopomofo`] that is categorized as tonal marks based on the index. It will
-    /// return [`None`] if the index is larger than 3. The index order is listed below starting
+    /// Returns a [`Bopomofo`] that is categorized as codas based on the index. It will
+    /// return [`None`] if the index is larger than 4. The index order is listed below starting
     /// from 0.
     ///
-    /// - Tonal marks: ˙ˊˇˋ
+    /// - Codas: ㆴㆵㆶㆷㆻ
+    pub(super) const fn from_coda(index: u16) -> Option<Bopomofo> {
+        if index as usize >= CODA_MAP.len() {
+            return None;
+        }
+        Some(CODA_MAP[index as usize])
+    }
+    /// Returns a [`Bopomofo`] that is categorized as tonal marks based on the index. It will
+    /// return [`None`] if the index is larger than 6 or is 4 ([`TONE1`][Bopomofo::TONE1]). The
+    /// index order is listed below starting from 0.
+    ///
+    /// - Tonal marks: ˙ˊˇˋˉ˪˫
     pub(super) const fn from_tone(index: u16) -> Option<Bopomofo> {
         if index as usize >= TONE_MAP.len() {
             return None;
         }
-        Some(TONE_MAP[index as usize])
+        TONE_MAP[index as usize]
     }
     pub(super) const fn index(&self) -> u16 {
         match self {
-            B | I | A | TONE5 => 1,
-            P | U | O | TONE2 => 2,
-            M | IU | E | TONE3 => 3,
-            F | EH | TONE4 => 4,
-            D | AI | TONE1 => 5,
-            T | EI => 6,
-            N | AU => 7,
+            B | I | A | FINAL_P | TONE5 => 1,
+            P | U | O | FINAL_T | TONE2 => 2,
+            M | IU | E | FINAL_K | TONE3 => 3,
+            F | EH | FINAL_H | TONE4 => 4,
+            D | AI | FINAL_G | TONE1 => 5,
+            T | EI | TONE_YIN_DEPARTING => 6,
+            N | AU | TONE_YANG_DEPARTING => 7,
             L | OU => 8,
             G | AN => 9,
             K | EN => 10,
             H | ANG => 11,
             J | ENG => 12,
             Q | ER => 13,
-            X => 14,
-            ZH => 15,
-            CH => 16,
-            SH => 17,
-            R => 18,
-            Z => 19,
-            C => 20,
-            S => 21,
+            X | EE => 14,
+            ZH | ENN => 15,
+            CH | OO => 16,
+            SH | ONN => 17,
+            R | IR => 18,
+            Z | ANN => 19,
+            C | INN => 20,
+            S | UNN => 21,
+            V | IM => 22,
+            NG | NGG => 23,
+            GN | AINN => 24,
+            BU | AUNN => 25,
+            ZI | AM => 26,
+            JI | OM => 27,
+            GU | ONG => 28,
+            GH | INNN => 29,
+            LH | OE => 30,
+            ZY | AH => 31,
+            GW => 32,
+            KW => 33,
         }
     }
 }
@@ -342,6 +487,43 @@ impl From<Bopomofo> for char {
             TONE2 => 'ˊ',
             TONE3 => 'ˇ',
             TONE4 => 'ˋ',
+            V => 'ㄪ',
+            NG => 'ㄫ',
+            GN => 'ㄬ',
+            BU => 'ㆠ',
+            ZI => 'ㆡ',
+            JI => 'ㆢ',
+            GU => 'ㆣ',
+            GH => 'ㆸ',
+            LH => 'ㆹ',
+            ZY => 'ㆺ',
+            GW => 'ㆼ',
+            KW => 'ㆽ',
+            EE => 'ㆤ',
+            ENN => 'ㆥ',
+            OO => 'ㆦ',
+            ONN => 'ㆧ',
+            IR => 'ㆨ',
+            ANN => 'ㆩ',
+            INN => 'ㆪ',
+            UNN => 'ㆫ',
+            IM => 'ㆬ',
+            NGG => 'ㆭ',
+            AINN => 'ㆮ',
+            AUNN => 'ㆯ',
+            AM => 'ㆰ',
+            OM => 'ㆱ',
+            ONG => 'ㆲ',
+            INNN => 'ㆳ',
+            OE => 'ㆾ',
+            AH => 'ㆿ',
+            FINAL_P => 'ㆴ',
+            FINAL_T => 'ㆵ',
+            FINAL_K => 'ㆶ',
+            FINAL_H => 'ㆷ',
+            FINAL_G => 'ㆻ',
+            TONE_YIN_DEPARTING => '˪',
+            TONE_YANG_DEPARTING => '˫',
         }
     }
 }
@@ -393,6 +575,43 @@ impl TryFrom<char> for Bopomofo {
             'ˊ' => Ok(TONE2),
             'ˇ' => Ok(TONE3),
             'ˋ' => Ok(TONE4),
+            'ㄪ' => Ok(V),
+            'ㄫ' => Ok(NG),
+            'ㄬ' => Ok(GN),
+            'ㆠ' => Ok(BU),
+            'ㆡ' => Ok(ZI),
+            'ㆢ' => Ok(JI),
+            'ㆣ' => Ok(GU),
+            'ㆸ' => Ok(GH),
+            'ㆹ' => Ok(LH),
+            'ㆺ' => Ok(ZY),
+            'ㆼ' => Ok(GW),
+            'ㆽ' => Ok(KW),
+            'ㆤ' => Ok(EE),
+            'ㆥ' => Ok(ENN),
+            'ㆦ' => Ok(OO),
+            'ㆧ' => Ok(ONN),
+            'ㆨ' => Ok(IR),
+            'ㆩ' => Ok(ANN),
+            'ㆪ' => Ok(INN),
+            'ㆫ' => Ok(UNN),
+            'ㆬ' => Ok(IM),
+            'ㆭ' => Ok(NGG),
+            'ㆮ' => Ok(AINN),
+            'ㆯ' => Ok(AUNN),
+            'ㆰ' => Ok(AM),
+            'ㆱ' => Ok(OM),
+            'ㆲ' => Ok(ONG),
+            'ㆳ' => Ok(INNN),
+            'ㆾ' => Ok(OE),
+            'ㆿ' => Ok(AH),
+            'ㆴ' => Ok(FINAL_P),
+            'ㆵ' => Ok(FINAL_T),
+            'ㆶ' => Ok(FINAL_K),
+            'ㆷ' => Ok(FINAL_H),
+            'ㆻ' => Ok(FINAL_G),
+            '˪' => Ok(TONE_YIN_DEPARTING),
+            '˫' => Ok(TONE_YANG_DEPARTING),
             _ => Err(ParseBopomofoError::invalid_symbol()),
         }
     }
@@ -434,4 +653,16 @@ mod tests {
     fn to_string() {
         assert_eq!(Bopomofo::B.to_string(), "ㄅ")
     }
+
+    #[test]
+    fn extended_bopomofo_block() {
+        for c in '\u{31A0}'..='\u{31BF}' {
+            let bopomofo = Bopomofo::try_from(c).unwrap();
+            assert!(bopomofo.is_extended());
+            assert_eq!(c, char::from(bopomofo));
+        }
+        assert!(!Bopomofo::ZH.is_extended());
+        assert!(Bopomofo::TONE_YIN_DEPARTING.is_extended());
+        assert!(!Bopomofo::TONE4.is_extended());
+    }
 }
@@ -0,0 +1,184 @@
+//! Inventory of valid Mandarin syllables
+
+use std::sync::LazyLock;
+
+use super::Syllable;
+
+/// All valid syllables without tones, grouped by initial.
+const INVENTORY: &str = "
+    ㄧ ㄧㄚ ㄧㄛ ㄧㄝ ㄧㄞ ㄧㄠ ㄧㄡ ㄧㄢ ㄧㄣ ㄧㄤ ㄧㄥ ㄨ ㄨㄚ ㄨㄛ ㄨㄞ ㄨㄟ ㄨㄢ ㄨㄣ ㄨㄤ
+    ㄨㄥ ㄩ ㄩㄝ ㄩㄢ ㄩㄣ ㄩㄥ ㄚ ㄛ ㄜ ㄝ ㄞ ㄟ ㄠ ㄡ ㄢ ㄣ ㄤ ㄥ ㄦ
+    ㄅㄧ ㄅㄧㄝ ㄅㄧㄠ ㄅㄧㄢ ㄅㄧㄣ ㄅㄧㄥ ㄅㄨ ㄅㄚ ㄅㄛ ㄅㄞ ㄅㄟ ㄅㄠ ㄅㄢ ㄅㄣ ㄅㄤ ㄅㄥ
+    ㄆㄧ ㄆㄧㄝ ㄆㄧㄠ ㄆㄧㄢ ㄆㄧㄣ ㄆㄧㄥ ㄆㄨ ㄆㄚ ㄆㄛ ㄆㄞ ㄆㄟ ㄆㄠ ㄆㄡ ㄆㄢ ㄆㄣ ㄆㄤ ㄆㄥ
+    ㄇㄧ ㄇㄧㄝ ㄇㄧㄠ ㄇㄧㄡ ㄇㄧㄢ ㄇㄧㄣ ㄇㄧㄥ ㄇㄨ ㄇㄚ ㄇㄛ ㄇㄜ ㄇㄞ ㄇㄟ ㄇㄠ ㄇㄡ ㄇㄢ ㄇㄣ
+    ㄇㄤ ㄇㄥ
+    ㄈㄧㄠ ㄈㄨ ㄈㄨㄥ ㄈㄚ ㄈㄛ ㄈㄟ ㄈㄡ ㄈㄢ ㄈㄣ ㄈㄤ ㄈㄥ
+    ㄉㄧ ㄉㄧㄚ ㄉㄧㄝ ㄉㄧㄠ ㄉㄧㄡ ㄉㄧㄢ ㄉㄧㄥ ㄉㄨ ㄉㄨㄛ ㄉㄨㄟ ㄉㄨㄢ ㄉㄨㄣ ㄉㄨㄥ ㄉㄚ ㄉㄜ
+    ㄉㄞ ㄉㄟ ㄉㄠ ㄉㄡ ㄉㄢ ㄉㄣ ㄉㄤ ㄉㄥ
+    ㄊㄧ ㄊㄧㄝ ㄊㄧㄠ ㄊㄧㄢ ㄊㄧㄥ ㄊㄨ ㄊㄨㄛ ㄊㄨㄟ ㄊㄨㄢ ㄊㄨㄣ ㄊㄨㄥ ㄊㄚ ㄊㄜ ㄊㄞ ㄊㄟ ㄊㄠ
+    ㄊㄡ ㄊㄢ ㄊㄤ ㄊㄥ
+    ㄋㄧ ㄋㄧㄚ ㄋㄧㄝ ㄋㄧㄠ ㄋㄧㄡ ㄋㄧㄢ ㄋㄧㄣ ㄋㄧㄤ ㄋㄧㄥ ㄋㄨ ㄋㄨㄛ ㄋㄨㄢ ㄋㄨㄣ ㄋㄨㄥ ㄋㄩ
+    ㄋㄩㄝ ㄋㄚ ㄋㄜ ㄋㄞ ㄋㄟ ㄋㄠ ㄋㄡ ㄋㄢ ㄋㄣ ㄋㄤ ㄋㄥ
+    ㄌㄧ ㄌㄧㄚ ㄌㄧㄝ ㄌㄧㄠ ㄌㄧㄡ ㄌㄧㄢ ㄌㄧㄣ ㄌㄧㄤ ㄌㄧㄥ ㄌㄨ ㄌㄨㄛ ㄌㄨㄢ ㄌㄨㄣ ㄌㄨㄥ ㄌㄩ
+    ㄌㄩㄝ ㄌㄩㄢ ㄌㄚ ㄌㄛ ㄌㄜ ㄌㄞ ㄌㄟ ㄌㄠ ㄌㄡ ㄌㄢ ㄌㄤ ㄌㄥ
+    ㄍㄨ ㄍㄨㄚ ㄍㄨㄛ ㄍㄨㄞ ㄍㄨㄟ ㄍㄨㄢ ㄍㄨㄣ ㄍㄨㄤ ㄍㄨㄥ ㄍㄚ ㄍㄜ ㄍㄞ ㄍㄟ ㄍㄠ ㄍㄡ ㄍㄢ
+    ㄍㄣ ㄍㄤ ㄍㄥ
+    ㄎㄨ ㄎㄨㄚ ㄎㄨㄛ ㄎㄨㄞ ㄎㄨㄟ ㄎㄨㄢ ㄎㄨㄣ ㄎㄨㄤ ㄎㄨㄥ ㄎㄚ ㄎㄜ ㄎㄞ ㄎㄟ ㄎㄠ ㄎㄡ ㄎㄢ
+    ㄎㄣ ㄎㄤ ㄎㄥ
+    ㄏㄨ ㄏㄨㄚ ㄏㄨㄛ ㄏㄨㄞ ㄏㄨㄟ ㄏㄨㄢ ㄏㄨㄣ ㄏㄨㄤ ㄏㄨㄥ ㄏㄚ ㄏㄜ ㄏㄞ ㄏㄟ ㄏㄠ ㄏㄡ ㄏㄢ
+    ㄏㄣ ㄏㄤ ㄏㄥ
+    ㄐㄧ ㄐㄧㄚ ㄐㄧㄝ ㄐㄧㄠ ㄐㄧㄡ ㄐㄧㄢ ㄐㄧㄣ ㄐㄧㄤ ㄐㄧㄥ ㄐㄩ ㄐㄩㄝ ㄐㄩㄢ ㄐㄩㄣ ㄐㄩㄥ
+    ㄑㄧ ㄑㄧㄚ ㄑㄧㄝ ㄑㄧㄠ ㄑㄧㄡ ㄑㄧㄢ ㄑㄧㄣ ㄑㄧㄤ ㄑㄧㄥ ㄑㄩ ㄑㄩㄝ ㄑㄩㄢ ㄑㄩㄣ ㄑㄩㄥ
+    ㄒㄧ ㄒㄧㄚ ㄒㄧㄝ ㄒㄧㄠ ㄒㄧㄡ ㄒㄧㄢ ㄒㄧㄣ ㄒㄧㄤ ㄒㄧㄥ ㄒㄩ ㄒㄩㄝ ㄒㄩㄢ ㄒㄩㄣ ㄒㄩㄥ
+    ㄓ ㄓㄨ ㄓㄨㄚ ㄓㄨㄛ ㄓㄨㄞ ㄓㄨㄟ ㄓㄨㄢ ㄓㄨㄣ ㄓㄨㄤ ㄓㄨㄥ ㄓㄚ ㄓㄜ ㄓㄞ ㄓㄟ ㄓㄠ ㄓㄡ
+    ㄓㄢ ㄓㄣ ㄓㄤ ㄓㄥ
+    ㄔ ㄔㄨ ㄔㄨㄚ ㄔㄨㄛ ㄔㄨㄞ ㄔㄨㄟ ㄔㄨㄢ ㄔㄨㄣ ㄔㄨㄤ ㄔㄨㄥ ㄔㄚ ㄔㄜ ㄔㄞ ㄔㄠ ㄔㄡ ㄔㄢ
+    ㄔㄣ ㄔㄤ ㄔㄥ
+    ㄕ ㄕㄨ ㄕㄨㄚ ㄕㄨㄛ ㄕㄨㄞ ㄕㄨㄟ ㄕㄨㄢ ㄕㄨㄣ ㄕㄨㄤ ㄕㄚ ㄕㄜ ㄕㄞ ㄕㄟ ㄕㄠ ㄕㄡ ㄕㄢ ㄕㄣ
+    ㄕㄤ ㄕㄥ
+    ㄖ ㄖㄨ ㄖㄨㄛ ㄖㄨㄟ ㄖㄨㄢ ㄖㄨㄣ ㄖㄨㄥ ㄖㄜ ㄖㄠ ㄖㄡ ㄖㄢ ㄖㄣ ㄖㄤ ㄖㄥ
+    ㄗ ㄗㄨ ㄗㄨㄛ ㄗㄨㄟ ㄗㄨㄢ ㄗㄨㄣ ㄗㄨㄥ ㄗㄚ ㄗㄜ ㄗㄞ ㄗㄟ ㄗㄠ ㄗㄡ ㄗㄢ ㄗㄣ ㄗㄤ ㄗㄥ
+    ㄘ ㄘㄨ ㄘㄨㄛ ㄘㄨㄟ ㄘㄨㄢ ㄘㄨㄣ ㄘㄨㄥ ㄘㄚ ㄘㄜ ㄘㄞ ㄘㄠ ㄘㄡ ㄘㄢ ㄘㄣ ㄘㄤ ㄘㄥ
+    ㄙ ㄙㄨ ㄙㄨㄛ ㄙㄨㄟ ㄙㄨㄢ ㄙㄨㄣ ㄙㄨㄥ ㄙㄚ ㄙㄜ ㄙㄞ ㄙㄟ ㄙㄠ ㄙㄡ ㄙㄢ ㄙㄣ ㄙㄤ ㄙㄥ
+";
+
+static SYLLABLES: LazyLock<Vec<Syllable>> = LazyLock::new(|| {
+    let mut syllables: Vec<Syllable> = INVENTORY
+        .split_whitespace()
+        .map(|s| {
+            s.parse()
+                .expect("inventory should only contain valid syllables")
+        })
+        .collect();
+    syllables.sort_by_key(|syl| syl.to_u16());
+    syllables
+});
+
+/// Returns all valid Mandarin syllables without tones.
+///
+/// The syllables are sorted by their encoded value.
+///
+/// # Examples
+///
+/// ```
+/// use chewing::{syl, zhuyin::{valid_syllables, Bopomofo}};
+///
+/// assert!(valid_syllables().contains(&syl![Bopomofo::ZH, Bopomofo::U, Bopomofo::ENG]));
+/// ```
+pub fn valid_syllables() -> &'static [Syllable] {
+    &SYLLABLES
+}
+
+fn without_tone(mut syl: Syllable) -> Syllable {
+    syl.remove_tone();
+    syl
+}
+
+/// Returns true if the syllable, ignoring its tone, is a valid syllable.
+pub(crate) fn is_valid(syl: Syllable) -> bool {
+    !syl.is_extended()
+        && SYLLABLES
+            .binary_search_by_key(&without_tone(syl).to_u16(), |syl| syl.to_u16())
+            .is_ok()
+}
+
+/// Returns true if the syllable, ignoring its tone, can be completed to a
+/// valid syllable by adding the missing initial, medial, or rime.
+pub(crate) fn is_valid_partial(syl: Syllable) -> bool {
+    without_tone(syl).is_empty() || SYLLABLES.iter().any(|&valid| is_completed_by(syl, valid))
+}
+
+/// Returns true if every initial, medial, and rime of `partial` is also in
+/// `syl`.
+pub(crate) fn is_completed_by(partial: Syllable, syl: Syllable) -> bool {
+    (partial.initial().is_none() || partial.initial() == syl.initial())
+        && (partial.medial().is_none() || partial.medial() == syl.medial())
+        && (partial.rime().is_none() || partial.rime() == syl.rime())
+}
+
+impl Syllable {
+    /// Returns true if the syllable is a valid Mandarin syllable.
+    ///
+    /// The tone is not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chewing::{syl, zhuyin::Bopomofo};
+    ///
+    /// assert!(syl![Bopomofo::B, Bopomofo::I, Bopomofo::TONE4].is_valid());
+    /// assert!(!syl![Bopomofo::B, Bopomofo::IU].is_valid());
+    /// ```
+    pub fn is_valid(&self) -> bool {
+        is_valid(*self)
+    }
+    /// Returns the valid syllable that differs the least from this syllable.
+    ///
+    /// Keeping the initial is preferred over keeping the rime, which is
+    /// preferred over keeping the medial. The tone is kept. Returns `None`
+    /// if the syllable has no initial, medial, or rime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chewing::{syl, zhuyin::Bopomofo};
+    ///
+    /// assert_eq!(
+    ///     Some(syl![Bopomofo::F, Bopomofo::U, Bopomofo::TONE2]),
+    ///     syl![Bopomofo::F, Bopomofo::I, Bopomofo::TONE2].nearest_valid()
+    /// );
+    /// ```
+    pub fn nearest_valid(&self) -> Option<Syllable> {
+        if !self.has_initial() && !self.has_medial() && !self.has_rime() {
+            return None;
+        }
+        let distance = |valid: Syllable| {
+            4 * usize::from(self.initial() != valid.initial())
+                + 2 * usize::from(self.rime() != valid.rime())
+                + usize::from(self.medial() != valid.medial())
+        };
+        let mut nearest = *SYLLABLES.iter().min_by_key(|&&valid| distance(valid))?;
+        if let Some(tone) = self.tone() {
+            nearest.update(tone);
+        }
+        Some(nearest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{syl, zhuyin::Bopomofo::*};
+
+    use super::{is_valid, is_valid_partial, valid_syllables};
+
+    #[test]
+    fn valid_complete_syllables() {
+        assert!(is_valid(syl![ZH, U, ENG]));
+        assert!(is_valid(syl![L, IU, TONE4]));
+        assert!(is_valid(syl![S]));
+        assert!(is_valid(syl![ER, TONE2]));
+        assert!(!is_valid(syl![B, IU]));
+        assert!(!is_valid(syl![F, I]));
+        assert!(!is_valid(syl![B]));
+        assert_eq!(418, valid_syllables().len());
+    }
+
+    #[test]
+    fn nearest_valid_syllables() {
+        assert_eq!(Some(syl![ZH, U, ENG]), syl![ZH, U, ENG].nearest_valid());
+        assert_eq!(Some(syl![F, U, TONE3]), syl![F, I, TONE3].nearest_valid());
+        assert_eq!(Some(syl![B, I]), syl![B, IU].nearest_valid());
+        assert_eq!(Some(syl![G, AN]), syl![G, I, AN].nearest_valid());
+        assert_eq!(None, syl![TONE4].nearest_valid());
+    }
+
+    #[test]
+    fn valid_partial_syllables() {
+        assert!(is_valid_partial(syl![ZH, ENG]));
+        assert!(is_valid_partial(syl![IU]));
+        assert!(is_valid_partial(syl![B]));
+        assert!(!is_valid_partial(syl![B, IU]));
+        assert!(!is_valid_partial(syl![G, I]));
+        assert!(!is_valid_partial(syl![ZH, ER]));
+    }
+}
@@ -1,10 +1,15 @@
-//! Chinese syllables and bopomofo phonetic symbols.
+//! Chinese syllables, bopomofo phonetic symbols, and their romanization.
 
 mod bopomofo;
+pub(crate) mod inventory;
+mod romanization;
 mod syllable;
 
 pub use bopomofo::{Bopomofo, BopomofoErrorKind, BopomofoKind, ParseBopomofoError};
+pub use inventory::valid_syllables;
+pub(crate) use romanization::resolve_pinyin;
+pub use romanization::{PinyinVariant, ToneStyle};
 pub use syllable::{
-    BuildSyllableError, DecodeSyllableError, ParseSyllableError, Syllable, SyllableBuilder,
-    SyllableErrorKind, SyllableSlice,
+    BuildSyllableError, DecodeSyllableError, DecodeU16, ParseSyllableError, Syllable,
+    SyllableBuilder, SyllableErrorKind, SyllableSlice,
 };
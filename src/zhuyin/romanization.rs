@@ -0,0 +1,986 @@
+//! Romanization of syllables
+//!
+//! Conversions between [`Syllable`] and the romanization systems commonly
+//! used to annotate Mandarin, plus a broad IPA transcription.
+
+use super::{Bopomofo, ParseSyllableError, Syllable};
+
+/// The romanization systems supported by the pinyin layout and the
+/// [`Syllable`] conversion methods.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PinyinVariant {
+    /// [Hanyu Pinyin](https://en.wikipedia.org/wiki/Pinyin), or simply pinyin
+    #[default]
+    HanyuPinyin,
+    /// [THL, Taíwan Huáyǔ Luómǎ Pinyin](https://pinyin.thl.tw/)
+    ThlPinyin,
+    /// [MPS2, Mandarin Phonetic Symbols II](https://pinyin.info/romanization/mps2)
+    Mps2Pinyin,
+}
+
+/// How the tone is written in romanized output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ToneStyle {
+    /// Omits the tone, e.g. `zhong`.
+    None,
+    /// Appends the tone number 1 to 5, e.g. `zhong1`.
+    #[default]
+    Number,
+    /// Places a tone diacritic on the main vowel, e.g. `zhōng`.
+    Diacritic,
+}
+
+impl Syllable {
+    /// Returns the romanized spelling of the syllable.
+    ///
+    /// A syllable without a tone mark is treated as the first tone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chewing::syl;
+    /// use chewing::zhuyin::{Bopomofo::*, PinyinVariant, ToneStyle};
+    ///
+    /// let syl = syl![ZH, U, ENG];
+    ///
+    /// assert_eq!("zhong1", syl.to_pinyin(PinyinVariant::HanyuPinyin, ToneStyle::Number));
+    /// assert_eq!("zhōng", syl.to_pinyin(PinyinVariant::HanyuPinyin, ToneStyle::Diacritic));
+    /// assert_eq!("jhong", syl.to_pinyin(PinyinVariant::ThlPinyin, ToneStyle::Diacritic));
+    /// assert_eq!("jung1", syl.to_pinyin(PinyinVariant::Mps2Pinyin, ToneStyle::Number));
+    /// ```
+    pub fn to_pinyin(&self, variant: PinyinVariant, style: ToneStyle) -> String {
+        let spelling = spell(self, variant);
+        let tone = tone_number(self);
+        match style {
+            ToneStyle::None => spelling,
+            ToneStyle::Number => format!("{spelling}{tone}"),
+            ToneStyle::Diacritic => place_tone_mark(&spelling, tone, variant),
+        }
+    }
+    /// Returns a broad IPA transcription of the syllable with Chao tone
+    /// letters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chewing::syl;
+    /// use chewing::zhuyin::Bopomofo::*;
+    ///
+    /// assert_eq!("ʈʂʊŋ˥", syl![ZH, U, ENG].to_ipa());
+    /// assert_eq!("ɕjɛn˨˩˦", syl![X, I, AN, TONE3].to_ipa());
+    /// ```
+    pub fn to_ipa(&self) -> String {
+        let mut ipa = String::new();
+        if let Some(initial) = self.initial() {
+            ipa.push_str(ipa_initial(initial));
+        }
+        if self.medial().is_none() && self.rime().is_none() {
+            ipa.push_str(match self.initial() {
+                Some(Bopomofo::ZH | Bopomofo::CH | Bopomofo::SH | Bopomofo::R) => "ɻ̩",
+                Some(Bopomofo::Z | Bopomofo::C | Bopomofo::S) => "ɹ̩",
+                _ => "",
+            });
+        } else if let Some(entry) = find_final(self.medial(), self.rime()) {
+            ipa.push_str(match (self.initial(), entry.medial, entry.rime) {
+                (Some(_), Some(Bopomofo::U), Some(Bopomofo::ENG)) => "ʊŋ",
+                _ => entry.ipa,
+            });
+        }
+        ipa.push_str(match tone_number(self) {
+            1 => "˥",
+            2 => "˧˥",
+            3 => "˨˩˦",
+            4 => "˥˩",
+            _ => "",
+        });
+        ipa
+    }
+    /// Parses a romanized syllable.
+    ///
+    /// The tone can be written either as a trailing tone number or as a
+    /// diacritic on the vowel. A syllable without tone is parsed as the
+    /// first tone. Both `ü` and `v` are accepted for the `ㄩ` vowel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chewing::syl;
+    /// use chewing::zhuyin::{Bopomofo::*, PinyinVariant, Syllable};
+    ///
+    /// let syl = Syllable::from_pinyin("lǜ", PinyinVariant::HanyuPinyin)?;
+    /// assert_eq!(syl![L, IU, TONE4], syl);
+    ///
+    /// let syl = Syllable::from_pinyin("shr4", PinyinVariant::Mps2Pinyin)?;
+    /// assert_eq!(syl![SH, TONE4], syl);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_pinyin(s: &str, variant: PinyinVariant) -> Result<Syllable, ParseSyllableError> {
+        let (spelling, tone) = split_tone(s).ok_or(ParseSyllableError::invalid_pinyin())?;
+        if spelling.is_empty() {
+            return Err(ParseSyllableError::invalid_pinyin());
+        }
+        let (mut syllable, _) =
+            resolve_pinyin(&spelling, variant, true).ok_or(ParseSyllableError::invalid_pinyin())?;
+        if let Some(tone) = tone {
+            syllable.update(tone);
+        }
+        Ok(syllable)
+    }
+}
+
+/// Resolves a toneless romanized key sequence to its primary and alternative
+/// syllable.
+///
+/// When `strict` is false, trailing letters that do not form a valid final
+/// are dropped instead of failing the whole sequence, matching the lenient
+/// behavior of the pinyin layout.
+pub(crate) fn resolve_pinyin(
+    key_seq: &str,
+    variant: PinyinVariant,
+    strict: bool,
+) -> Option<(Syllable, Syllable)> {
+    if let Some(entry) = match variant {
+        PinyinVariant::HanyuPinyin => table::HANYU_PINYIN_MAPPING.iter(),
+        PinyinVariant::ThlPinyin => table::THL_PINYIN_MAPPING.iter(),
+        PinyinVariant::Mps2Pinyin => table::MPS2_PINYIN_MAPPING.iter(),
+    }
+    .find(|entry| entry.pinyin == key_seq)
+    {
+        return Some((entry.primary, entry.alt));
+    }
+
+    // Bare initials such as "sh" are shortcuts of the layout, not syllables
+    let is_initial = table::INITIAL_MAPPING
+        .iter()
+        .any(|entry| entry.pinyin == key_seq);
+    if let Some(entry) = table::COMMON_MAPPING
+        .iter()
+        .find(|entry| entry.pinyin == key_seq)
+        .filter(|_| !(strict && is_initial))
+    {
+        return Some((entry.primary, entry.alt));
+    }
+
+    let initial = table::INITIAL_MAPPING
+        .iter()
+        .find(|entry| key_seq.starts_with(entry.pinyin));
+
+    let final_seq = match initial {
+        Some(entry) => key_seq.trim_start_matches(entry.pinyin),
+        None => key_seq,
+    };
+
+    let fina = table::FINAL_MAPPING
+        .iter()
+        .find(|entry| final_seq == entry.pinyin);
+
+    if initial.is_none() && fina.is_none() {
+        return None;
+    }
+    // A bare initial such as "b" is only a partial syllable
+    if strict && fina.is_none() {
+        return None;
+    }
+
+    let mut initial = initial.map(|i| i.initial);
+    let mut medial = fina.and_then(|f| f.medial);
+    let mut rime = fina.and_then(|f| f.rime);
+
+    /* Hanyu empty rime
+     * ㄓ/ㄔ/ㄕ/ㄖ/ㄗ/ㄘ/ㄙ + -i, -i is empty rime, not ㄧ
+     * */
+    if variant == PinyinVariant::HanyuPinyin
+        && matches!(
+            (medial, rime),
+            (Some(Bopomofo::I), None) | (None, Some(Bopomofo::I))
+        )
+    {
+        match initial {
+            Some(Bopomofo::ZH) | Some(Bopomofo::CH) | Some(Bopomofo::SH) | Some(Bopomofo::R)
+            | Some(Bopomofo::Z) | Some(Bopomofo::C) | Some(Bopomofo::S) => {
+                medial.take();
+                rime.take();
+            }
+            _ => (),
+        }
+    }
+
+    /* Hanyu uan/un/u :
+     * ㄐ/ㄑ/ㄒ + -uan, -uan is ㄩㄢ, not ㄨㄢ
+     * ㄐ/ㄑ/ㄒ + -un,  -un is ㄩㄣ, not ㄨㄣ
+     * ㄐ/ㄑ/ㄒ + -u,   -u is ㄧ, not ㄨ
+     */
+    if variant == PinyinVariant::HanyuPinyin {
+        match initial {
+            Some(Bopomofo::J) | Some(Bopomofo::Q) | Some(Bopomofo::X) => {
+                match (medial, rime) {
+                    (Some(Bopomofo::U), Some(Bopomofo::AN))
+                    | (Some(Bopomofo::U), Some(Bopomofo::EN))
+                    | (Some(Bopomofo::U), None) => {
+                        medial.replace(Bopomofo::IU);
+                    }
+                    _ => (),
+                };
+            }
+            _ => (),
+        }
+    }
+
+    /* THL/MPS2 s/sh/c/ch/j :
+     * s-  + ー/ㄩ, s-  is ㄒ, not ㄙ (THL/Tongyong)
+     * sh- + ー/ㄩ, sh- is ㄒ, not ㄕ (MPS2)
+     * c-  + ー/ㄩ, c-  is ㄑ, not ㄘ (Tongyong)
+     * ch- + ㄧ/ㄩ, ch- is ㄑ, not ㄔ (THL)
+     * j-  + other than ー/ㄩ, j-  is ㄓ, not ㄐ (MPS2)
+     */
+    match variant {
+        PinyinVariant::ThlPinyin | PinyinVariant::Mps2Pinyin => match medial {
+            Some(Bopomofo::I) | Some(Bopomofo::IU) => {
+                match initial {
+                    Some(Bopomofo::S) | Some(Bopomofo::SH) => {
+                        initial.replace(Bopomofo::X);
+                    }
+                    Some(Bopomofo::C) | Some(Bopomofo::CH) => {
+                        initial.replace(Bopomofo::Q);
+                    }
+                    _ => (),
+                };
+            }
+            _ => {
+                if initial == Some(Bopomofo::J) {
+                    initial.replace(Bopomofo::ZH);
+                }
+            }
+        },
+        PinyinVariant::HanyuPinyin => {}
+    }
+
+    /* THL supplemental set
+     * ㄅ/ㄆ/ㄇ/ㄈ + -ㄨㄥ, -ㄨㄥ is another reading of -ㄥ
+     * ㄅ/ㄆ/ㄇ/ㄈ + -ㄨㄛ, -ㄨㄛ is another reading of -ㄛ
+     */
+    match variant {
+        PinyinVariant::ThlPinyin | PinyinVariant::Mps2Pinyin => match initial {
+            Some(Bopomofo::B) | Some(Bopomofo::P) | Some(Bopomofo::M) | Some(Bopomofo::F) => {
+                match (medial, rime) {
+                    (Some(Bopomofo::U), Some(Bopomofo::ENG))
+                    | (Some(Bopomofo::U), Some(Bopomofo::O)) => {
+                        medial.take();
+                    }
+                    _ => (),
+                };
+            }
+            _ => (),
+        },
+        _ => {}
+    }
+
+    let mut builder = Syllable::builder();
+    if let Some(initial) = initial {
+        builder = builder.insert(initial).ok()?;
+    }
+    if let Some(medial) = medial {
+        builder = builder.insert(medial).ok()?;
+    }
+    if let Some(rime) = rime {
+        builder = builder.insert(rime).ok()?;
+    }
+    let syllable = builder.build();
+    Some((syllable, syllable))
+}
+
+fn tone_number(syl: &Syllable) -> u8 {
+    match syl.tone() {
+        Some(Bopomofo::TONE2) => 2,
+        Some(Bopomofo::TONE3) => 3,
+        Some(Bopomofo::TONE4) => 4,
+        Some(Bopomofo::TONE5) => 5,
+        _ => 1,
+    }
+}
+
+fn spell(syl: &Syllable, variant: PinyinVariant) -> String {
+    let initial = syl.initial();
+    let mut spelling = String::new();
+    if let Some(initial) = initial {
+        spelling.push_str(spell_initial(initial, variant));
+    }
+    if syl.medial().is_none() && syl.rime().is_none() {
+        spelling.push_str(match (variant, initial) {
+            (
+                PinyinVariant::HanyuPinyin,
+                Some(Bopomofo::ZH | Bopomofo::CH | Bopomofo::SH | Bopomofo::R),
+            )
+            | (PinyinVariant::HanyuPinyin, Some(Bopomofo::Z | Bopomofo::C | Bopomofo::S)) => "i",
+            (
+                PinyinVariant::ThlPinyin,
+                Some(
+                    Bopomofo::ZH
+                    | Bopomofo::CH
+                    | Bopomofo::SH
+                    | Bopomofo::R
+                    | Bopomofo::Z
+                    | Bopomofo::C
+                    | Bopomofo::S,
+                ),
+            ) => "ih",
+            (PinyinVariant::Mps2Pinyin, Some(Bopomofo::ZH | Bopomofo::CH | Bopomofo::SH)) => "r",
+            (PinyinVariant::Mps2Pinyin, Some(Bopomofo::C | Bopomofo::S)) => "z",
+            _ => "",
+        });
+        return spelling;
+    }
+    let Some(entry) = find_final(syl.medial(), syl.rime()) else {
+        return spelling;
+    };
+    let index = if initial.is_some() { 1 } else { 0 };
+    let fina = match variant {
+        PinyinVariant::HanyuPinyin => match initial {
+            Some(Bopomofo::J | Bopomofo::Q | Bopomofo::X) => {
+                return spelling + &entry.hanyu[index].replace('ü', "u");
+            }
+            _ => entry.hanyu[index],
+        },
+        PinyinVariant::ThlPinyin => match (initial, entry.medial, entry.rime) {
+            (
+                Some(Bopomofo::B | Bopomofo::P | Bopomofo::M | Bopomofo::F),
+                None,
+                Some(Bopomofo::ENG),
+            ) => "ong",
+            _ => entry.thl[index],
+        },
+        PinyinVariant::Mps2Pinyin => entry.mps2[index],
+    };
+    spelling.push_str(fina);
+    spelling
+}
+
+fn spell_initial(initial: Bopomofo, variant: PinyinVariant) -> &'static str {
+    use Bopomofo::*;
+    match (initial, variant) {
+        (B, _) => "b",
+        (P, _) => "p",
+        (M, _) => "m",
+        (F, _) => "f",
+        (D, _) => "d",
+        (T, _) => "t",
+        (N, _) => "n",
+        (L, _) => "l",
+        (G, _) => "g",
+        (K, _) => "k",
+        (H, _) => "h",
+        (J, _) => "j",
+        (Q, PinyinVariant::HanyuPinyin) => "q",
+        (Q, _) => "ch",
+        (X, PinyinVariant::HanyuPinyin) => "x",
+        (X, PinyinVariant::ThlPinyin) => "s",
+        (X, PinyinVariant::Mps2Pinyin) => "sh",
+        (ZH, PinyinVariant::HanyuPinyin) => "zh",
+        (ZH, PinyinVariant::ThlPinyin) => "jh",
+        (ZH, PinyinVariant::Mps2Pinyin) => "j",
+        (CH, _) => "ch",
+        (SH, _) => "sh",
+        (R, _) => "r",
+        (Z, PinyinVariant::Mps2Pinyin) => "tz",
+        (Z, _) => "z",
+        (C, PinyinVariant::Mps2Pinyin) => "ts",
+        (C, _) => "c",
+        (S, _) => "s",
+        _ => "",
+    }
+}
+
+fn ipa_initial(initial: Bopomofo) -> &'static str {
+    use Bopomofo::*;
+    match initial {
+        B => "p",
+        P => "pʰ",
+        M => "m",
+        F => "f",
+        D => "t",
+        T => "tʰ",
+        N => "n",
+        L => "l",
+        G => "k",
+        K => "kʰ",
+        H => "x",
+        J => "tɕ",
+        Q => "tɕʰ",
+        X => "ɕ",
+        ZH => "ʈʂ",
+        CH => "ʈʂʰ",
+        SH => "ʂ",
+        R => "ʐ",
+        Z => "ts",
+        C => "tsʰ",
+        S => "s",
+        _ => "",
+    }
+}
+
+fn find_final(medial: Option<Bopomofo>, rime: Option<Bopomofo>) -> Option<&'static FinalSpelling> {
+    table::FINAL_SPELLING
+        .iter()
+        .find(|entry| entry.medial == medial && entry.rime == rime)
+}
+
+const TONE_MARKS: [(char, [&str; 5]); 7] = [
+    // base, macron, acute, caron, grave, ring above
+    ('a', ["ā", "á", "ǎ", "à", "å"]),
+    ('e', ["ē", "é", "ě", "è", "e\u{30a}"]),
+    ('i', ["ī", "í", "ǐ", "ì", "i\u{30a}"]),
+    ('o', ["ō", "ó", "ǒ", "ò", "o\u{30a}"]),
+    ('u', ["ū", "ú", "ǔ", "ù", "ů"]),
+    ('ü', ["ǖ", "ǘ", "ǚ", "ǜ", "ü\u{30a}"]),
+    ('ê', ["ê\u{304}", "ế", "ê\u{30c}", "ề", "ê\u{30a}"]),
+];
+
+const COMBINING_MARKS: [char; 5] = ['\u{304}', '\u{301}', '\u{30c}', '\u{300}', '\u{30a}'];
+
+fn place_tone_mark(spelling: &str, tone: u8, variant: PinyinVariant) -> String {
+    // Index into TONE_MARKS, THL leaves the first tone unmarked and uses a
+    // ring above for the neutral tone.
+    let mark = match (variant, tone) {
+        (PinyinVariant::ThlPinyin, 1) => return spelling.to_string(),
+        (PinyinVariant::ThlPinyin, 5) => 4,
+        (_, 5) => return spelling.to_string(),
+        (_, tone) => tone as usize - 1,
+    };
+    let position = spelling
+        .find(['a', 'e', 'ê'])
+        .or_else(|| spelling.find("ou"))
+        .or_else(|| spelling.rfind(['i', 'o', 'u', 'ü']));
+    let Some(position) = position else {
+        // MPS2 writes syllables such as jr and sz without a vowel
+        return format!("{spelling}{}", COMBINING_MARKS[mark]);
+    };
+    let vowel = spelling[position..].chars().next().unwrap();
+    let marked = TONE_MARKS
+        .iter()
+        .find(|(base, _)| *base == vowel)
+        .map(|(_, marks)| marks[mark])
+        .unwrap();
+    format!(
+        "{}{}{}",
+        &spelling[..position],
+        marked,
+        &spelling[position + vowel.len_utf8()..]
+    )
+}
+
+/// Splits a romanized syllable into a normalized toneless spelling and the
+/// tone written as digit or diacritic.
+fn split_tone(s: &str) -> Option<(String, Option<Bopomofo>)> {
+    let mut spelling = String::new();
+    let mut mark = None;
+    for c in s.trim().to_lowercase().chars() {
+        if let Some(index) = COMBINING_MARKS.iter().position(|&m| m == c) {
+            mark = Some(index);
+            continue;
+        }
+        let precomposed = TONE_MARKS.iter().find_map(|(base, marks)| {
+            marks
+                .iter()
+                .position(|m| m.chars().count() == 1 && m.starts_with(c))
+                .map(|index| (*base, index))
+        });
+        match precomposed {
+            Some((base, index)) => {
+                mark = Some(index);
+                spelling.push(base);
+            }
+            None => spelling.push(c),
+        }
+    }
+    let mut tone = mark.map(|index| match index {
+        0 => Bopomofo::TONE1,
+        1 => Bopomofo::TONE2,
+        2 => Bopomofo::TONE3,
+        3 => Bopomofo::TONE4,
+        _ => Bopomofo::TONE5,
+    });
+    if let Some(digit) = spelling.chars().last().filter(char::is_ascii_digit) {
+        spelling.pop();
+        tone = Some(match digit {
+            '1' => Bopomofo::TONE1,
+            '2' => Bopomofo::TONE2,
+            '3' => Bopomofo::TONE3,
+            '4' => Bopomofo::TONE4,
+            '5' | '0' => Bopomofo::TONE5,
+            _ => return None,
+        });
+    }
+    if !spelling
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c == 'ü' || c == 'ê')
+    {
+        return None;
+    }
+    let spelling = spelling.replace('ü', "v").replace('ê', "eh");
+    // The first tone is not stored in the syllable
+    Some((spelling, tone.filter(|&tone| tone != Bopomofo::TONE1)))
+}
+
+pub(crate) struct AmbiguousMapEntry {
+    pinyin: &'static str,
+    primary: Syllable,
+    alt: Syllable,
+}
+
+macro_rules! amb {
+    ($pinyin:expr, $primary:expr, $alt:expr ) => {
+        AmbiguousMapEntry {
+            pinyin: $pinyin,
+            primary: $primary,
+            alt: $alt,
+        }
+    };
+}
+
+pub(crate) struct InitialMapEntry {
+    pinyin: &'static str,
+    initial: Bopomofo,
+}
+
+macro_rules! ini {
+    ($pinyin:expr, $bopomofo:expr) => {
+        InitialMapEntry {
+            pinyin: $pinyin,
+            initial: $bopomofo,
+        }
+    };
+}
+
+pub(crate) struct FinalMapEntry {
+    pinyin: &'static str,
+    medial: Option<Bopomofo>,
+    rime: Option<Bopomofo>,
+}
+
+macro_rules! fin {
+    ($pinyin:expr, $medial:expr, $rime:expr) => {
+        FinalMapEntry {
+            pinyin: $pinyin,
+            medial: $medial,
+            rime: $rime,
+        }
+    };
+}
+
+/// Spelling of a final in each system, without and with a preceding
+/// initial.
+pub(crate) struct FinalSpelling {
+    medial: Option<Bopomofo>,
+    rime: Option<Bopomofo>,
+    hanyu: [&'static str; 2],
+    thl: [&'static str; 2],
+    mps2: [&'static str; 2],
+    ipa: &'static str,
+}
+
+macro_rules! spl {
+    ($medial:expr, $rime:expr, $hanyu:expr, $thl:expr, $mps2:expr, $ipa:expr) => {
+        FinalSpelling {
+            medial: $medial,
+            rime: $rime,
+            hanyu: $hanyu,
+            thl: $thl,
+            mps2: $mps2,
+            ipa: $ipa,
+        }
+    };
+}
+
+mod table {
+
+    use crate::{syl, zhuyin::Bopomofo::*};
+
+    use super::{AmbiguousMapEntry, FinalMapEntry, FinalSpelling, InitialMapEntry};
+
+    pub(super) const COMMON_MAPPING: [AmbiguousMapEntry; 18] = [
+        // Special cases for WG
+        amb!("tzu", syl![Z], syl![Z, U]),
+        amb!("ssu", syl![S], syl![S, U]),
+        amb!("szu", syl![S], syl![S, U]),
+        // Common multiple mapping
+        amb!("e", syl![E], syl![EH]),
+        amb!("ch", syl![CH], syl![Q]),
+        amb!("sh", syl![SH], syl![X]),
+        amb!("c", syl![C], syl![Q]),
+        amb!("s", syl![S], syl![X]),
+        amb!("nu", syl![N, U], syl![N, IU]),
+        amb!("lu", syl![L, U], syl![L, IU]),
+        amb!("luan", syl![L, U, AN], syl![L, IU, AN]),
+        amb!("niu", syl![N, I, OU], syl![N, IU]),
+        amb!("liu", syl![L, I, OU], syl![L, IU]),
+        amb!("jiu", syl![J, I, OU], syl![J, IU]),
+        amb!("chiu", syl![Q, I, OU], syl![Q, IU]),
+        amb!("shiu", syl![X, I, OU], syl![X, IU]),
+        amb!("ju", syl![J, IU], syl![ZH, U]),
+        amb!("juan", syl![J, IU, AN], syl![ZH, U, AN]),
+    ];
+
+    pub(super) const HANYU_PINYIN_MAPPING: [AmbiguousMapEntry; 4] = [
+        amb!("chi", syl![CH], syl![Q, I]),
+        amb!("shi", syl![SH], syl![X, I]),
+        amb!("ci", syl![C], syl![Q, I]),
+        amb!("si", syl![S], syl![X, I]),
+    ];
+
+    pub(super) const THL_PINYIN_MAPPING: [AmbiguousMapEntry; 4] = [
+        amb!("chi", syl![Q, I], syl![CH]),
+        amb!("shi", syl![X, I], syl![SH]),
+        amb!("ci", syl![Q, I], syl![C]),
+        amb!("si", syl![X, I], syl![S]),
+    ];
+
+    pub(super) const MPS2_PINYIN_MAPPING: [AmbiguousMapEntry; 15] = [
+        amb!("chi", syl![Q, I], syl![CH]),
+        amb!("shi", syl![X, I], syl![SH]),
+        amb!("ci", syl![Q, I], syl![C]),
+        amb!("si", syl![X, I], syl![S]),
+        amb!("niu", syl![N, IU], syl![N, I, OU]),
+        amb!("liu", syl![L, IU], syl![L, I, OU]),
+        amb!("jiu", syl![J, IU], syl![J, I, OU]),
+        amb!("chiu", syl![Q, IU], syl![Q, I, OU]),
+        amb!("shiu", syl![X, IU], syl![X, I, OU]),
+        amb!("ju", syl![ZH, U], syl![J, IU]),
+        amb!("juan", syl![ZH, U, AN], syl![J, IU, AN]),
+        amb!("juen", syl![ZH, U, EN], syl![J, IU, EN]),
+        amb!("tzu", syl![Z, U], syl![Z]),
+        // Syllables spelled as a bare initial
+        amb!("tz", syl![Z], syl![Z]),
+        amb!("r", syl![R], syl![R]),
+    ];
+
+    pub(super) const INITIAL_MAPPING: [InitialMapEntry; 25] = [
+        ini!("tz", Z),
+        ini!("b", B),
+        ini!("p", P),
+        ini!("m", M),
+        ini!("f", F),
+        ini!("d", D),
+        ini!("ts", C),
+        ini!("t", T),
+        ini!("n", N),
+        ini!("l", L),
+        ini!("g", G),
+        ini!("k", K),
+        ini!("hs", X),
+        ini!("h", H),
+        ini!("jh", ZH),
+        ini!("j", J),
+        ini!("q", Q),
+        ini!("x", X),
+        ini!("zh", ZH),
+        ini!("ch", CH),
+        ini!("sh", SH),
+        ini!("r", R),
+        ini!("z", Z),
+        ini!("c", C),
+        ini!("s", S),
+    ];
+
+    pub(super) const FINAL_MAPPING: [FinalMapEntry; 87] = [
+        fin!("uang", Some(U), Some(ANG)),
+        fin!("wang", Some(U), Some(ANG)),
+        fin!("weng", Some(U), Some(ENG)),
+        fin!("wong", Some(U), Some(ENG)),
+        fin!("ying", Some(I), Some(ENG)),
+        fin!("yung", Some(IU), Some(ENG)),
+        fin!("yong", Some(IU), Some(ENG)),
+        fin!("iung", Some(IU), Some(ENG)),
+        fin!("iong", Some(IU), Some(ENG)),
+        fin!("iang", Some(I), Some(ANG)),
+        fin!("yang", Some(I), Some(ANG)),
+        fin!("yuan", Some(IU), Some(AN)),
+        fin!("iuan", Some(IU), Some(AN)),
+        fin!("ing", Some(I), Some(ENG)),
+        fin!("iao", Some(I), Some(AU)),
+        fin!("iau", Some(I), Some(AU)),
+        fin!("yao", Some(I), Some(AU)),
+        fin!("yau", Some(I), Some(AU)),
+        fin!("yun", Some(IU), Some(EN)),
+        fin!("iun", Some(IU), Some(EN)),
+        fin!("vn", Some(IU), Some(EN)),
+        fin!("iou", Some(I), Some(OU)),
+        fin!("iu", Some(I), Some(OU)),
+        fin!("you", Some(I), Some(OU)),
+        fin!("io", Some(I), Some(O)),
+        fin!("yo", Some(I), Some(O)),
+        fin!("ian", Some(I), Some(AN)),
+        fin!("ien", Some(I), Some(AN)),
+        fin!("yan", Some(I), Some(AN)),
+        fin!("yen", Some(I), Some(AN)),
+        fin!("yin", Some(I), Some(EN)),
+        fin!("ang", None, Some(ANG)),
+        fin!("eng", None, Some(ENG)),
+        fin!("uei", Some(U), Some(EI)),
+        fin!("ui", Some(U), Some(EI)),
+        fin!("wei", Some(U), Some(EI)),
+        fin!("uen", Some(U), Some(EN)),
+        fin!("yueh", Some(IU), Some(EH)),
+        fin!("yue", Some(IU), Some(EH)),
+        fin!("iue", Some(IU), Some(EH)),
+        fin!("ueh", Some(IU), Some(EH)),
+        fin!("ue", Some(IU), Some(EH)),
+        fin!("ve", Some(IU), Some(EH)),
+        fin!("uai", Some(U), Some(AI)),
+        fin!("wai", Some(U), Some(AI)),
+        fin!("uan", Some(U), Some(AN)),
+        fin!("wan", Some(U), Some(AN)),
+        fin!("un", Some(U), Some(EN)),
+        fin!("wen", Some(U), Some(EN)),
+        fin!("wun", Some(U), Some(EN)),
+        fin!("ung", Some(U), Some(ENG)),
+        fin!("ong", Some(U), Some(ENG)),
+        fin!("van", Some(IU), Some(AN)),
+        fin!("er", None, Some(ER)),
+        fin!("ai", None, Some(AI)),
+        fin!("ei", None, Some(EI)),
+        fin!("ao", None, Some(AU)),
+        fin!("au", None, Some(AU)),
+        fin!("ou", None, Some(OU)),
+        fin!("an", None, Some(AN)),
+        fin!("en", None, Some(EN)),
+        fin!("yi", None, Some(I)),
+        fin!("ia", Some(I), Some(A)),
+        fin!("ya", Some(I), Some(A)),
+        fin!("ieh", Some(I), Some(EH)),
+        fin!("ie", Some(I), Some(EH)),
+        fin!("yeh", Some(I), Some(EH)),
+        fin!("ye", Some(I), Some(EH)),
+        fin!("in", Some(I), Some(EN)),
+        fin!("wu", Some(U), None),
+        fin!("ua", Some(U), Some(A)),
+        fin!("wa", Some(U), Some(A)),
+        fin!("uo", Some(U), Some(O)),
+        fin!("wo", Some(U), Some(O)),
+        fin!("yu", Some(IU), None),
+        fin!("ih", None, None),
+        fin!("a", None, Some(A)),
+        fin!("o", None, Some(O)),
+        fin!("eh", None, Some(EH)),
+        fin!("e", None, Some(E)),
+        fin!("v", Some(IU), None),
+        fin!("i", Some(I), None),
+        fin!("u", Some(U), None),
+        fin!("n", None, Some(EN)),
+        fin!("ng", None, Some(ENG)),
+        fin!("r", None, None),
+        fin!("z", None, None),
+    ];
+
+    #[rustfmt::skip]
+    pub(super) const FINAL_SPELLING: [FinalSpelling; 38] = [
+        spl!(None, Some(A), ["a", "a"], ["a", "a"], ["a", "a"], "a"),
+        spl!(None, Some(O), ["o", "o"], ["o", "o"], ["o", "o"], "o"),
+        spl!(None, Some(E), ["e", "e"], ["e", "e"], ["e", "e"], "ɤ"),
+        spl!(None, Some(EH), ["ê", "ê"], ["ê", "ê"], ["ê", "ê"], "ɛ"),
+        spl!(None, Some(AI), ["ai", "ai"], ["ai", "ai"], ["ai", "ai"], "ai"),
+        spl!(None, Some(EI), ["ei", "ei"], ["ei", "ei"], ["ei", "ei"], "ei"),
+        spl!(None, Some(AU), ["ao", "ao"], ["ao", "ao"], ["au", "au"], "au"),
+        spl!(None, Some(OU), ["ou", "ou"], ["ou", "ou"], ["ou", "ou"], "ou"),
+        spl!(None, Some(AN), ["an", "an"], ["an", "an"], ["an", "an"], "an"),
+        spl!(None, Some(EN), ["en", "en"], ["en", "en"], ["en", "en"], "ən"),
+        spl!(None, Some(ANG), ["ang", "ang"], ["ang", "ang"], ["ang", "ang"], "aŋ"),
+        spl!(None, Some(ENG), ["eng", "eng"], ["eng", "eng"], ["eng", "eng"], "əŋ"),
+        spl!(None, Some(ER), ["er", "er"], ["er", "er"], ["er", "er"], "aɚ"),
+        spl!(Some(I), None, ["yi", "i"], ["yi", "i"], ["yi", "i"], "i"),
+        spl!(Some(I), Some(A), ["ya", "ia"], ["ya", "ia"], ["ya", "ia"], "ja"),
+        spl!(Some(I), Some(O), ["yo", "io"], ["yo", "io"], ["yo", "io"], "jo"),
+        spl!(Some(I), Some(EH), ["ye", "ie"], ["ye", "ie"], ["ye", "ie"], "jɛ"),
+        spl!(Some(I), Some(AI), ["yai", "iai"], ["yai", "iai"], ["yai", "iai"], "jai"),
+        spl!(Some(I), Some(AU), ["yao", "iao"], ["yao", "iao"], ["yau", "iau"], "jau"),
+        spl!(Some(I), Some(OU), ["you", "iu"], ["you", "iou"], ["you", "iou"], "jou"),
+        spl!(Some(I), Some(AN), ["yan", "ian"], ["yan", "ian"], ["yan", "ian"], "jɛn"),
+        spl!(Some(I), Some(EN), ["yin", "in"], ["yin", "in"], ["yin", "in"], "in"),
+        spl!(Some(I), Some(ANG), ["yang", "iang"], ["yang", "iang"], ["yang", "iang"], "jaŋ"),
+        spl!(Some(I), Some(ENG), ["ying", "ing"], ["ying", "ing"], ["ying", "ing"], "iŋ"),
+        spl!(Some(U), None, ["wu", "u"], ["wu", "u"], ["wu", "u"], "u"),
+        spl!(Some(U), Some(A), ["wa", "ua"], ["wa", "ua"], ["wa", "ua"], "wa"),
+        spl!(Some(U), Some(O), ["wo", "uo"], ["wo", "uo"], ["wo", "uo"], "wo"),
+        spl!(Some(U), Some(AI), ["wai", "uai"], ["wai", "uai"], ["wai", "uai"], "wai"),
+        spl!(Some(U), Some(EI), ["wei", "ui"], ["wei", "uei"], ["wei", "uei"], "wei"),
+        spl!(Some(U), Some(AN), ["wan", "uan"], ["wan", "uan"], ["wan", "uan"], "wan"),
+        spl!(Some(U), Some(EN), ["wen", "un"], ["wun", "un"], ["wen", "uen"], "wən"),
+        spl!(Some(U), Some(ANG), ["wang", "uang"], ["wang", "uang"], ["wang", "uang"], "waŋ"),
+        spl!(Some(U), Some(ENG), ["weng", "ong"], ["wong", "ong"], ["weng", "ung"], "wəŋ"),
+        spl!(Some(IU), None, ["yu", "ü"], ["yu", "yu"], ["yu", "iu"], "y"),
+        spl!(Some(IU), Some(EH), ["yue", "üe"], ["yue", "yue"], ["yue", "iue"], "ɥɛ"),
+        spl!(Some(IU), Some(AN), ["yuan", "üan"], ["yuan", "yuan"], ["yuan", "iuan"], "ɥɛn"),
+        spl!(Some(IU), Some(EN), ["yun", "ün"], ["yun", "yun"], ["yun", "iun"], "yn"),
+        spl!(Some(IU), Some(ENG), ["yong", "iong"], ["yong", "yong"], ["yung", "iung"], "jʊŋ"),
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{syl, zhuyin::Bopomofo::*};
+
+    use super::{PinyinVariant, Syllable, ToneStyle};
+
+    #[test]
+    fn hanyu_pinyin_spelling() {
+        let hanyu = PinyinVariant::HanyuPinyin;
+        assert_eq!("zhi1", syl![ZH].to_pinyin(hanyu, ToneStyle::Number));
+        assert_eq!(
+            "ju4",
+            syl![J, IU, TONE4].to_pinyin(hanyu, ToneStyle::Number)
+        );
+        assert_eq!(
+            "lü4",
+            syl![L, IU, TONE4].to_pinyin(hanyu, ToneStyle::Number)
+        );
+        assert_eq!(
+            "lǜ",
+            syl![L, IU, TONE4].to_pinyin(hanyu, ToneStyle::Diacritic)
+        );
+        assert_eq!(
+            "liú",
+            syl![L, I, OU, TONE2].to_pinyin(hanyu, ToneStyle::Diacritic)
+        );
+        assert_eq!(
+            "duì",
+            syl![D, U, EI, TONE4].to_pinyin(hanyu, ToneStyle::Diacritic)
+        );
+        assert_eq!(
+            "de",
+            syl![D, E, TONE5].to_pinyin(hanyu, ToneStyle::Diacritic)
+        );
+        assert_eq!("wēng", syl![U, ENG].to_pinyin(hanyu, ToneStyle::Diacritic));
+        assert_eq!("xiong", syl![X, IU, ENG].to_pinyin(hanyu, ToneStyle::None));
+    }
+
+    #[test]
+    fn thl_and_mps2_spelling() {
+        let thl = PinyinVariant::ThlPinyin;
+        let mps2 = PinyinVariant::Mps2Pinyin;
+        assert_eq!("jhih", syl![ZH].to_pinyin(thl, ToneStyle::Diacritic));
+        assert_eq!(
+            "syué",
+            syl![X, IU, EH, TONE2].to_pinyin(thl, ToneStyle::Diacritic)
+        );
+        assert_eq!("fong1", syl![F, ENG].to_pinyin(thl, ToneStyle::Number));
+        assert_eq!(
+            "de\u{30a}",
+            syl![D, E, TONE5].to_pinyin(thl, ToneStyle::Diacritic)
+        );
+        assert_eq!("tsz3", syl![C, TONE3].to_pinyin(mps2, ToneStyle::Number));
+        assert_eq!(
+            "shiuan2",
+            syl![X, IU, AN, TONE2].to_pinyin(mps2, ToneStyle::Number)
+        );
+        assert_eq!("liou", syl![L, I, OU].to_pinyin(mps2, ToneStyle::None));
+    }
+
+    #[test]
+    fn ipa_transcription() {
+        assert_eq!("ʂɻ̩˥˩", syl![SH, TONE4].to_ipa());
+        assert_eq!("sɹ̩˥", syl![S].to_ipa());
+        assert_eq!("wəŋ˥", syl![U, ENG].to_ipa());
+        assert_eq!("tɤ", syl![D, E, TONE5].to_ipa());
+    }
+
+    #[test]
+    fn parse_pinyin() {
+        let hanyu = PinyinVariant::HanyuPinyin;
+        assert_eq!(Ok(syl![ZH, U, ENG]), Syllable::from_pinyin("zhōng", hanyu));
+        assert_eq!(Ok(syl![ZH, U, ENG]), Syllable::from_pinyin("zhong1", hanyu));
+        assert_eq!(Ok(syl![ZH, U, ENG]), Syllable::from_pinyin("zhong", hanyu));
+        assert_eq!(Ok(syl![N, IU, TONE3]), Syllable::from_pinyin("nv3", hanyu));
+        assert_eq!(Ok(syl![EH, TONE2]), Syllable::from_pinyin("ế", hanyu));
+        assert_eq!(
+            Ok(syl![X, IU, EH, TONE2]),
+            Syllable::from_pinyin("syue\u{301}", PinyinVariant::ThlPinyin)
+        );
+        assert!(Syllable::from_pinyin("zhongx", hanyu).is_err());
+        assert!(Syllable::from_pinyin("", hanyu).is_err());
+        assert!(Syllable::from_pinyin("ㄓㄨㄥ", hanyu).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_pinyin() {
+        for variant in [
+            PinyinVariant::HanyuPinyin,
+            PinyinVariant::ThlPinyin,
+            PinyinVariant::Mps2Pinyin,
+        ] {
+            for input in ["b", "zh", "sh4", "q", "7", "a9", "bx", "-"] {
+                assert!(
+                    Syllable::from_pinyin(input, variant).is_err(),
+                    "{input} should be rejected in {variant:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pinyin_round_trip() {
+        let variants = [
+            PinyinVariant::HanyuPinyin,
+            PinyinVariant::ThlPinyin,
+            PinyinVariant::Mps2Pinyin,
+        ];
+        let syllables = [
+            syl![ZH],
+            syl![CH, TONE2],
+            syl![SH, TONE3],
+            syl![R, TONE4],
+            syl![Z],
+            syl![C, TONE2],
+            syl![S, TONE5],
+            syl![J, IU, TONE2],
+            syl![Q, IU, AN, TONE3],
+            syl![X, IU, EN, TONE4],
+            syl![J, IU, ENG, TONE3],
+            syl![J, I, OU, TONE3],
+            syl![X, I, TONE2],
+            syl![Q, I, ENG, TONE2],
+            syl![N, IU, TONE3],
+            syl![L, IU, EH, TONE4],
+            syl![L, I, OU, TONE2],
+            syl![L, U, TONE4],
+            syl![D, U, EI, TONE4],
+            syl![D, U, EN, TONE4],
+            syl![D, U, ENG, TONE4],
+            syl![ZH, U, ENG],
+            syl![ZH, U, AN, TONE3],
+            syl![B, O, TONE2],
+            syl![F, ENG],
+            syl![M, ENG, TONE2],
+            syl![E, TONE4],
+            syl![EH, TONE4],
+            syl![ER, TONE2],
+            syl![I],
+            syl![I, OU, TONE3],
+            syl![U, TONE3],
+            syl![U, EN, TONE2],
+            syl![U, ENG],
+            syl![IU, TONE2],
+            syl![IU, EH, TONE4],
+            syl![IU, ENG, TONE3],
+            syl![H, AU, TONE3],
+            syl![T, I, AU, TONE4],
+        ];
+        for variant in variants {
+            for syl in syllables {
+                for style in [ToneStyle::Number, ToneStyle::Diacritic] {
+                    // The neutral tone is unmarked in these systems
+                    if style == ToneStyle::Diacritic
+                        && variant != PinyinVariant::ThlPinyin
+                        && syl.tone() == Some(TONE5)
+                    {
+                        continue;
+                    }
+                    let romanized = syl.to_pinyin(variant, style);
+                    assert_eq!(
+                        Ok(syl),
+                        Syllable::from_pinyin(&romanized, variant),
+                        "{variant:?} {romanized}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -2,20 +2,25 @@ use std::{
     borrow::Cow,
     error::Error,
     fmt::{Debug, Display, Write},
-    num::NonZeroU16,
-    ops::Shl,
+    num::NonZeroU32,
     str::FromStr,
 };
 
-use super::{Bopomofo, BopomofoKind, ParseBopomofoError};
+use super::{
+    Bopomofo, BopomofoKind, ParseBopomofoError,
+    bopomofo::{MANDARIN_INITIALS, MANDARIN_RIMES, MANDARIN_TONES},
+};
 
 /// The consonants and vowels that are taken together to make a single sound.
 ///
 /// <https://en.m.wikipedia.org/wiki/Syllable#Chinese_model>
+///
+/// A syllable can also hold the Taiwanese Hokkien and Hakka extensions of
+/// bopomofo. See [`Syllable::encode_u16`] for how these syllables are stored.
 #[derive(Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
 pub struct Syllable {
-    value: NonZeroU16,
+    value: NonZeroU32,
 }
 
 impl Debug for Syllable {
@@ -27,15 +32,45 @@ impl Debug for Syllable {
     }
 }
 
-#[allow(clippy::unusual_byte_groupings)]
+// The in-memory layout, from the most significant bits:
+//
+//     | Empty | Initial (6) | Medial (2) | Rime (5) | Coda (3) | Tone (3) |
+const TONE_SHIFT: u32 = 0;
+const CODA_SHIFT: u32 = 3;
+const RIME_SHIFT: u32 = 6;
+const MEDIAL_SHIFT: u32 = 11;
+const INITIAL_SHIFT: u32 = 13;
+const TONE_MASK: u32 = 0b111 << TONE_SHIFT;
+const CODA_MASK: u32 = 0b111 << CODA_SHIFT;
+const RIME_MASK: u32 = 0b11111 << RIME_SHIFT;
+const MEDIAL_MASK: u32 = 0b11 << MEDIAL_SHIFT;
+const INITIAL_MASK: u32 = 0b111111 << INITIAL_SHIFT;
+const EMPTY_PATTERN: u32 = 1 << 19;
+
+// The u16 code units.
+const NARROW_EMPTY: u16 = 0x8000;
+const LEAD_TAG: u16 = 0xC000;
+const LEAD_TAG_MASK: u16 = 0xFF00;
+const TRAIL_TAG: u16 = 0xA000;
+const TRAIL_TAG_MASK: u16 = 0xF800;
+
+const fn field(value: u32, mask: u32, shift: u32) -> u16 {
+    ((value & mask) >> shift) as u16
+}
+
 impl Syllable {
-    const EMPTY_PATTERN: u16 = 0b1000000_00_0000_000;
     const EMPTY: Syllable = Syllable {
-        value: match NonZeroU16::new(Self::EMPTY_PATTERN) {
+        value: match NonZeroU32::new(EMPTY_PATTERN) {
             Some(v) => v,
             None => unreachable!(),
         },
     };
+    const fn from_value(value: u32) -> Syllable {
+        match NonZeroU32::new(value & !EMPTY_PATTERN) {
+            Some(value) => Syllable { value },
+            None => Syllable::EMPTY,
+        }
+    }
     /// Creates a new empty syllable.
     pub const fn new() -> Syllable {
         Syllable::EMPTY
@@ -46,7 +81,7 @@ impl Syllable {
     }
     /// Returns the initial part of the syllable.
     pub const fn initial(&self) -> Option<Bopomofo> {
-        let index = (self.value.get() & 0b0111111_00_0000_000) >> 9;
+        let index = field(self.value.get(), INITIAL_MASK, INITIAL_SHIFT);
         if index == 0 {
             None
         } else {
@@ -55,7 +90,7 @@ impl Syllable {
     }
     /// Returns the medial part of the syllable.
     pub const fn medial(&self) -> Option<Bopomofo> {
-        let index = (self.value.get() & 0b0000000_11_0000_000) >> 7;
+        let index = field(self.value.get(), MEDIAL_MASK, MEDIAL_SHIFT);
         if index == 0 {
             None
         } else {
@@ -64,60 +99,62 @@ impl Syllable {
     }
     /// Returns the rime part of the syllable.
     pub const fn rime(&self) -> Option<Bopomofo> {
-        let index = (self.value.get() & 0b0000000_00_1111_000) >> 3;
+        let index = field(self.value.get(), RIME_MASK, RIME_SHIFT);
         if index == 0 {
             None
         } else {
             Bopomofo::from_rime(index - 1)
         }
     }
+    /// Returns the stop final of the syllable.
+    pub const fn coda(&self) -> Option<Bopomofo> {
+        let index = field(self.value.get(), CODA_MASK, CODA_SHIFT);
+        if index == 0 {
+            None
+        } else {
+            Bopomofo::from_coda(index - 1)
+        }
+    }
     /// Returns the tone of the syllable.
     pub const fn tone(&self) -> Option<Bopomofo> {
-        let index = self.value.get() & 0b0000000_00_0000_111;
+        let index = field(self.value.get(), TONE_MASK, TONE_SHIFT);
         if index == 0 {
             None
         } else {
             Bopomofo::from_tone(index - 1)
         }
     }
+    fn remove(&mut self, mask: u32) {
+        *self = Syllable::from_value(self.value.get() & !mask);
+    }
     /// Removes the initial from the syllable.
     pub fn remove_initial(&mut self) -> Option<Bopomofo> {
         let ret = self.initial();
-        let value = self.value.get() & 0b0000000_11_1111_111;
-        self.value = match value {
-            0 => Syllable::EMPTY.value,
-            _ => NonZeroU16::new(value).unwrap(),
-        };
+        self.remove(INITIAL_MASK);
         ret
     }
     /// Removes the medial from the syllable.
     pub fn remove_medial(&mut self) -> Option<Bopomofo> {
         let ret = self.medial();
-        let value = self.value.get() & 0b1111111_00_1111_111;
-        self.value = match value {
-            0 => Syllable::EMPTY.value,
-            _ => NonZeroU16::new(value).unwrap(),
-        };
+        self.remove(MEDIAL_MASK);
         ret
     }
     /// Removes the rime from the syllable.
     pub fn remove_rime(&mut self) -> Option<Bopomofo> {
         let ret = self.rime();
-        let value = self.value.get() & 0b1111111_11_0000_111;
-        self.value = match value {
-            0 => Syllable::EMPTY.value,
-            _ => NonZeroU16::new(value).unwrap(),
-        };
+        self.remove(RIME_MASK);
+        ret
+    }
+    /// Removes the stop final from the syllable.
+    pub fn remove_coda(&mut self) -> Option<Bopomofo> {
+        let ret = self.coda();
+        self.remove(CODA_MASK);
         ret
     }
     /// Removes the tone from the syllable.
     pub fn remove_tone(&mut self) -> Option<Bopomofo> {
         let ret = self.tone();
-        let value = self.value.get() & 0b1111111_11_1111_000;
-        self.value = match value {
-            0 => Syllable::EMPTY.value,
-            _ => NonZeroU16::new(value).unwrap(),
-        };
+        self.remove(TONE_MASK);
         ret
     }
     /// Returns whether the syllable is empty.
@@ -136,25 +173,84 @@ impl Syllable {
     pub fn has_rime(&self) -> bool {
         self.rime().is_some()
     }
+    /// Returns whether the syllable has a stop final.
+    pub fn has_coda(&self) -> bool {
+        self.coda().is_some()
+    }
     /// Returns whether the syllable has a tone.
     pub fn has_tone(&self) -> bool {
         self.tone().is_some()
     }
+    /// Returns whether the syllable uses any of the Taiwanese Hokkien and
+    /// Hakka extensions.
+    ///
+    /// Extended syllables need two u16 code units. See
+    /// [`encode_u16`][Syllable::encode_u16].
+    pub const fn is_extended(&self) -> bool {
+        let value = self.value.get();
+        field(value, INITIAL_MASK, INITIAL_SHIFT) > MANDARIN_INITIALS
+            || field(value, RIME_MASK, RIME_SHIFT) > MANDARIN_RIMES
+            || field(value, CODA_MASK, CODA_SHIFT) != 0
+            || field(value, TONE_MASK, TONE_SHIFT) > MANDARIN_TONES
+    }
+    /// Returns whether the syllable ends with a stop final and is read in
+    /// one of the entering tones.
+    pub fn is_checked(&self) -> bool {
+        self.has_coda()
+    }
+    /// Returns the Taiwanese Hokkien tone number of the syllable, from 1 to 8.
+    ///
+    /// The tone is read from the tone mark and the stop final according to
+    /// the Taiwanese Phonetic Symbols (臺灣方音符號). Checked syllables are in
+    /// the 4th tone, or the 8th tone when marked with `˙`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chewing::syl;
+    /// use chewing::zhuyin::Bopomofo::{A, FINAL_P, TONE5, TONE_YANG_DEPARTING, T};
+    ///
+    /// assert_eq!(Some(7), syl![T, A, TONE_YANG_DEPARTING].hokkien_tone());
+    /// assert_eq!(Some(4), syl![T, A, FINAL_P].hokkien_tone());
+    /// assert_eq!(Some(8), syl![T, A, FINAL_P, TONE5].hokkien_tone());
+    /// ```
+    pub fn hokkien_tone(&self) -> Option<u8> {
+        if self.is_empty() {
+            return None;
+        }
+        if self.is_checked() {
+            return match self.tone() {
+                None => Some(4),
+                Some(Bopomofo::TONE5) => Some(8),
+                Some(_) => None,
+            };
+        }
+        match self.tone() {
+            None => Some(1),
+            Some(Bopomofo::TONE4) => Some(2),
+            Some(Bopomofo::TONE_YIN_DEPARTING) => Some(3),
+            Some(Bopomofo::TONE2) => Some(5),
+            Some(Bopomofo::TONE3) => Some(6),
+            Some(Bopomofo::TONE_YANG_DEPARTING) => Some(7),
+            Some(_) => None,
+        }
+    }
     /// Returns whether the syllable partially matches another syllable.
+    ///
+    /// Every part of `other` must match `self`, up to and including the last
+    /// part present in `other`.
     pub fn starts_with(&self, other: Syllable) -> bool {
-        let trailing_zeros = other.to_u16().trailing_zeros();
-        let mask = if trailing_zeros >= 9 {
-            9
-        } else if trailing_zeros >= 7 {
-            7
-        } else if trailing_zeros >= 3 {
-            3
-        } else {
-            0
-        };
-        let self_prefix = self.to_u16() >> mask;
-        let other_prefix = other.to_u16() >> mask;
-        self_prefix == other_prefix
+        let other_value = other.value.get();
+        let shift = [
+            (TONE_MASK, TONE_SHIFT),
+            (CODA_MASK, CODA_SHIFT),
+            (RIME_MASK, RIME_SHIFT),
+            (MEDIAL_MASK, MEDIAL_SHIFT),
+        ]
+        .into_iter()
+        .find(|&(mask, _)| other_value & mask != 0)
+        .map_or(INITIAL_SHIFT, |(_, shift)| shift);
+        self.value.get() >> shift == other_value >> shift
     }
     /// Returns the `Syllable` encoded in a u16 integer.
     ///
@@ -167,39 +263,161 @@ impl Syllable {
     /// |   Initial   | M | Rime  |Tone |
     /// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
     /// ```
+    ///
+    /// Extended syllables do not fit in one u16 integer. For them this
+    /// returns the leading code unit only; use
+    /// [`encode_u16`][Syllable::encode_u16] instead.
     pub fn to_u16(self) -> u16 {
-        self.value.get()
+        let mut buf = [0; 2];
+        self.encode_u16(&mut buf)[0]
+    }
+    /// Returns the number of u16 code units needed to encode the syllable.
+    pub const fn len_u16(&self) -> usize {
+        if self.is_extended() { 2 } else { 1 }
     }
-    /// Returns the `Syllable` encoded in a u16 integer in little-endian bytes.
+    /// Encodes the syllable as u16 code units into the buffer, and then
+    /// returns the subslice of the buffer that contains the encoded syllable.
     ///
-    /// The data layout used:
+    /// Mandarin syllables are encoded as one u16 integer, as returned by
+    /// [`to_u16`][Syllable::to_u16]. Syllables using the Taiwanese Hokkien
+    /// and Hakka extensions are encoded as a leading and a trailing code
+    /// unit. Neither of them collides with a Mandarin syllable.
     ///
     /// ```text
     ///  0                   1
     ///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5
     /// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
-    /// |   Initial   | M | Rime  |Tone |
+    /// |1 1 0 0 0 0 0 0|  Initial  | M |  Leading unit
+    /// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    /// |1 0 1 0 0|  Rime   |Coda |Tone |  Trailing unit
     /// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
     /// ```
-    fn to_le_bytes(self) -> [u8; 2] {
-        self.to_u16().to_le_bytes()
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is not large enough. A buffer of length 2 is
+    /// large enough to encode any syllable.
+    pub fn encode_u16(self, dst: &mut [u16]) -> &mut [u16] {
+        let value = self.value.get();
+        let initial = field(value, INITIAL_MASK, INITIAL_SHIFT);
+        let medial = field(value, MEDIAL_MASK, MEDIAL_SHIFT);
+        let rime = field(value, RIME_MASK, RIME_SHIFT);
+        let coda = field(value, CODA_MASK, CODA_SHIFT);
+        let tone = field(value, TONE_MASK, TONE_SHIFT);
+        if self.is_empty() {
+            dst[0] = NARROW_EMPTY;
+            &mut dst[..1]
+        } else if self.is_extended() {
+            dst[..2].copy_from_slice(&[
+                LEAD_TAG | initial << 2 | medial,
+                TRAIL_TAG | rime << 6 | coda << 3 | tone,
+            ]);
+            &mut dst[..2]
+        } else {
+            dst[0] = initial << 9 | medial << 7 | rime << 3 | tone;
+            &mut dst[..1]
+        }
+    }
+    /// Returns an iterator that decodes syllables from u16 code units.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chewing::syl;
+    /// use chewing::zhuyin::{Bopomofo::{A, FINAL_P, TONE4, T, ZH, U, ENG}, Syllable};
+    ///
+    /// let syllables = [syl![ZH, U, ENG], syl![T, A, FINAL_P, TONE4]];
+    /// let mut units = vec![];
+    /// for syl in syllables {
+    ///     units.extend_from_slice(syl.encode_u16(&mut [0; 2]));
+    /// }
+    /// assert_eq!(3, units.len());
+    ///
+    /// let decoded: Result<Vec<_>, _> = Syllable::decode_u16(units).collect();
+    /// assert_eq!(Ok(syllables.to_vec()), decoded);
+    /// ```
+    pub fn decode_u16<I>(iter: I) -> DecodeU16<I::IntoIter>
+    where
+        I: IntoIterator<Item = u16>,
+    {
+        DecodeU16 {
+            iter: iter.into_iter(),
+        }
+    }
+    /// Returns whether the u16 code unit is the leading unit of an extended
+    /// syllable.
+    pub(crate) const fn is_lead_u16(unit: u16) -> bool {
+        unit & LEAD_TAG_MASK == LEAD_TAG
+    }
+    fn from_narrow_u16(unit: u16) -> Result<Syllable, DecodeSyllableError> {
+        if unit == NARROW_EMPTY {
+            return Ok(Syllable::EMPTY);
+        }
+        let initial = unit >> 9;
+        let medial = (unit >> 7) & 0b11;
+        let rime = (unit >> 3) & 0b1111;
+        let tone = unit & 0b111;
+        if unit == 0
+            || initial > MANDARIN_INITIALS
+            || rime > MANDARIN_RIMES
+            || tone > MANDARIN_TONES
+        {
+            return Err(DecodeSyllableError);
+        }
+        Ok(Syllable::from_value(
+            (initial as u32) << INITIAL_SHIFT
+                | (medial as u32) << MEDIAL_SHIFT
+                | (rime as u32) << RIME_SHIFT
+                | (tone as u32) << TONE_SHIFT,
+        ))
+    }
+    fn from_wide_u16(lead: u16, trail: u16) -> Result<Syllable, DecodeSyllableError> {
+        if !Syllable::is_lead_u16(lead) || trail & TRAIL_TAG_MASK != TRAIL_TAG {
+            return Err(DecodeSyllableError);
+        }
+        let initial = (lead >> 2) & 0b111111;
+        let medial = lead & 0b11;
+        let rime = (trail >> 6) & 0b11111;
+        let coda = (trail >> 3) & 0b111;
+        let tone = trail & 0b111;
+        if Bopomofo::from_initial(initial.wrapping_sub(1)).is_none() && initial != 0
+            || Bopomofo::from_coda(coda.wrapping_sub(1)).is_none() && coda != 0
+        {
+            return Err(DecodeSyllableError);
+        }
+        let syl = Syllable::from_value(
+            (initial as u32) << INITIAL_SHIFT
+                | (medial as u32) << MEDIAL_SHIFT
+                | (rime as u32) << RIME_SHIFT
+                | (coda as u32) << CODA_SHIFT
+                | (tone as u32) << TONE_SHIFT,
+        );
+        // Mandarin syllables are always encoded in one code unit.
+        if !syl.is_extended() {
+            return Err(DecodeSyllableError);
+        }
+        Ok(syl)
     }
     /// Combines the current syllable with a new sound.
     pub fn update(&mut self, bopomofo: Bopomofo) {
-        let orig = self.value.get();
-        let value = match bopomofo.kind() {
-            BopomofoKind::Initial => (orig & 0b0000000_11_1111_111) | bopomofo.index().shl(9),
-            BopomofoKind::Medial => (orig & 0b0111111_00_1111_111) | bopomofo.index().shl(7),
-            BopomofoKind::Rime => (orig & 0b0111111_11_0000_111) | bopomofo.index().shl(3),
-            BopomofoKind::Tone => (orig & 0b0111111_11_1111_000) | bopomofo.index(),
+        let (mask, shift) = match bopomofo.kind() {
+            BopomofoKind::Initial => (INITIAL_MASK, INITIAL_SHIFT),
+            BopomofoKind::Medial => (MEDIAL_MASK, MEDIAL_SHIFT),
+            BopomofoKind::Rime => (RIME_MASK, RIME_SHIFT),
+            BopomofoKind::Coda => (CODA_MASK, CODA_SHIFT),
+            BopomofoKind::Tone => (TONE_MASK, TONE_SHIFT),
         };
-        self.value = NonZeroU16::new(value).unwrap();
+        let value = self.value.get() & !mask & !EMPTY_PATTERN;
+        *self = Syllable::from_value(value | (bopomofo.index() as u32) << shift);
     }
     /// Removes components of the syllable.
     pub fn pop(&mut self) -> Option<Bopomofo> {
         if self.has_tone() {
             return self.remove_tone();
         }
+        if self.has_coda() {
+            return self.remove_coda();
+        }
         if self.has_rime() {
             return self.remove_rime();
         }
@@ -238,11 +456,38 @@ impl From<&Syllable> for u16 {
 impl TryFrom<u16> for Syllable {
     type Error = DecodeSyllableError;
 
+    /// Decodes a syllable encoded in one u16 integer.
+    ///
+    /// Extended syllables are encoded in two code units and can only be
+    /// decoded with [`Syllable::decode_u16`].
     fn try_from(value: u16) -> Result<Self, Self::Error> {
-        // TODO check invalid value
-        Ok(Syllable {
-            value: NonZeroU16::try_from(value).map_err(|_| DecodeSyllableError)?,
-        })
+        Syllable::from_narrow_u16(value)
+    }
+}
+
+/// An iterator that decodes syllables from u16 code units.
+///
+/// This struct is created by [`Syllable::decode_u16`].
+#[derive(Debug, Clone)]
+pub struct DecodeU16<I> {
+    iter: I,
+}
+
+impl<I> Iterator for DecodeU16<I>
+where
+    I: Iterator<Item = u16>,
+{
+    type Item = Result<Syllable, DecodeSyllableError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let unit = self.iter.next()?;
+        if Syllable::is_lead_u16(unit) {
+            return Some(match self.iter.next() {
+                Some(trail) => Syllable::from_wide_u16(unit, trail),
+                None => Err(DecodeSyllableError),
+            });
+        }
+        Some(Syllable::from_narrow_u16(unit))
     }
 }
 
@@ -268,12 +513,22 @@ impl AsRef<Syllable> for Syllable {
 /// A slice that can be converted to a slice of syllables.
 pub trait SyllableSlice: Debug {
     fn to_slice(&self) -> Cow<'_, [Syllable]>;
-    fn to_bytes(&self) -> Vec<u8> {
-        let mut syllables_bytes = vec![];
+    /// Returns the syllables encoded as u16 code units.
+    ///
+    /// See [`Syllable::encode_u16`].
+    fn to_u16_units(&self) -> Vec<u16> {
+        let mut units = vec![];
         self.to_slice()
             .iter()
-            .for_each(|syl| syllables_bytes.extend_from_slice(&syl.as_ref().to_le_bytes()));
-        syllables_bytes
+            .for_each(|syl| units.extend_from_slice(syl.encode_u16(&mut [0; 2])));
+        units
+    }
+    /// Returns the syllables encoded as u16 code units in little-endian bytes.
+    fn to_bytes(&self) -> Vec<u8> {
+        self.to_u16_units()
+            .into_iter()
+            .flat_map(u16::to_le_bytes)
+            .collect()
     }
 }
 
@@ -297,10 +552,17 @@ impl<const N: usize> SyllableSlice for [Syllable; N] {
 
 impl Display for Syllable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for &bopomofo in [&self.initial(), &self.medial(), &self.rime(), &self.tone()] {
-            if let Some(bopomofo) = bopomofo {
-                f.write_char(bopomofo.into())?;
-            }
+        for bopomofo in [
+            self.initial(),
+            self.medial(),
+            self.rime(),
+            self.coda(),
+            self.tone(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            f.write_char(bopomofo.into())?;
         }
         Ok(())
     }
@@ -309,7 +571,7 @@ impl Display for Syllable {
 /// A syllable builder can be used to construct syllables at compile time.
 #[derive(Debug)]
 pub struct SyllableBuilder {
-    value: u16,
+    value: u32,
     step: u8,
 }
 
@@ -323,72 +585,61 @@ impl SyllableBuilder {
     /// Creates a new syllable builder.
     pub const fn new() -> SyllableBuilder {
         SyllableBuilder {
-            value: Syllable::EMPTY_PATTERN,
+            value: EMPTY_PATTERN,
             step: 0,
         }
     }
     /// Inserts syllable components and checks they follow correct order.
-    #[allow(clippy::unusual_byte_groupings)]
     pub const fn insert(
         mut self,
         bopomofo: Bopomofo,
     ) -> Result<SyllableBuilder, BuildSyllableError> {
-        match bopomofo.kind() {
-            BopomofoKind::Initial => {
-                if self.value & 0b0111111_00_0000_000 != 0 {
-                    return Err(BuildSyllableError::multiple_initials());
-                }
-                if self.step > 0 {
-                    return Err(BuildSyllableError::incorrect_order());
-                }
-                self.step = 1;
-                self.value &= 0b0000000_11_1111_111;
-                self.value |= (bopomofo as u16 + 1) << 9;
-            }
-            BopomofoKind::Medial => {
-                if self.value & 0b0000000_11_0000_000 != 0 {
-                    return Err(BuildSyllableError::multiple_medials());
-                }
-                if self.step > 1 {
-                    return Err(BuildSyllableError::incorrect_order());
-                }
-                self.step = 2;
-                self.value &= 0b0111111_00_1111_111;
-                self.value |= (bopomofo as u16 - 20) << 7;
-            }
-            BopomofoKind::Rime => {
-                if self.value & 0b0000000_00_1111_000 != 0 {
-                    return Err(BuildSyllableError::multiple_rimes());
-                }
-                if self.step > 2 {
-                    return Err(BuildSyllableError::incorrect_order());
-                }
-                self.step = 3;
-                self.value &= 0b0111111_11_0000_111;
-                self.value |= (bopomofo as u16 - 23) << 3;
-            }
-            BopomofoKind::Tone => {
-                if self.value & 0b0000000_00_0000_111 != 0 {
-                    return Err(BuildSyllableError::multiple_tones());
-                }
-                if self.step > 3 {
-                    return Err(BuildSyllableError::incorrect_order());
-                }
-                self.step = 4;
-                self.value &= 0b0111111_11_1111_000;
-                self.value |= bopomofo as u16 - 36;
-            }
+        let (mask, shift, step, duplicate) = match bopomofo.kind() {
+            BopomofoKind::Initial => (
+                INITIAL_MASK,
+                INITIAL_SHIFT,
+                1,
+                BuildSyllableError::multiple_initials(),
+            ),
+            BopomofoKind::Medial => (
+                MEDIAL_MASK,
+                MEDIAL_SHIFT,
+                2,
+                BuildSyllableError::multiple_medials(),
+            ),
+            BopomofoKind::Rime => (
+                RIME_MASK,
+                RIME_SHIFT,
+                3,
+                BuildSyllableError::multiple_rimes(),
+            ),
+            BopomofoKind::Coda => (
+                CODA_MASK,
+                CODA_SHIFT,
+                4,
+                BuildSyllableError::multiple_codas(),
+            ),
+            BopomofoKind::Tone => (
+                TONE_MASK,
+                TONE_SHIFT,
+                5,
+                BuildSyllableError::multiple_tones(),
+            ),
         };
+        if self.value & mask != 0 {
+            return Err(duplicate);
+        }
+        if self.step >= step {
+            return Err(BuildSyllableError::incorrect_order());
+        }
+        self.step = step;
+        self.value &= !EMPTY_PATTERN;
+        self.value |= (bopomofo.index() as u32) << shift;
         Ok(self)
     }
     /// Builds the syllable.
     pub const fn build(self) -> Syllable {
-        Syllable {
-            value: match NonZeroU16::new(self.value) {
-                Some(v) => v,
-                None => unreachable!(),
-            },
-        }
+        Syllable::from_value(self.value)
     }
 }
 
@@ -411,9 +662,11 @@ pub enum SyllableErrorKind {
     MultipleInitials,
     MultipleMedials,
     MultipleRimes,
+    MultipleCodas,
     MultipleTones,
     IncorrectOrder,
     InvalidBopomofo,
+    InvalidPinyin,
 }
 
 /// Errors when building a new syllable.
@@ -438,6 +691,11 @@ impl BuildSyllableError {
             kind: SyllableErrorKind::MultipleRimes,
         }
     }
+    const fn multiple_codas() -> BuildSyllableError {
+        Self {
+            kind: SyllableErrorKind::MultipleCodas,
+        }
+    }
     const fn multiple_tones() -> BuildSyllableError {
         Self {
             kind: SyllableErrorKind::MultipleTones,
@@ -468,6 +726,12 @@ pub struct ParseSyllableError {
 }
 
 impl ParseSyllableError {
+    pub(super) const fn invalid_pinyin() -> ParseSyllableError {
+        Self {
+            kind: SyllableErrorKind::InvalidPinyin,
+        }
+    }
+
     pub fn kind(&self) -> &SyllableErrorKind {
         &self.kind
     }
@@ -655,4 +919,62 @@ mod test {
         assert_eq!(None, syl.pop());
         assert_eq!(syl![], syl);
     }
+
+    #[test]
+    fn extended_syllable_as_u16_units() {
+        let syl = syl![
+            Bopomofo::GU,
+            Bopomofo::I,
+            Bopomofo::A,
+            Bopomofo::FINAL_T,
+            Bopomofo::TONE5
+        ];
+        assert!(syl.is_extended());
+        assert_eq!("ㆣㄧㄚㆵ˙", syl.to_string());
+
+        let mut buf = [0; 2];
+        let units = syl.encode_u16(&mut buf).to_vec();
+        assert_eq!(2, units.len());
+        assert_eq!(
+            Ok(syl),
+            Syllable::decode_u16(units.iter().copied()).next().unwrap()
+        );
+        assert!(Syllable::try_from(units[0]).is_err());
+        assert!(Syllable::decode_u16([units[0]]).next().unwrap().is_err());
+        assert_eq!(units[0], syl.to_u16());
+        assert_eq!(2, syl.len_u16());
+    }
+
+    #[test]
+    fn mandarin_syllable_as_one_u16_unit() {
+        let syl = syl![Bopomofo::X, Bopomofo::I, Bopomofo::EN, Bopomofo::TONE4];
+        let mut buf = [0; 2];
+        assert_eq!(&[syl.to_u16()], syl.encode_u16(&mut buf));
+        assert_eq!(1, syl.len_u16());
+        assert!(Syllable::try_from(0).is_err());
+        assert!(Syllable::try_from(0x8001).is_err());
+    }
+
+    #[test]
+    fn extended_syllable_starts_with() {
+        let syl = syl![Bopomofo::T, Bopomofo::A, Bopomofo::FINAL_P, Bopomofo::TONE5];
+        assert!(syl.starts_with(syl![Bopomofo::T, Bopomofo::A]));
+        assert!(syl.starts_with(syl![Bopomofo::T, Bopomofo::A, Bopomofo::FINAL_P]));
+        assert!(!syl.starts_with(syl![Bopomofo::T, Bopomofo::A, Bopomofo::FINAL_T]));
+    }
+
+    #[test]
+    fn coda_order() {
+        assert!(
+            Syllable::builder()
+                .insert(Bopomofo::FINAL_P)
+                .unwrap()
+                .insert(Bopomofo::A)
+                .is_err()
+        );
+        let mut syl = syl![Bopomofo::T, Bopomofo::A, Bopomofo::FINAL_P, Bopomofo::TONE5];
+        assert_eq!(Some(Bopomofo::TONE5), syl.pop());
+        assert_eq!(Some(Bopomofo::FINAL_P), syl.pop());
+        assert!(!syl.is_extended());
+    }
 }
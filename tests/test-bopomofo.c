@@ -1477,6 +1477,32 @@ void test_get_phoneSeq()
     chewing_delete(ctx);
 }
 
+void test_get_phoneSeqUnits()
+{
+    ChewingContext *ctx;
+    int len;
+    unsigned short *phone;
+    unsigned short *units;
+
+    ctx = chewing_new();
+    start_testcase(ctx, fd);
+    chewing_set_maxChiSymbolLen(ctx, 16);
+
+    type_keystroke_by_string(ctx, "hk4g4`31hk4g4");
+
+    /* Mandarin syllables take one unit each */
+    len = chewing_get_phoneSeqUnitsLen(ctx);
+    ok(len == 4, "phoneSeqUnitsLen `%d' shall be `%d'", len, 4);
+
+    phone = chewing_get_phoneSeq(ctx);
+    units = chewing_get_phoneSeqUnits(ctx);
+    ok(memcmp(units, phone, sizeof(units[0]) * len) == 0, "phoneSeqUnits shall be phoneSeq");
+    chewing_free(units);
+    chewing_free(phone);
+
+    chewing_delete(ctx);
+}
+
 void test_bopomofo_buffer()
 {
     ChewingContext *ctx;
@@ -2471,6 +2497,7 @@ int main(int argc, char *argv[])
     test_Acknowledge();
 
     test_get_phoneSeq();
+    test_get_phoneSeqUnits();
     test_bopomofo_buffer();
 
     test_longest_phrase();
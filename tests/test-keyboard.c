@@ -36,7 +36,12 @@ static const char *const KEYBOARD_STRING[] = {
     "KB_COLEMAK_DH_ANSI",
     "KB_COLEMAK_DH_ORTH",
     "KB_WORKMAN",
-    "KB_COLEMAK"
+    "KB_COLEMAK",
+    "KB_MS_DOUBLE_PINYIN",
+    "KB_ZIRANMA_DOUBLE_PINYIN",
+    "KB_XIAOHE_DOUBLE_PINYIN",
+    "KB_KEYPAD",
+    "KB_FANGYIN"
 };
 
 static const int KEYBOARD_DEFAULT_TYPE = 0;
@@ -0,0 +1,58 @@
+use std::io::{BufRead, stdin};
+
+use anyhow::{Result, anyhow};
+use chewing::{
+    conversion::{
+        ChewingEngine, ConversionEngine, FuzzyChewingEngine, SimpleEngine, SyllableFormat,
+        convert_text,
+    },
+    dictionary::{Layered, SystemDictionaryLoader, UserDictionaryLoader},
+    editor::zhuyin_layout::KeyboardLayoutCompat,
+    zhuyin::PinyinVariant,
+};
+
+use crate::flags::{self, ConversionArgs, Engine, InputFormat};
+
+pub(crate) fn run(args: flags::Convert) -> Result<()> {
+    let dict = Layered::new(
+        SystemDictionaryLoader::new().load()?,
+        UserDictionaryLoader::new().load()?,
+    );
+    let format = syllable_format(args.conversion.format, &args.conversion.layout)?;
+    let engine = conversion_engine(&args.conversion);
+    // Output one line for every input line so results can be pasted side by
+    // side with the input.
+    for (line_no, line) in stdin().lock().lines().enumerate() {
+        match convert_text(engine.as_ref(), &dict, format, &line?, args.top) {
+            Ok(sentences) => println!("{}", sentences.join("\t")),
+            Err(error) => {
+                eprintln!("line {}: {}", line_no + 1, error);
+                println!();
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn syllable_format(format: InputFormat, layout: &str) -> Result<SyllableFormat> {
+    Ok(match format {
+        InputFormat::Bopomofo => SyllableFormat::Bopomofo,
+        InputFormat::HanyuPinyin => SyllableFormat::Pinyin(PinyinVariant::HanyuPinyin),
+        InputFormat::ThlPinyin => SyllableFormat::Pinyin(PinyinVariant::ThlPinyin),
+        InputFormat::Mps2Pinyin => SyllableFormat::Pinyin(PinyinVariant::Mps2Pinyin),
+        InputFormat::Keys => {
+            let kb: KeyboardLayoutCompat = layout
+                .parse()
+                .map_err(|_| anyhow!("unknown keyboard layout {}", layout))?;
+            SyllableFormat::Keys(kb)
+        }
+    })
+}
+
+pub(crate) fn conversion_engine(args: &ConversionArgs) -> Box<dyn ConversionEngine> {
+    match args.engine {
+        Engine::Simple => Box::new(SimpleEngine::new()),
+        Engine::Chewing => Box::new(ChewingEngine::new()),
+        Engine::Fuzzy => Box::new(FuzzyChewingEngine::new()),
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use chewing::dictionary::DictionaryInfo;
+
+use crate::{
+    flags,
+    merge::{entry_map, open_read_only},
+    query::reading,
+};
+
+fn info_fields(info: &DictionaryInfo) -> [(&'static str, &str); 5] {
+    [
+        ("name", &info.name),
+        ("copyright", &info.copyright),
+        ("license", &info.license),
+        ("version", &info.version),
+        ("software", &info.software),
+    ]
+}
+
+pub(crate) fn run(args: flags::Diff) -> Result<()> {
+    let old = open_read_only(&args.old)?;
+    let new = open_read_only(&args.new)?;
+
+    for ((field, old), (_, new)) in info_fields(&old.about())
+        .into_iter()
+        .zip(info_fields(&new.about()))
+    {
+        if old != new {
+            println!("# {}: {} -> {}", field, old, new);
+        }
+    }
+
+    let old = entry_map(old.as_ref());
+    let new = entry_map(new.as_ref());
+    let keys: BTreeSet<_> = old.keys().chain(new.keys()).collect();
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for key in keys {
+        let (syllables, phrase) = key;
+        match (old.get(key), new.get(key)) {
+            (None, Some(new)) => {
+                added += 1;
+                println!("+ {}\t{}\t{}", phrase, reading(syllables), new.freq());
+            }
+            (Some(old), None) => {
+                removed += 1;
+                println!("- {}\t{}\t{}", phrase, reading(syllables), old.freq());
+            }
+            (Some(old), Some(new)) if old.freq() != new.freq() => {
+                changed += 1;
+                println!(
+                    "~ {}\t{}\t{} -> {}",
+                    phrase,
+                    reading(syllables),
+                    old.freq(),
+                    new.freq()
+                );
+            }
+            _ => {}
+        }
+    }
+    eprintln!(
+        "{} added, {} removed, {} frequency changes",
+        added, removed, changed
+    );
+    Ok(())
+}
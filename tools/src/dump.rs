@@ -1,13 +1,16 @@
 use std::{
     fs::File,
-    io::{stdout, BufWriter, Write},
-    path::PathBuf,
+    io::{BufWriter, Write, stdout},
 };
 
 use anyhow::Result;
-use chewing::dictionary::{Dictionary, SqliteDictionary, Trie};
+use chewing::{
+    dictionary::{Dictionary, SqliteDictionary, Trie},
+    zhuyin::{PinyinVariant, Syllable, ToneStyle},
+};
+use serde_json::{Value, json};
 
-use crate::flags;
+use crate::flags::{self, DumpFormat};
 
 pub(crate) fn run(args: flags::Dump) -> Result<()> {
     let ext = args
@@ -19,8 +22,16 @@ pub(crate) fn run(args: flags::Dump) -> Result<()> {
     } else {
         Box::new(Trie::open(&args.path)?)
     };
+    // RIME requires the name to match the file name
+    let rime_name = args
+        .output
+        .as_ref()
+        .and_then(|output| output.file_name()?.to_str()?.split('.').next())
+        .filter(|name| !name.is_empty() && *name != "-")
+        .unwrap_or("chewing")
+        .to_string();
     let sink: Box<dyn Write> = if let Some(output) = args.output {
-        if output == PathBuf::from("-") {
+        if output.as_os_str() == "-" {
             Box::new(stdout())
         } else {
             Box::new(File::create(output)?)
@@ -29,15 +40,50 @@ pub(crate) fn run(args: flags::Dump) -> Result<()> {
         Box::new(stdout())
     };
     let sink = BufWriter::new(sink);
-    if args.csv {
-        dump_dict_csv(sink, dict.as_ref())?;
+    let format = if args.csv {
+        DumpFormat::Csv
+    } else if args.json {
+        DumpFormat::Jsonl
     } else {
-        dump_dict_tsi_src(sink, dict.as_ref())?;
+        args.format
+    };
+    match format {
+        DumpFormat::TsiSrc => dump_dict_tsi_src(sink, dict.as_ref())?,
+        DumpFormat::Csv => dump_dict_csv(sink, dict.as_ref())?,
+        DumpFormat::Rime => dump_dict_rime(sink, dict.as_ref(), &rime_name)?,
+        DumpFormat::Mcbopomofo => dump_dict_mcbopomofo(sink, dict.as_ref())?,
+        DumpFormat::Jsonl => dump_dict_jsonl(sink, dict.as_ref())?,
     }
     Ok(())
 }
 
-fn dump_dict_tsi_src(mut sink: BufWriter<Box<dyn Write>>, dict: &dyn Dictionary) -> Result<()> {
+fn pinyin(syllables: &[Syllable], style: ToneStyle) -> String {
+    syllables
+        .iter()
+        .map(|syl| syl.to_pinyin(PinyinVariant::HanyuPinyin, style))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the RIME code of the syllables, spelled with `v` for `ü`.
+///
+/// Returns `None` if a syllable has no pinyin spelling, such as a lone tone
+/// mark.
+fn rime_code(syllables: &[Syllable]) -> Option<String> {
+    let spellings: Vec<String> = syllables
+        .iter()
+        .map(|syl| {
+            syl.to_pinyin(PinyinVariant::HanyuPinyin, ToneStyle::None)
+                .replace('ü', "v")
+        })
+        .collect();
+    if spellings.iter().any(String::is_empty) {
+        return None;
+    }
+    Some(spellings.join(" "))
+}
+
+fn dump_dict_tsi_src(mut sink: impl Write, dict: &dyn Dictionary) -> Result<()> {
     for (syllables, phrase) in dict.entries() {
         writeln!(
             sink,
@@ -54,7 +100,7 @@ fn dump_dict_tsi_src(mut sink: BufWriter<Box<dyn Write>>, dict: &dyn Dictionary)
     Ok(())
 }
 
-fn dump_dict_csv(mut sink: BufWriter<Box<dyn Write>>, dict: &dyn Dictionary) -> Result<()> {
+fn dump_dict_csv(mut sink: impl Write, dict: &dyn Dictionary) -> Result<()> {
     writeln!(sink, "詞(phrase),詞頻(freq),注音(bopomofo)")?;
     for (syllables, phrase) in dict.entries() {
         writeln!(
@@ -71,3 +117,154 @@ fn dump_dict_csv(mut sink: BufWriter<Box<dyn Write>>, dict: &dyn Dictionary) ->
     }
     Ok(())
 }
+
+fn dump_dict_rime(mut sink: impl Write, dict: &dyn Dictionary, name: &str) -> Result<()> {
+    let info = dict.about();
+    writeln!(sink, "# Rime dictionary")?;
+    writeln!(sink, "# encoding: utf-8")?;
+    writeln!(sink, "#")?;
+    writeln!(sink, "# {}", info.name)?;
+    if !info.copyright.is_empty() {
+        writeln!(sink, "# Copyright: {}", info.copyright)?;
+    }
+    if !info.license.is_empty() {
+        writeln!(sink, "# License: {}", info.license)?;
+    }
+    writeln!(sink)?;
+    writeln!(sink, "---")?;
+    writeln!(sink, "name: {}", name)?;
+    // A JSON string is also a YAML string
+    writeln!(sink, "version: {}", Value::from(info.version))?;
+    writeln!(sink, "sort: by_weight")?;
+    writeln!(sink, "use_preset_vocabulary: false")?;
+    writeln!(sink, "...")?;
+    writeln!(sink)?;
+    let mut skipped = 0;
+    for (syllables, phrase) in dict.entries() {
+        let Some(code) = rime_code(&syllables) else {
+            skipped += 1;
+            continue;
+        };
+        writeln!(sink, "{}\t{}\t{}", phrase, code, phrase.freq())?;
+    }
+    if skipped > 0 {
+        eprintln!("warning: {skipped} phrases without pinyin spelling skipped");
+    }
+    Ok(())
+}
+
+fn dump_dict_mcbopomofo(mut sink: impl Write, dict: &dyn Dictionary) -> Result<()> {
+    for (syllables, phrase) in dict.entries() {
+        writeln!(
+            sink,
+            "{} {}",
+            phrase,
+            syllables
+                .iter()
+                .map(|syl| syl.to_string())
+                .collect::<Vec<_>>()
+                .join("-")
+        )?;
+    }
+    Ok(())
+}
+
+fn dump_dict_jsonl(mut sink: impl Write, dict: &dyn Dictionary) -> Result<()> {
+    for (syllables, phrase) in dict.entries() {
+        let units: Vec<u16> = syllables
+            .iter()
+            .flat_map(|syl| {
+                let mut buf = [0; 2];
+                syl.encode_u16(&mut buf).to_vec()
+            })
+            .collect();
+        let bopomofo = syllables
+            .iter()
+            .map(|syl| syl.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let entry = json!({
+            "phrase": phrase.as_str(),
+            "syllables": units,
+            "bopomofo": bopomofo,
+            "pinyin": pinyin(&syllables, ToneStyle::Number),
+            "freq": phrase.freq(),
+            "last_used": phrase.last_used(),
+        });
+        writeln!(sink, "{}", entry)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chewing::{dictionary::TrieBuf, syl, zhuyin::Bopomofo::*};
+    use serde_json::Value;
+
+    use super::{dump_dict_jsonl, dump_dict_mcbopomofo, dump_dict_rime};
+
+    fn dict() -> TrieBuf {
+        TrieBuf::from([
+            (vec![syl![N, IU, TONE3]], vec![("女", 100)]),
+            (
+                vec![syl![L, IU, EH, TONE4], syl![SH, TONE4]],
+                vec![("略勢", 10)],
+            ),
+            (vec![syl![TONE5]], vec![("˙", 1)]),
+        ])
+    }
+
+    fn entries(output: &[u8]) -> Vec<&str> {
+        let output = std::str::from_utf8(output).unwrap();
+        output
+            .split_once("...\n\n")
+            .map_or(output, |(_, entries)| entries)
+            .lines()
+            .collect()
+    }
+
+    #[test]
+    fn rime_codes() {
+        let mut output = vec![];
+        dump_dict_rime(&mut output, &dict(), "chewing").unwrap();
+        let text = std::str::from_utf8(&output).unwrap();
+        assert!(text.contains("name: chewing\n"));
+        let mut lines = entries(&output);
+        lines.sort();
+        assert_eq!(vec!["女\tnv\t100", "略勢\tlve shi\t10"], lines);
+    }
+
+    #[test]
+    fn mcbopomofo_readings() {
+        let mut output = vec![];
+        dump_dict_mcbopomofo(&mut output, &dict()).unwrap();
+        let mut lines = entries(&output);
+        lines.sort();
+        assert_eq!(vec!["˙ ˙", "女 ㄋㄩˇ", "略勢 ㄌㄩㄝˋ-ㄕˋ"], lines);
+    }
+
+    #[test]
+    fn jsonl_entries() {
+        let mut output = vec![];
+        dump_dict_jsonl(&mut output, &dict()).unwrap();
+        let entries: Vec<Value> = entries(&output)
+            .into_iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(3, entries.len());
+        let entry = entries
+            .iter()
+            .find(|entry| entry["phrase"] == "略勢")
+            .unwrap();
+        assert_eq!("ㄌㄩㄝˋ ㄕˋ", entry["bopomofo"]);
+        assert_eq!("lüe4 shi4", entry["pinyin"]);
+        assert_eq!(10, entry["freq"]);
+        assert_eq!(
+            Value::from(vec![
+                syl![L, IU, EH, TONE4].to_u16(),
+                syl![SH, TONE4].to_u16()
+            ]),
+            entry["syllables"]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use anyhow::{Result, anyhow};
+use chewing::{
+    dictionary::{Layered, SystemDictionaryLoader, TrieBuf, UserDictionaryLoader},
+    editor::{
+        AbbrevTable, Editor, LaxUserFreqEstimate, SymbolSelector,
+        zhuyin_layout::KeyboardLayoutCompat,
+    },
+};
+use serde_json::{Value, json};
+
+use crate::{
+    convert::{conversion_engine, syllable_format},
+    flags,
+    merge::open_read_only,
+};
+
+/// The conversion of one line of the corpus.
+struct Sample {
+    line_no: usize,
+    input: String,
+    expected: String,
+    actual: String,
+    correct_chars: usize,
+    /// Names of the keys that correct the conversion
+    keys: Option<Vec<String>>,
+}
+
+impl Sample {
+    fn is_correct(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+pub(crate) fn run(args: flags::Eval) -> Result<()> {
+    let dict = if args.dict.is_empty() {
+        Layered::new(
+            SystemDictionaryLoader::new().load()?,
+            UserDictionaryLoader::new().load()?,
+        )
+    } else {
+        let dictionaries = args
+            .dict
+            .iter()
+            .map(|path| open_read_only(path))
+            .collect::<Result<_>>()?;
+        Layered::new(dictionaries, Box::new(TrieBuf::new_in_memory()))
+    };
+    let format = syllable_format(args.conversion.format, &args.conversion.layout)?;
+    let layout: KeyboardLayoutCompat = args
+        .conversion
+        .layout
+        .parse()
+        .map_err(|_| anyhow!("unknown keyboard layout {}", args.conversion.layout))?;
+
+    let name = args.corpus.display();
+    let mut corpus = vec![];
+    for (line_no, line) in BufReader::new(File::open(&args.corpus)?)
+        .lines()
+        .enumerate()
+    {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((input, expected)) = line.split_once('\t') else {
+            eprintln!(
+                "{}:{}: missing tab before the expected text",
+                name,
+                line_no + 1
+            );
+            continue;
+        };
+        match format.parse(&dict, input) {
+            Ok(syllables) => corpus.push((
+                line_no + 1,
+                input.to_string(),
+                expected.to_string(),
+                syllables,
+            )),
+            Err(error) => eprintln!("{}:{}: {}", name, line_no + 1, error),
+        }
+    }
+
+    let mut editor = Editor::new(
+        conversion_engine(&args.conversion),
+        dict,
+        LaxUserFreqEstimate::new(0),
+        AbbrevTable::new(),
+        SymbolSelector::default(),
+    );
+    let mut options = editor.editor_options();
+    options.candidates_per_page = args.candidates_per_page;
+    options.phrase_choice_rearward = args.rearward;
+    editor.set_editor_options(options);
+
+    let mut samples = vec![];
+    for (line_no, input, expected, syllables) in corpus {
+        let correction = editor.correct_conversion(&syllables, &expected, layout);
+        let correct_chars = expected
+            .chars()
+            .zip(correction.conversion.chars())
+            .filter(|(a, b)| a == b)
+            .count();
+        samples.push(Sample {
+            line_no,
+            input,
+            expected,
+            actual: correction.conversion,
+            correct_chars,
+            keys: correction
+                .keys
+                .map(|keys| keys.iter().map(|ev| format!("{:?}", ev.code)).collect()),
+        });
+    }
+    if args.json {
+        print_json_report(&samples);
+    } else {
+        print_report(&name.to_string(), &samples);
+    }
+    Ok(())
+}
+
+/// Totals of the samples as (correct sentences, characters, correct
+/// characters, correction keys, uncorrectable sentences).
+fn totals(samples: &[Sample]) -> (usize, usize, usize, usize, usize) {
+    let correct = samples.iter().filter(|s| s.is_correct()).count();
+    let chars = samples.iter().map(|s| s.expected.chars().count()).sum();
+    let correct_chars = samples.iter().map(|s| s.correct_chars).sum();
+    let keys = samples
+        .iter()
+        .filter_map(|s| s.keys.as_ref())
+        .map(|keys| keys.len())
+        .sum();
+    let uncorrectable = samples.iter().filter(|s| s.keys.is_none()).count();
+    (correct, chars, correct_chars, keys, uncorrectable)
+}
+
+fn ratio(n: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        n as f64 / total as f64
+    }
+}
+
+/// Marks the characters that differ from the other text with brackets.
+fn mark_diff(text: &str, other: &str) -> String {
+    let mut out = String::new();
+    let mut other = other.chars();
+    let mut in_diff = false;
+    for ch in text.chars() {
+        let same = other.next() == Some(ch);
+        if same == in_diff {
+            out.push(if in_diff { ']' } else { '[' });
+            in_diff = !in_diff;
+        }
+        out.push(ch);
+    }
+    if in_diff {
+        out.push(']');
+    }
+    out
+}
+
+fn print_report(name: &str, samples: &[Sample]) {
+    for sample in samples.iter().filter(|s| !s.is_correct()) {
+        println!("{}:{}: {}", name, sample.line_no, sample.input);
+        println!("- {}", mark_diff(&sample.expected, &sample.actual));
+        println!("+ {}", mark_diff(&sample.actual, &sample.expected));
+        match &sample.keys {
+            Some(keys) => println!("  {} keys: {}", keys.len(), keys.join(" ")),
+            None => println!("  cannot be corrected"),
+        }
+    }
+    let (correct, chars, correct_chars, keys, uncorrectable) = totals(samples);
+    println!("---");
+    println!(
+        "Sentence accuracy : {:.2}% ({}/{})",
+        ratio(correct, samples.len()) * 100.0,
+        correct,
+        samples.len()
+    );
+    println!(
+        "Character accuracy: {:.2}% ({}/{})",
+        ratio(correct_chars, chars) * 100.0,
+        correct_chars,
+        chars
+    );
+    println!("Correction keys   : {}", keys);
+    println!("Uncorrectable     : {}", uncorrectable);
+}
+
+fn print_json_report(samples: &[Sample]) {
+    let (correct, chars, correct_chars, keys, uncorrectable) = totals(samples);
+    let round = |ratio: f64| (ratio * 10000.0).round() / 10000.0;
+    let results: Vec<Value> = samples
+        .iter()
+        .map(|sample| {
+            json!({
+                "line": sample.line_no,
+                "input": sample.input,
+                "expected": sample.expected,
+                "actual": sample.actual,
+                "correct": sample.is_correct(),
+                "correct_characters": sample.correct_chars,
+                "correction_keys": sample.keys,
+            })
+        })
+        .collect();
+    let report = json!({
+        "sentences": samples.len(),
+        "correct_sentences": correct,
+        "sentence_accuracy": round(ratio(correct, samples.len())),
+        "characters": chars,
+        "correct_characters": correct_chars,
+        "character_accuracy": round(ratio(correct_chars, chars)),
+        "correction_keys": keys,
+        "uncorrectable": uncorrectable,
+        "results": results,
+    });
+    println!("{:#}", report);
+}
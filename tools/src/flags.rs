@@ -18,6 +18,36 @@ pub(crate) enum ChewingCliCmd {
     Info(Info),
     /// Dump the dictionary entries into tsi.src formatted stream
     Dump(Dump),
+    /// Check a text file for homophone mistakes
+    Proofread(Proofread),
+    /// Convert syllables read from standard input, one sentence per line
+    Convert(Convert),
+    /// Measure the conversion accuracy over a corpus
+    Eval(Eval),
+    /// Re-estimate the phrase frequencies from a text corpus
+    Train(Train),
+    /// Add the new phrases of a text file to the user dictionary
+    Learn(Learn),
+    /// Look up the phrases of syllables or the readings of a phrase
+    Query(Query),
+    /// Manage the entries of the user dictionary
+    User(User),
+    /// Import the user phrases of another input method
+    Import(Import),
+    /// Compare the entries of two dictionaries
+    Diff(Diff),
+    /// Combine several dictionaries into a new dictionary
+    Merge(Merge),
+    /// Check dictionary sources for mistakes
+    Lint(Lint),
+    /// Verify a trie dictionary file and recover its entries
+    Fsck(Fsck),
+    /// Analyse the entries and the file size of dictionaries
+    Stats(Stats),
+    /// Sign dictionaries with a minisign secret key
+    Sign(Sign),
+    /// Verify the minisign signatures of dictionaries
+    Verify(Verify),
 }
 
 #[derive(Args)]
@@ -46,6 +76,17 @@ pub(crate) struct InitDatabase {
     /// Read the dictionary source as CSV with header
     #[arg(long)]
     pub(crate) csv: bool,
+    /// Read the dictionary source as a CIN table, such as phone.cin
+    ///
+    /// The frequencies of the characters follow their order in the table.
+    #[arg(long, conflicts_with = "csv")]
+    pub(crate) cin: bool,
+    /// Print a build report in JSON format to standard output
+    ///
+    /// The report includes the statistics of the dictionary, the lines that
+    /// failed to parse, and the lines skipped with --skip-invalid.
+    #[arg(long)]
+    pub(crate) json: bool,
     /// Path to the dictionary source file
     pub(crate) tsi_src: PathBuf,
     /// Path to the output file
@@ -76,9 +117,450 @@ pub(crate) struct Dump {
     ///
     /// If OUTPUT equals to `-` then standard output will be used.
     pub(crate) output: Option<PathBuf>,
-    /// Output CSV format
+    /// Output CSV format, the same as `--format csv`
+    #[arg(long, conflicts_with_all = ["format", "json"])]
+    pub(crate) csv: bool,
+    /// Output JSON Lines, the same as `--format jsonl`
+    #[arg(long, conflicts_with = "format")]
+    pub(crate) json: bool,
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "tsi-src")]
+    pub(crate) format: DumpFormat,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum DumpFormat {
+    /// `phrase freq ㄅㄆ ㄇㄈ`, the format of tsi.src
+    TsiSrc,
+    /// CSV with a header row
+    Csv,
+    /// RIME dictionary source with toneless pinyin codes, `*.dict.yaml`
+    Rime,
+    /// McBopomofo or vChewing user phrases, `phrase ㄅㄆ-ㄇㄈ`
+    Mcbopomofo,
+    /// One JSON object per line, with the syllables as u16 code units, the
+    /// readings, and the last used time
+    Jsonl,
+}
+
+#[derive(Args)]
+pub(crate) struct Proofread {
+    /// Minimum confidence, from 0 to 1, of reported mistakes
+    #[arg(short, long, default_value_t = 0.5)]
+    pub(crate) min_confidence: f64,
+    /// Path to the text file
+    ///
+    /// If FILE equals to `-` then standard input will be used.
+    pub(crate) file: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct ConversionArgs {
+    /// How the syllables are written
+    #[arg(short, long, value_enum, default_value = "bopomofo")]
+    pub(crate) format: InputFormat,
+    /// Keyboard layout of the keys format and of typed keys, e.g. KB_HSU
+    #[arg(short, long, default_value = "KB_DEFAULT")]
+    pub(crate) layout: String,
+    /// Conversion engine
+    #[arg(short, long, value_enum, default_value = "chewing")]
+    pub(crate) engine: Engine,
+}
+
+#[derive(Args)]
+pub(crate) struct Convert {
+    #[command(flatten)]
+    pub(crate) conversion: ConversionArgs,
+    /// Number of sentences to output for each line
+    #[arg(short('n'), long, default_value_t = 1)]
+    pub(crate) top: usize,
+}
+
+#[derive(Args)]
+pub(crate) struct Eval {
+    #[command(flatten)]
+    pub(crate) conversion: ConversionArgs,
+    /// Dictionary files to use instead of the system and user dictionaries
+    #[arg(short, long)]
+    pub(crate) dict: Vec<PathBuf>,
+    /// Number of candidates per page when counting correction keys
+    #[arg(short('p'), long, default_value_t = 10)]
+    pub(crate) candidates_per_page: usize,
+    /// Select candidates from the phrase before the cursor
+    #[arg(short, long)]
+    pub(crate) rearward: bool,
+    /// Output in JSON format
+    #[arg(short, long)]
+    pub(crate) json: bool,
+    /// Path to the corpus file
+    ///
+    /// Each line holds the syllables and the expected text separated by a
+    /// tab. Empty lines and lines starting with `#` are skipped.
+    pub(crate) corpus: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct Train {
+    /// Choose the underlying database implementation.
+    #[arg(short('t'), long, value_enum, default_value = "trie")]
+    pub(crate) db_type: DbType,
+    /// Dictionary files to train instead of the system dictionaries
+    #[arg(short, long)]
+    pub(crate) dict: Vec<PathBuf>,
+    /// How the corpus is split into phrases
+    #[arg(short, long, value_enum, default_value = "engine")]
+    pub(crate) segmentation: Segmentation,
+    /// Count added to every phrase before estimating frequencies
+    #[arg(short('a'), long, default_value_t = 1.0)]
+    pub(crate) smoothing: f64,
+    /// Weight, from 0 to 1, of the corpus frequencies against the existing
+    /// frequencies
+    #[arg(short, long, default_value_t = 0.5)]
+    pub(crate) weight: f64,
+    /// Directory to write the trained dictionaries to
+    ///
+    /// Each dictionary is trained separately and written under its own file
+    /// name, with the extension of the database type.
+    #[arg(short, long)]
+    pub(crate) output: PathBuf,
+    /// Paths to the corpus files
+    ///
+    /// If a FILE equals to `-` then standard input will be used.
+    #[arg(required = true)]
+    pub(crate) corpus: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+pub(crate) struct Learn {
+    /// Word list of terms to add, one per line
+    #[arg(short, long)]
+    pub(crate) words: Option<PathBuf>,
+    /// Number of times a phrase must repeat in the text to be added
+    #[arg(short, long, default_value_t = 2)]
+    pub(crate) min_count: usize,
+    /// Minimum pointwise mutual information, in bits, between the parts of
+    /// a repeated phrase
+    #[arg(short('p'), long, default_value_t = 1.0)]
+    pub(crate) min_pmi: f64,
+    /// Maximum length of repeated phrases
+    #[arg(short('l'), long, default_value_t = 4)]
+    pub(crate) max_len: usize,
+    /// Only report the phrases that would be added
+    #[arg(short('n'), long)]
+    pub(crate) dry_run: bool,
+    /// Path to the user dictionary instead of the detected one
+    #[arg(short, long)]
+    pub(crate) user_path: Option<PathBuf>,
+    /// Modify the user dictionary even if a running editor is using it
+    #[arg(short('f'), long)]
+    pub(crate) force: bool,
+    /// Path to the text file
+    ///
+    /// If FILE equals to `-` then standard input will be used.
+    pub(crate) file: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct Query {
+    /// Search the system dictionaries
+    #[arg(short, long)]
+    pub(crate) system: bool,
+    /// Search the extra dictionaries
+    #[arg(short, long)]
+    pub(crate) extra: bool,
+    /// Search the user dictionary
+    #[arg(short, long)]
+    pub(crate) user: bool,
+    /// Search the dictionary file
+    #[arg(short, long)]
+    pub(crate) path: Vec<PathBuf>,
+    /// How the syllables are written, either bopomofo or pinyin
+    #[arg(short, long, value_enum, default_value = "bopomofo")]
+    pub(crate) format: InputFormat,
+    /// Lookup strategy for syllables
+    #[arg(short('S'), long, value_enum, default_value = "standard")]
+    pub(crate) strategy: Strategy,
+    /// Treat the query as a phrase and list its readings
+    #[arg(short('P'), long)]
+    pub(crate) phrase: bool,
+    /// Output in JSON format
+    #[arg(short, long)]
+    pub(crate) json: bool,
+    /// The syllables or the phrase
+    ///
+    /// Text with characters other than bopomofo and ASCII is looked up as a
+    /// phrase.
+    pub(crate) query: String,
+}
+
+#[derive(Args)]
+pub(crate) struct User {
+    /// Path to the user dictionary instead of the detected one
+    #[arg(short, long, global = true)]
+    pub(crate) user_path: Option<PathBuf>,
+    /// Modify the user dictionary even if a running editor is using it
+    #[arg(short('f'), long, global = true)]
+    pub(crate) force: bool,
+    #[command(subcommand)]
+    pub(crate) command: UserCmd,
+}
+
+#[derive(Args)]
+pub(crate) struct Import {
+    /// Format of the exported user phrases
+    #[arg(long, value_enum)]
+    pub(crate) from: ImportFormat,
+    /// Frequency of the phrases exported without one
+    #[arg(short('F'), long, default_value_t = 100)]
+    pub(crate) freq: u32,
+    /// Only report the phrases that would be imported
+    #[arg(short('n'), long)]
+    pub(crate) dry_run: bool,
+    /// Path to the user dictionary instead of the detected one
+    #[arg(short, long)]
+    pub(crate) user_path: Option<PathBuf>,
+    /// Modify the user dictionary even if a running editor is using it
+    #[arg(short('f'), long)]
+    pub(crate) force: bool,
+    /// Paths to the exported files
+    #[arg(required = true)]
+    pub(crate) files: Vec<PathBuf>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ImportFormat {
+    /// McBopomofo or vChewing user phrases, `phrase ㄅㄆ-ㄇㄈ`
+    #[value(alias("vchewing"))]
+    Mcbopomofo,
+    /// RIME user dictionary snapshot, `*.userdb.txt`
+    RimeUserdb,
+    /// RIME dictionary source, `*.dict.yaml`
+    RimeDict,
+    /// gcin or hime tsin text export, `phrase ㄅㄆ ㄇㄈ count`
+    #[value(alias("gcin"), alias("hime"))]
+    Tsin,
+    /// fcitx table text with pinyin codes, `code phrase`
+    FcitxTable,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum UserCmd {
+    /// List all entries
+    List(UserList),
+    /// List the entries whose phrase or reading contains the text
+    Search(UserSearch),
+    /// Add a phrase
+    Add(UserAdd),
+    /// Remove a phrase
+    #[command(alias = "rm")]
+    Remove(UserEntry),
+    /// Change the frequency of a phrase
+    SetFreq(UserSetFreq),
+}
+
+#[derive(Args)]
+pub(crate) struct UserList {
+    /// Output in JSON format
+    #[arg(short, long)]
+    pub(crate) json: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct UserSearch {
+    /// Output in JSON format
+    #[arg(short, long)]
+    pub(crate) json: bool,
+    /// Text to find in the phrase or the bopomofo reading
+    pub(crate) text: String,
+}
+
+#[derive(Args)]
+pub(crate) struct UserEntry {
+    /// How the reading is written, either bopomofo or pinyin
+    #[arg(short, long, value_enum, default_value = "bopomofo")]
+    pub(crate) format: InputFormat,
+    /// The phrase
+    pub(crate) phrase: String,
+    /// The syllables of the phrase, separated by spaces
+    pub(crate) reading: String,
+}
+
+#[derive(Args)]
+pub(crate) struct UserAdd {
+    #[command(flatten)]
+    pub(crate) entry: UserEntry,
+    /// Frequency of the phrase
+    #[arg(short('F'), long, default_value = "100")]
+    pub(crate) freq: u32,
+}
+
+#[derive(Args)]
+pub(crate) struct UserSetFreq {
+    #[command(flatten)]
+    pub(crate) entry: UserEntry,
+    /// The new frequency
+    pub(crate) freq: u32,
+}
+
+#[derive(Args)]
+pub(crate) struct Diff {
+    /// Path to the old dictionary
+    pub(crate) old: PathBuf,
+    /// Path to the new dictionary
+    pub(crate) new: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct Merge {
+    /// Choose the underlying database implementation.
+    #[arg(short('t'), long, value_enum, default_value = "trie")]
+    pub(crate) db_type: DbType,
+    /// How to combine a phrase found in more than one dictionary
+    #[arg(short, long, value_enum, default_value = "max")]
+    pub(crate) policy: MergePolicy,
+    /// Name of the merged dictionary instead of the name of the first one
+    #[arg(short, long)]
+    pub(crate) name: Option<String>,
+    /// Copyright information instead of the combined copyright
+    #[arg(short, long)]
+    pub(crate) copyright: Option<String>,
+    /// License information instead of the combined license
+    #[arg(short, long)]
+    pub(crate) license: Option<String>,
+    /// Version instead of the version of the first dictionary
+    #[arg(short('r'), long)]
+    pub(crate) version: Option<String>,
+    /// Path to the output file
+    #[arg(short, long)]
+    pub(crate) output: PathBuf,
+    /// Paths to the dictionaries, the most preferred first
+    #[arg(required = true)]
+    pub(crate) dict: Vec<PathBuf>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum MergePolicy {
+    /// Keep the highest frequency
+    Max,
+    /// Add up the frequencies
+    Sum,
+    /// Keep the entry of the first dictionary that has the phrase
+    PreferLeft,
+    /// Keep the most recently used entry
+    Newest,
+}
+
+#[derive(Args)]
+pub(crate) struct Lint {
+    /// Read the dictionary sources as CSV with header
     #[arg(long)]
     pub(crate) csv: bool,
+    /// Path to the word.src that lists the readings of the characters
+    ///
+    /// Without it the single characters of the sources are used.
+    #[arg(short, long)]
+    pub(crate) word: Option<PathBuf>,
+    /// Report frequencies that are more than this many standard deviations
+    /// away from the mean, on a log scale
+    #[arg(long, default_value_t = 4.0)]
+    pub(crate) outlier: f64,
+    /// Output format of the report
+    #[arg(short, long, value_enum, default_value = "gnu")]
+    pub(crate) format: LintFormat,
+    /// Exit with failure on warnings too, not only on errors
+    #[arg(long)]
+    pub(crate) deny_warnings: bool,
+    /// Paths to the dictionary sources
+    #[arg(required = true)]
+    pub(crate) sources: Vec<PathBuf>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum LintFormat {
+    /// `file:line: message`, understood by most editors
+    Gnu,
+    /// A JSON array of diagnostics
+    Json,
+    /// GitHub Actions workflow commands
+    Github,
+}
+
+#[derive(Args)]
+pub(crate) struct Fsck {
+    /// Write every readable entry into a fresh dictionary
+    #[arg(short, long)]
+    pub(crate) salvage: Option<PathBuf>,
+    /// Choose the underlying database implementation of the fresh dictionary.
+    #[arg(short('t'), long, value_enum, default_value = "trie")]
+    pub(crate) db_type: DbType,
+    /// Path to the trie dictionary file
+    pub(crate) path: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct Stats {
+    /// Output in JSON format
+    #[arg(short, long)]
+    pub(crate) json: bool,
+    /// Paths to the dictionary files
+    #[arg(required = true)]
+    pub(crate) paths: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+pub(crate) struct Sign {
+    /// Path to the minisign secret key
+    #[arg(short, long)]
+    pub(crate) secret_key: PathBuf,
+    /// Trusted comment stored in the signatures
+    #[arg(short, long)]
+    pub(crate) trusted_comment: Option<String>,
+    /// Paths to the files to sign, each signature is written to `<file>.minisig`
+    #[arg(required = true)]
+    pub(crate) files: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+pub(crate) struct Verify {
+    /// Path to a minisign public key, or a folder of `*.pub` keys
+    #[arg(short('p'), long, required_unless_present = "key")]
+    pub(crate) public_key: Vec<PathBuf>,
+    /// Base64 encoded minisign public key
+    #[arg(short('P'), long)]
+    pub(crate) key: Vec<String>,
+    /// Paths to the signed files, with the signatures in `<file>.minisig`
+    #[arg(required = true)]
+    pub(crate) files: Vec<PathBuf>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Strategy {
+    Standard,
+    FuzzyPartialPrefix,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Segmentation {
+    /// Take the longest phrase at each position
+    LongestMatch,
+    /// Take the phrases the conversion engine chooses for the readings
+    Engine,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum InputFormat {
+    Bopomofo,
+    HanyuPinyin,
+    ThlPinyin,
+    Mps2Pinyin,
+    Keys,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Engine {
+    Simple,
+    Chewing,
+    Fuzzy,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
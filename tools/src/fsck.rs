@@ -0,0 +1,50 @@
+use std::{fs::File, path::Path, process::ExitCode};
+
+use anyhow::{Context, Result};
+use chewing::dictionary::{
+    DictionaryBuilder, DictionaryInfo, SqliteDictionaryBuilder, Trie, TrieBuilder,
+};
+
+use crate::flags::{self, DbType};
+
+pub(crate) fn run(args: flags::Fsck) -> Result<ExitCode> {
+    let name = args.path.display();
+    let file = File::open(&args.path).with_context(|| format!("opening {}", name))?;
+    let salvage = Trie::salvage(file)?;
+    for problem in &salvage.problems {
+        println!("{}: {}", name, problem);
+    }
+    if salvage.problems.is_empty() {
+        eprintln!("{}: no problems found", name);
+    } else {
+        eprintln!("{}: {} problems found", name, salvage.problems.len());
+    }
+
+    if let Some(output) = &args.salvage {
+        let mut builder: Box<dyn DictionaryBuilder> = match args.db_type {
+            DbType::Sqlite => Box::new(SqliteDictionaryBuilder::new()),
+            DbType::Trie => Box::new(TrieBuilder::new()),
+        };
+        builder.set_info(DictionaryInfo {
+            software: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            ..salvage.info
+        })?;
+        for (syllables, phrase) in &salvage.entries {
+            builder.insert(syllables, phrase.clone())?;
+        }
+        let path: &Path = output.as_ref();
+        if path.exists() && path != args.path {
+            std::fs::remove_file(path).context("unable to overwrite output")?;
+        }
+        builder.build(path)?;
+        eprintln!(
+            "Recovered {} entries into {}",
+            salvage.entries.len(),
+            path.display()
+        );
+    }
+    if !salvage.problems.is_empty() {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
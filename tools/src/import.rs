@@ -0,0 +1,606 @@
+use std::{cmp::Reverse, fs};
+
+use anyhow::{Context, Result};
+use chewing::{
+    conversion::SyllableFormat,
+    dictionary::{Dictionary, LookupStrategy, Phrase, SystemDictionaryLoader, TrieBuf},
+    zhuyin::{Bopomofo, PinyinVariant, Syllable},
+};
+
+use crate::{
+    flags::{self, ImportFormat},
+    query::reading,
+    user::{dict_mut, find, load_locked},
+};
+
+/// A user phrase read from the export of another input method.
+#[derive(Debug, PartialEq)]
+struct Record {
+    line: usize,
+    phrase: String,
+    reading: String,
+    freq: Option<u32>,
+}
+
+/// A line of the export that cannot be imported.
+#[derive(Debug, PartialEq)]
+struct Problem {
+    line: usize,
+    message: String,
+}
+
+impl Problem {
+    fn new(line: usize, message: impl Into<String>) -> Problem {
+        Problem {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+type Parsed = Result<Record, Problem>;
+
+fn is_bopomofo(text: &str) -> bool {
+    text.chars()
+        .any(|ch| !ch.is_ascii() && Bopomofo::try_from(ch).is_ok())
+}
+
+/// Returns the lines that are not empty or comments, numbered from 1.
+fn content_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Parses `phrase ㄅㄆ-ㄇㄈ [weight]` lines. The weights are log
+/// probabilities, so they are not used as frequencies.
+fn parse_mcbopomofo(text: &str) -> Vec<Parsed> {
+    content_lines(text)
+        .map(|(line, content)| {
+            let tokens: Vec<&str> = content
+                .split_whitespace()
+                .take_while(|token| !token.starts_with('#'))
+                .collect();
+            let reading = tokens
+                .iter()
+                .find(|token| is_bopomofo(token))
+                .ok_or_else(|| Problem::new(line, "missing bopomofo reading"))?;
+            let phrase = tokens
+                .iter()
+                .find(|token| !is_bopomofo(token) && token.parse::<f64>().is_err())
+                .ok_or_else(|| Problem::new(line, "missing phrase"))?;
+            Ok(Record {
+                line,
+                phrase: phrase.to_string(),
+                reading: reading.replace('-', " "),
+                freq: None,
+            })
+        })
+        .collect()
+}
+
+/// Parses `code\tphrase\tc=count d=... t=...` lines. Entries with a count of
+/// zero or less were deleted by the user and are skipped.
+fn parse_rime_userdb(text: &str) -> Vec<Parsed> {
+    content_lines(text)
+        .filter_map(|(line, content)| {
+            let mut fields = content.split('\t');
+            let (Some(code), Some(phrase)) = (fields.next(), fields.next()) else {
+                return Some(Err(Problem::new(line, "expected code and phrase")));
+            };
+            let count = fields
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .find_map(|attr| attr.strip_prefix("c="))
+                .and_then(|count| count.parse::<i64>().ok());
+            if count.is_some_and(|count| count <= 0) {
+                return None;
+            }
+            Some(Ok(Record {
+                line,
+                phrase: phrase.trim().to_string(),
+                reading: code.trim().to_string(),
+                freq: count.map(|count| count.min(u32::MAX.into()) as u32),
+            }))
+        })
+        .collect()
+}
+
+/// Parses the entries after the YAML header, in the order given by the
+/// `columns` setting. Entries without a code are encoded by RIME from the
+/// single characters, which cannot be done here.
+fn parse_rime_dict(text: &str) -> Vec<Parsed> {
+    let lines: Vec<&str> = text.lines().collect();
+    let body = lines
+        .iter()
+        .position(|line| line.trim_end() == "...")
+        .map_or(0, |end| end + 1);
+    let mut columns = vec!["text", "code", "weight"];
+    let mut in_columns = false;
+    for line in &lines[..body] {
+        let trimmed = line.trim();
+        if trimmed.starts_with("columns:") {
+            columns.clear();
+            in_columns = true;
+        } else if let Some(column) = trimmed.strip_prefix("- ").filter(|_| in_columns) {
+            columns.push(column.trim());
+        } else if !trimmed.is_empty() {
+            in_columns = false;
+        }
+    }
+    let column = |name: &str| columns.iter().position(|column| *column == name);
+    let (text_col, code_col, weight_col) = (column("text"), column("code"), column("weight"));
+
+    lines[body..]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| (body + i + 1, line.trim_end()))
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(line, content)| {
+            let fields: Vec<&str> = content.split('\t').collect();
+            let field = |col: Option<usize>| col.and_then(|col| fields.get(col).copied());
+            let phrase = field(text_col).ok_or_else(|| Problem::new(line, "missing text"))?;
+            let code = field(code_col)
+                .filter(|code| !code.trim().is_empty())
+                .ok_or_else(|| Problem::new(line, "missing code"))?;
+            Ok(Record {
+                line,
+                phrase: phrase.trim().to_string(),
+                reading: code.trim().to_string(),
+                freq: field(weight_col).and_then(|weight| weight.trim().parse().ok()),
+            })
+        })
+        .collect()
+}
+
+/// Parses `phrase ㄅㄆ ㄇㄈ count` lines written by `tsd2a32`.
+fn parse_tsin(text: &str) -> Vec<Parsed> {
+    content_lines(text)
+        .map(|(line, content)| {
+            let mut tokens: Vec<&str> = content.split_whitespace().collect();
+            let freq = match tokens.last().map(|token| token.parse::<u32>()) {
+                Some(Ok(count)) if tokens.len() > 2 => {
+                    tokens.pop();
+                    Some(count)
+                }
+                _ => None,
+            };
+            if tokens.len() < 2 {
+                return Err(Problem::new(line, "expected phrase and reading"));
+            }
+            Ok(Record {
+                line,
+                phrase: tokens[0].to_string(),
+                reading: tokens[1..].join(" "),
+                freq,
+            })
+        })
+        .collect()
+}
+
+/// Parses `code phrase` lines after the `[Data]` section marker, or every
+/// line when there is no header.
+///
+/// Only pinyin is imported. The codes of a table are used only if all of
+/// them are pinyin, because tables of shape-based input methods like
+/// cangjie or wubi have codes that sometimes look like pinyin. Otherwise
+/// only the entries marked with the pinyin key, `@` by default, are used.
+fn parse_fcitx_table(text: &str) -> Vec<Parsed> {
+    let lines: Vec<&str> = text.lines().collect();
+    let marker = lines
+        .iter()
+        .position(|line| matches!(line.trim(), "[Data]" | "[数据]"));
+    let body = marker.map_or(0, |marker| marker + 1);
+    let (mut pinyin_key, mut prompt_key, mut phrase_key) = ('@', '&', '^');
+    for line in &lines[..body] {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Some(value) = value.trim().chars().next() else {
+            continue;
+        };
+        match key.trim() {
+            "Pinyin" | "拼音" => pinyin_key = value,
+            "Prompt" | "提示" => prompt_key = value,
+            "ConstructPhrase" | "构词" => phrase_key = value,
+            _ => {}
+        }
+    }
+
+    let mut pinyin = vec![];
+    let mut codes = vec![];
+    for (line, content) in lines[body..]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| (body + i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+    {
+        // Prompts and phrase rules of the keys are not phrases
+        if content.starts_with([prompt_key, phrase_key]) {
+            continue;
+        }
+        let (entries, content) = match content.strip_prefix(pinyin_key) {
+            Some(content) => (&mut pinyin, content),
+            None => (&mut codes, content),
+        };
+        let record = content
+            .split_once(char::is_whitespace)
+            .map(|(code, phrase)| Record {
+                line,
+                phrase: phrase.trim().to_string(),
+                reading: code.to_string(),
+                freq: None,
+            })
+            .ok_or_else(|| Problem::new(line, "expected code and phrase"));
+        entries.push(record);
+    }
+    let not_pinyin = codes.iter().find_map(|record| match record {
+        Ok(record) => parse_reading(&record.phrase, &record.reading)
+            .is_err()
+            .then_some(record),
+        Err(_) => None,
+    });
+    if let Some(record) = not_pinyin {
+        let message = format!(
+            "not a pinyin table, {} is not the pinyin of {}, only pinyin entries are imported",
+            record.reading, record.phrase
+        );
+        pinyin.push(Err(Problem::new(
+            marker.map_or(1, |marker| marker + 1),
+            message,
+        )));
+    } else {
+        pinyin.extend(codes);
+    }
+    pinyin.sort_by_key(|parsed| match parsed {
+        Ok(record) => record.line,
+        Err(problem) => problem.line,
+    });
+    pinyin
+}
+
+/// A syllable of a reading. Readings of some input methods do not have
+/// tones, which are guessed later.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reading {
+    Toned(Syllable),
+    Toneless(Syllable),
+}
+
+fn parse_pinyin(text: &str) -> Option<Reading> {
+    let syllable = Syllable::from_pinyin(text, PinyinVariant::HanyuPinyin)
+        .ok()
+        .filter(Syllable::is_valid)?;
+    let toned = !text.is_ascii() || text.ends_with(|ch: char| ch.is_ascii_digit());
+    Some(if toned {
+        Reading::Toned(syllable)
+    } else {
+        Reading::Toneless(syllable)
+    })
+}
+
+/// Splits romanized text into exactly `count` syllables. Syllables may be
+/// separated by spaces, apostrophes, or hyphens, or written together.
+fn split_pinyin(text: &str, count: usize, readings: &mut Vec<Reading>) -> bool {
+    let text = text.trim_start_matches(|ch: char| ch.is_whitespace() || ch == '\'' || ch == '-');
+    if text.is_empty() || count == 0 {
+        return text.is_empty() && count == 0;
+    }
+    let ends: Vec<usize> = text
+        .char_indices()
+        .map(|(i, ch)| i + ch.len_utf8())
+        .take_while(|&end| {
+            !text[..end].ends_with(|ch: char| ch.is_whitespace() || ch == '\'' || ch == '-')
+        })
+        .take(7)
+        .collect();
+    for &end in ends.iter().rev() {
+        if let Some(reading) = parse_pinyin(&text[..end]) {
+            readings.push(reading);
+            if split_pinyin(&text[end..], count - 1, readings) {
+                return true;
+            }
+            readings.pop();
+        }
+    }
+    false
+}
+
+fn parse_reading(phrase: &str, text: &str) -> Result<Vec<Reading>, String> {
+    let len = phrase.chars().count();
+    let readings = if is_bopomofo(text) {
+        SyllableFormat::Bopomofo
+            .parse(&TrieBuf::new_in_memory(), &text.replace('-', " "))
+            .map_err(|error| format!("{text}: {error}"))?
+            .into_iter()
+            .map(Reading::Toned)
+            .collect()
+    } else {
+        let mut readings = vec![];
+        if !split_pinyin(text, len, &mut readings) {
+            return Err(format!("cannot read {text} as {len} syllables"));
+        }
+        readings
+    };
+    if readings.len() != len {
+        return Err(format!(
+            "{} has {} characters but {} syllables",
+            phrase,
+            len,
+            readings.len()
+        ));
+    }
+    Ok(readings)
+}
+
+/// Limit of the tone combinations looked up for a phrase.
+const MAX_COMBINATIONS: usize = 256;
+
+/// Returns the frequency of the phrase read as the syllables in the
+/// dictionaries, if it is there.
+fn phrase_freq(dicts: &[Box<dyn Dictionary>], syllables: &[Syllable], phrase: &str) -> Option<u32> {
+    dicts
+        .iter()
+        .flat_map(|dict| dict.lookup_all_phrases(&syllables, LookupStrategy::Standard))
+        .filter(|it| it.as_str() == phrase)
+        .map(|it| it.freq())
+        .max()
+}
+
+/// Fills in the missing tones with the readings of the system dictionaries.
+///
+/// The reading of the whole phrase is preferred. Otherwise each character
+/// takes its most frequent reading.
+fn resolve_tones(
+    dicts: &[Box<dyn Dictionary>],
+    phrase: &str,
+    readings: &[Reading],
+) -> Result<Vec<Syllable>, String> {
+    let mut candidates: Vec<Vec<Syllable>> = vec![];
+    for (ch, reading) in phrase.chars().zip(readings) {
+        let syllable = match *reading {
+            Reading::Toned(syllable) => {
+                candidates.push(vec![syllable]);
+                continue;
+            }
+            Reading::Toneless(syllable) => syllable,
+        };
+        let mut tones: Vec<(u32, Syllable)> = [
+            None,
+            Some(Bopomofo::TONE2),
+            Some(Bopomofo::TONE3),
+            Some(Bopomofo::TONE4),
+            Some(Bopomofo::TONE5),
+        ]
+        .into_iter()
+        .filter_map(|tone| {
+            let mut syllable = syllable;
+            if let Some(tone) = tone {
+                syllable.update(tone);
+            }
+            Some((phrase_freq(dicts, &[syllable], &ch.to_string())?, syllable))
+        })
+        .collect();
+        if tones.is_empty() {
+            return Err(format!("cannot find the tone of {ch} {syllable}"));
+        }
+        tones.sort_by_key(|&(freq, _)| Reverse(freq));
+        candidates.push(tones.into_iter().map(|(_, syllable)| syllable).collect());
+    }
+
+    let mut choice = vec![0; candidates.len()];
+    for _ in 0..MAX_COMBINATIONS {
+        let syllables: Vec<Syllable> = choice
+            .iter()
+            .zip(&candidates)
+            .map(|(&i, tones)| tones[i])
+            .collect();
+        if candidates.len() == 1 || phrase_freq(dicts, &syllables, phrase).is_some() {
+            return Ok(syllables);
+        }
+        // Advance to the next combination
+        let Some(pos) = (0..choice.len()).find(|&pos| choice[pos] + 1 < candidates[pos].len())
+        else {
+            break;
+        };
+        choice[pos] += 1;
+        choice[..pos].fill(0);
+    }
+    Ok(candidates.iter().map(|tones| tones[0]).collect())
+}
+
+pub(crate) fn run(args: flags::Import) -> Result<()> {
+    let (mut dict, _lock) = load_locked(args.user_path.as_deref(), args.force)?;
+    let system = SystemDictionaryLoader::new()
+        .load()
+        .unwrap_or_else(|error| {
+            eprintln!("warning: {error}, readings without tones cannot be imported");
+            vec![]
+        });
+
+    let (mut imported, mut failed) = (0, 0);
+    let verb = if args.dry_run {
+        "would import"
+    } else {
+        "imported"
+    };
+    for path in &args.files {
+        let text =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let records = match args.from {
+            ImportFormat::Mcbopomofo => parse_mcbopomofo(&text),
+            ImportFormat::RimeUserdb => parse_rime_userdb(&text),
+            ImportFormat::RimeDict => parse_rime_dict(&text),
+            ImportFormat::Tsin => parse_tsin(&text),
+            ImportFormat::FcitxTable => parse_fcitx_table(&text),
+        };
+        for record in records {
+            let syllables = record.and_then(|record| {
+                parse_reading(&record.phrase, &record.reading)
+                    .and_then(|readings| resolve_tones(&system, &record.phrase, &readings))
+                    .map(|syllables| (record.phrase, syllables, record.freq))
+                    .map_err(|message| Problem::new(record.line, message))
+            });
+            let (phrase, syllables, freq) = match syllables {
+                Ok(entry) => entry,
+                Err(problem) => {
+                    failed += 1;
+                    eprintln!("{}:{}: {}", path.display(), problem.line, problem.message);
+                    continue;
+                }
+            };
+            // Keep the frequency the user already has if it is higher
+            let freq = freq
+                .unwrap_or(args.freq)
+                .max(find(dict.as_ref(), &syllables, &phrase).map_or(0, |old| old.freq()));
+            println!("{} {} {} {}", verb, phrase, reading(&syllables), freq);
+            if !args.dry_run {
+                dict_mut(dict.as_mut())?.update_phrase(
+                    &syllables,
+                    Phrase::new(phrase, freq),
+                    freq,
+                    0,
+                )?;
+            }
+            imported += 1;
+        }
+    }
+    if !args.dry_run {
+        dict_mut(dict.as_mut())?.flush()?;
+    }
+    if args.dry_run {
+        eprintln!("{} phrases would be imported, {} failed", imported, failed);
+    } else {
+        eprintln!("{} phrases imported, {} failed", imported, failed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chewing::{
+        dictionary::{Dictionary, TrieBuf},
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::{
+        Problem, Reading, Record, parse_fcitx_table, parse_mcbopomofo, parse_reading,
+        parse_rime_dict, parse_rime_userdb, parse_tsin, resolve_tones,
+    };
+
+    fn record(line: usize, phrase: &str, reading: &str, freq: Option<u32>) -> Record {
+        Record {
+            line,
+            phrase: phrase.to_string(),
+            reading: reading.to_string(),
+            freq,
+        }
+    }
+
+    #[test]
+    fn parse_exports() {
+        assert_eq!(
+            vec![
+                Ok(record(2, "小麥注音", "ㄒㄧㄠˇ ㄇㄞˋ ㄓㄨˋ ㄧㄣ", None)),
+                Ok(record(3, "測試", "ㄘㄜˋ ㄕˋ", None)),
+                Err(Problem::new(4, "missing bopomofo reading")),
+            ],
+            parse_mcbopomofo(
+                "# comment\n小麥注音 ㄒㄧㄠˇ-ㄇㄞˋ-ㄓㄨˋ-ㄧㄣ\n測試 ㄘㄜˋ-ㄕˋ -1.5\n_punctuation_list ，\n"
+            )
+        );
+        assert_eq!(
+            vec![Ok(record(2, "你好", "ni hao", Some(3)))],
+            parse_rime_userdb(
+                "#@/db_name\tluna_pinyin.userdb\nni hao \t你好\tc=3 d=2.1 t=10\nni \t妳\tc=-1 d=0 t=10\n"
+            )
+        );
+        assert_eq!(
+            vec![
+                Ok(record(8, "你好", "ni hao", Some(10))),
+                Err(Problem::new(9, "missing code")),
+            ],
+            parse_rime_dict(
+                "---\nname: test\ncolumns:\n  - text\n  - code\n  - weight\n...\n你好\tni hao\t10\n測試\n"
+            )
+        );
+        assert_eq!(
+            vec![Ok(record(1, "測試", "ㄘㄜˋ ㄕˋ", Some(5)))],
+            parse_tsin("測試 ㄘㄜˋ ㄕˋ 5\n")
+        );
+        assert_eq!(
+            vec![Ok(record(3, "測試", "ce4shi4", None))],
+            parse_fcitx_table("KeyCode=abc\n[Data]\nce4shi4 測試\n^c ㄘ\n")
+        );
+    }
+
+    #[test]
+    fn skip_codes_of_shape_tables() {
+        let cangjie = "KeyCode=abcdefghijklmnopqrstuvwxyz\nPinyin=@\nPrompt=&\n\
+            ConstructPhrase=^\n[Data]\n&a 日\n^a e2\na 日\nhqi 我\n@wo3 我\n";
+        let parsed = parse_fcitx_table(cangjie);
+        assert_eq!(2, parsed.len());
+        assert!(matches!(&parsed[0], Err(Problem { line: 5, .. })));
+        assert_eq!(Ok(record(10, "我", "wo3", None)), parsed[1]);
+    }
+
+    #[test]
+    fn read_syllables() {
+        assert_eq!(
+            Ok(vec![
+                Reading::Toned(syl![C, E, TONE4]),
+                Reading::Toned(syl![SH, TONE4])
+            ]),
+            parse_reading("測試", "ㄘㄜˋ ㄕˋ")
+        );
+        assert_eq!(
+            Ok(vec![
+                Reading::Toned(syl![C, E, TONE4]),
+                Reading::Toned(syl![SH, TONE4])
+            ]),
+            parse_reading("測試", "ce4shi4")
+        );
+        assert_eq!(
+            Ok(vec![
+                Reading::Toneless(syl![X, I]),
+                Reading::Toneless(syl![AN])
+            ]),
+            parse_reading("西安", "xi'an")
+        );
+        assert_eq!(
+            Ok(vec![
+                Reading::Toneless(syl![N, I]),
+                Reading::Toneless(syl![H, AU])
+            ]),
+            parse_reading("你好", "nihao")
+        );
+        assert!(parse_reading("測試", "ㄘㄜˋ").is_err());
+    }
+
+    #[test]
+    fn guess_tones() {
+        let dict = TrieBuf::from([
+            (vec![syl![H, AU, TONE3]], vec![("好", 100)]),
+            (vec![syl![H, AU, TONE4]], vec![("好", 10)]),
+            (vec![syl![AI, TONE4]], vec![("愛", 10)]),
+            (
+                vec![syl![AI, TONE4], syl![H, AU, TONE4]],
+                vec![("愛好", 10)],
+            ),
+        ]);
+        let dicts: Vec<Box<dyn Dictionary>> = vec![Box::new(dict)];
+        let toneless = |syllable| Reading::Toneless(syllable);
+        assert_eq!(
+            Ok(vec![syl![H, AU, TONE3]]),
+            resolve_tones(&dicts, "好", &[toneless(syl![H, AU])])
+        );
+        assert_eq!(
+            Ok(vec![syl![AI, TONE4], syl![H, AU, TONE4]]),
+            resolve_tones(&dicts, "愛好", &[toneless(syl![AI]), toneless(syl![H, AU])])
+        );
+        assert!(resolve_tones(&dicts, "你", &[toneless(syl![N, I])]).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use chewing::dictionary::{Dictionary, SystemDictionaryLoader, UserDictionaryLoader};
+use serde_json::{Value, json};
 
 use crate::flags;
 
@@ -22,42 +23,26 @@ pub(crate) fn run(args: flags::Info) -> Result<()> {
     Ok(())
 }
 
-fn escape_json(str: String) -> String {
-    use std::fmt::Write;
-    let mut out = String::new();
-    str.chars().for_each(|ch| {
-        if ch.is_control() {
-            write!(out, "\\u{:04x}", ch as u32).unwrap();
-        } else if ch == '\\' {
-            out.push_str("\\\\");
-        } else if ch == '\"' {
-            out.push_str("\\\"");
-        } else {
-            out.push(ch);
-        }
-    });
-    out
-}
-
 fn print_json_info(dictionaries: &[Box<dyn Dictionary>]) {
-    let mut iter = dictionaries.iter().peekable();
-    println!("[");
-    while let Some(dict) = iter.next() {
-        let path = dict
-            .path()
-            .map(|p| p.display().to_string())
-            .unwrap_or(String::new());
-        let info = dict.about();
-        println!("  {{");
-        println!(r#"    "path": "{}","#, escape_json(path));
-        println!(r#"    "name": "{}","#, escape_json(info.name));
-        println!(r#"    "version": "{}","#, escape_json(info.version));
-        println!(r#"    "copyright": "{}","#, escape_json(info.copyright));
-        println!(r#"    "license": "{}","#, escape_json(info.license));
-        println!(r#"    "software": "{}""#, escape_json(info.software));
-        println!("  }}{}", if iter.peek().is_some() { "," } else { "" });
-    }
-    println!("]");
+    let dictionaries: Vec<Value> = dictionaries
+        .iter()
+        .map(|dict| {
+            let path = dict
+                .path()
+                .map(|p| p.display().to_string())
+                .unwrap_or(String::new());
+            let info = dict.about();
+            json!({
+                "path": path,
+                "name": info.name,
+                "version": info.version,
+                "copyright": info.copyright,
+                "license": info.license,
+                "software": info.software,
+            })
+        })
+        .collect();
+    println!("{:#}", Value::from(dictionaries));
 }
 
 fn print_info(dictionaries: &[Box<dyn Dictionary>]) {
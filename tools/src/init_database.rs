@@ -1,9 +1,13 @@
 use anyhow::{Context, Result};
 use chewing::{
-    dictionary::{DictionaryBuilder, DictionaryInfo, SqliteDictionaryBuilder, TrieBuilder},
+    dictionary::{
+        DictionaryBuilder, DictionaryInfo, SqliteDictionaryBuilder, TrieBuilder, TrieStatistics,
+    },
     zhuyin::{Bopomofo, Syllable},
 };
+use serde_json::{Value, json};
 use std::{
+    collections::{BTreeMap, HashMap, HashSet},
     error::Error,
     fmt::Display,
     fs::{self, File},
@@ -73,28 +77,46 @@ pub(crate) fn run(args: flags::InitDatabase) -> Result<()> {
         software: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
     })?;
 
-    let tsi = File::open(args.tsi_src)?;
-    let reader = BufReader::new(tsi);
-    let delimiter = if args.csv { ',' } else { ' ' };
     let mut errors = vec![];
 
-    for (line_num, line) in reader.lines().enumerate() {
-        if args.csv && line_num == 0 {
-            continue;
+    if args.cin {
+        let text = fs::read_to_string(&args.tsi_src)?;
+        for entry in parse_cin(&text) {
+            match entry {
+                Ok((syllable, phrase, freq)) => {
+                    builder.insert(&[syllable], (phrase, freq).into())?
+                }
+                Err(error) => errors.push(error.into()),
+            }
+        }
+    } else {
+        let tsi = File::open(&args.tsi_src)?;
+        let reader = BufReader::new(tsi);
+        let delimiter = if args.csv { ',' } else { ' ' };
+        for (line_num, line) in reader.lines().enumerate() {
+            if args.csv && line_num == 0 {
+                continue;
+            }
+            let line = line?;
+            match parse_line(line_num, delimiter, &line, args.keep_word_freq) {
+                Ok((syllables, phrase, freq)) => {
+                    builder.insert(&syllables, (phrase, freq).into())?
+                }
+                Err(error) => errors.push(error),
+            };
         }
-        let line = line?;
-        match parse_line(line_num, delimiter, &line, args.keep_word_freq) {
-            Ok((syllables, phrase, freq)) => builder.insert(&syllables, (phrase, freq).into())?,
-            Err(error) => errors.push(error),
-        };
     }
-    if !errors.is_empty() {
-        for err in errors {
+    let build_errors: Vec<BuildError> = errors.iter().map(BuildError::from).collect();
+    if !args.json {
+        for err in &errors {
             eprintln!("{}", err);
         }
-        if !args.skip_invalid {
-            std::process::exit(1)
+    }
+    if !errors.is_empty() && !args.skip_invalid {
+        if args.json {
+            print_json_report(&args.output, &build_errors, false, None);
         }
+        std::process::exit(1)
     }
     let path: &Path = args.output.as_ref();
     if path.exists() {
@@ -102,8 +124,13 @@ pub(crate) fn run(args: flags::InitDatabase) -> Result<()> {
     }
     builder.build(path)?;
 
-    if let Some(trie_builder) = builder.as_any().downcast_ref::<TrieBuilder>() {
-        let stats = trie_builder.statistics();
+    let stats = builder
+        .as_any()
+        .downcast_ref::<TrieBuilder>()
+        .map(|trie_builder| trie_builder.statistics());
+    if args.json {
+        print_json_report(path, &build_errors, true, stats.as_ref());
+    } else if let Some(stats) = stats {
         eprintln!("== Trie Dictionary Statistics ==");
         eprintln!("Node count           : {}", stats.node_count);
         eprintln!("Leaf count           : {}", stats.leaf_count);
@@ -117,7 +144,82 @@ pub(crate) fn run(args: flags::InitDatabase) -> Result<()> {
     Ok(())
 }
 
-fn parse_line(
+/// A line of the dictionary source that failed to parse.
+#[derive(Debug, PartialEq)]
+struct BuildError {
+    /// One-based line number, or zero if the error is not about a line
+    line: usize,
+    text: String,
+    message: String,
+}
+
+impl From<&anyhow::Error> for BuildError {
+    fn from(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<ParseError>() {
+            Some(parse_error) => BuildError {
+                line: parse_error.line_num + 1,
+                text: parse_error.line.clone(),
+                message: format!("{:#}", parse_error.source),
+            },
+            None => BuildError {
+                line: 0,
+                text: String::new(),
+                message: format!("{:#}", error),
+            },
+        }
+    }
+}
+
+/// Prints the build report. Lines with errors are skipped when the build
+/// succeeds, which is only possible with `--skip-invalid`.
+fn print_json_report(
+    output: &Path,
+    errors: &[BuildError],
+    success: bool,
+    stats: Option<&TrieStatistics>,
+) {
+    let statistics = stats.map(|stats| {
+        json!({
+            "node_count": stats.node_count,
+            "leaf_count": stats.leaf_count,
+            "phrase_count": stats.phrase_count,
+            "max_height": stats.max_height,
+            "avg_height": stats.avg_height,
+            "root_branch_count": stats.root_branch_count,
+            "max_branch_count": stats.max_branch_count,
+            "avg_branch_count": stats.avg_branch_count,
+        })
+    });
+    let error_values: Vec<Value> = errors
+        .iter()
+        .map(|error| {
+            json!({
+                "line": error.line,
+                "text": error.text,
+                "message": error.message,
+            })
+        })
+        .collect();
+    let skipped: Vec<usize> = if success {
+        errors
+            .iter()
+            .filter(|error| error.line > 0)
+            .map(|error| error.line)
+            .collect()
+    } else {
+        vec![]
+    };
+    let report = json!({
+        "output": output.display().to_string(),
+        "success": success,
+        "statistics": statistics,
+        "errors": error_values,
+        "skipped_lines": skipped,
+    });
+    println!("{:#}", report);
+}
+
+pub(crate) fn parse_line(
     line_num: usize,
     delimiter: char,
     line: &str,
@@ -169,17 +271,167 @@ fn parse_line(
     Ok((syllables, phrase, freq))
 }
 
+/// Parses the `%chardef` section of a CIN table, mapping the keys to
+/// bopomofo with the `%keyname` section.
+///
+/// Characters listed earlier for the same keys get higher frequencies, so
+/// the order of the table is kept by every dictionary backend.
+fn parse_cin(text: &str) -> Vec<std::result::Result<(Syllable, &str, u32), ParseError>> {
+    let mut keynames: HashMap<char, Vec<Bopomofo>> = HashMap::new();
+    let mut chardefs = vec![];
+    let mut section = "";
+    for (line_num, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(directive) = line.strip_prefix('%') {
+            let mut words = directive.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(name), Some("begin")) => section = name,
+                (Some(_), Some("end")) => section = "",
+                _ => {}
+            }
+            continue;
+        }
+        let Some((keys, value)) = line.split_once(char::is_whitespace) else {
+            chardefs.push(Err(ParseError {
+                line_num,
+                line: line.to_string(),
+                source: anyhow::anyhow!("expected keys and a value"),
+            }));
+            continue;
+        };
+        // Values may be followed by comments
+        let value = value.split_whitespace().next().unwrap_or_default();
+        match section {
+            "keyname" => {
+                let bopomofo = value
+                    .chars()
+                    .map(Bopomofo::try_from)
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .context("parsing bopomofo")
+                    .parse_error(line_num, line);
+                match (keys.chars().next(), bopomofo) {
+                    (Some(key), Ok(bopomofo)) => {
+                        keynames.insert(key, bopomofo);
+                    }
+                    (_, Err(error)) => chardefs.push(Err(error)),
+                    (None, _) => {}
+                }
+            }
+            "chardef" => chardefs.push(Ok((line_num, line, keys, value))),
+            _ => {}
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut entries = vec![];
+    for chardef in chardefs {
+        let (line_num, line, keys, value) = match chardef {
+            Ok(chardef) => chardef,
+            Err(error) => {
+                entries.push(Err(error));
+                continue;
+            }
+        };
+        let syllable = keys
+            .chars()
+            .try_fold(Syllable::builder(), |builder, key| {
+                let bopomofo = keynames
+                    .get(&key)
+                    .with_context(|| format!("key {key} is not in %keyname"))?;
+                bopomofo.iter().try_fold(builder, |builder, &bopomofo| {
+                    builder
+                        .insert(bopomofo)
+                        .with_context(|| format!("parsing keys {keys}"))
+                })
+            })
+            .map(|builder| builder.build())
+            .parse_error(line_num, line);
+        match syllable {
+            Ok(syllable) => {
+                if seen.insert((syllable, value)) {
+                    entries.push(Ok((syllable, value)));
+                }
+            }
+            Err(error) => entries.push(Err(error)),
+        }
+    }
+
+    let mut counts: BTreeMap<Syllable, u32> = BTreeMap::new();
+    for (syllable, _) in entries.iter().flatten() {
+        *counts.entry(*syllable).or_default() += 1;
+    }
+    let mut positions: BTreeMap<Syllable, u32> = BTreeMap::new();
+    entries
+        .into_iter()
+        .map(|entry| {
+            let (syllable, value) = entry?;
+            let position = positions.entry(syllable).or_default();
+            *position += 1;
+            Ok((syllable, value, counts[&syllable] - *position + 1))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use chewing::syl;
     use chewing::zhuyin::Bopomofo::*;
 
-    use super::parse_line;
+    use super::{BuildError, parse_cin, parse_line};
+
+    #[test]
+    fn parse_cin_table() {
+        let cin = "%gen_inp
+%ename  Phonetic
+%keyname  begin
+h  ㄘ
+k  ㄜ
+g  ㄕ
+4  ˋ
+%keyname  end
+%chardef  begin
+hk4 測
+hk4 策 # comment
+hk4 測
+g4 試
+x4 錯
+%chardef  end
+";
+        let entries: Vec<_> = parse_cin(cin)
+            .into_iter()
+            .map(|entry| entry.map_err(|error| error.line_num + 1))
+            .collect();
+        assert_eq!(
+            vec![
+                Ok((syl![C, E, TONE4], "測", 2)),
+                Ok((syl![C, E, TONE4], "策", 1)),
+                Ok((syl![SH, TONE4], "試", 1)),
+                Err(14),
+            ],
+            entries
+        );
+    }
+
+    #[test]
+    fn build_error_from_parse_error() {
+        let error = parse_line(4, ' ', "測試 100 ㄘㄜˋ ㄕX", false).unwrap_err();
+        assert_eq!(
+            BuildError {
+                line: 5,
+                text: "測試 100 ㄘㄜˋ ㄕX".to_string(),
+                message: "parsing bopomofo: Parse bopomofo error: InvalidSymbol".to_string(),
+            },
+            BuildError::from(&error)
+        );
+    }
 
     #[test]
     fn parse_ssv() {
         let line = "鑰匙 668 ㄧㄠˋ ㄔˊ # not official";
-        if let Ok((syllables, phrase, freq)) = parse_line(0, ' ', &line, false) {
+        if let Ok((syllables, phrase, freq)) = parse_line(0, ' ', line, false) {
             assert_eq!(syllables, vec![syl![I, AU, TONE4], syl![CH, TONE2]]);
             assert_eq!("鑰匙", phrase);
             assert_eq!(668, freq);
@@ -191,7 +443,7 @@ mod tests {
     #[test]
     fn parse_csv() {
         let line = "鑰匙,668,ㄧㄠˋ ㄔˊ # not official";
-        if let Ok((syllables, phrase, freq)) = parse_line(0, ',', &line, false) {
+        if let Ok((syllables, phrase, freq)) = parse_line(0, ',', line, false) {
             assert_eq!(syllables, vec![syl![I, AU, TONE4], syl![CH, TONE2]]);
             assert_eq!("鑰匙", phrase);
             assert_eq!(668, freq);
@@ -203,7 +455,7 @@ mod tests {
     #[test]
     fn parse_csv_quoted() {
         let line = "\"鑰匙\",668,\"ㄧㄠˋ ㄔˊ # not official\"";
-        if let Ok((syllables, phrase, freq)) = parse_line(0, ',', &line, false) {
+        if let Ok((syllables, phrase, freq)) = parse_line(0, ',', line, false) {
             assert_eq!(syllables, vec![syl![I, AU, TONE4], syl![CH, TONE2]]);
             assert_eq!("鑰匙", phrase);
             assert_eq!(668, freq);
@@ -0,0 +1,60 @@
+use std::{
+    fs::{self, File},
+    io::{Read, stdin},
+};
+
+use anyhow::Result;
+use chewing::{
+    conversion::{PhraseFinder, add_new_phrases},
+    dictionary::{DictionaryMut, Layered, SystemDictionaryLoader},
+};
+
+use crate::{flags, user::load_locked};
+
+pub(crate) fn run(args: flags::Learn) -> Result<()> {
+    let (user_dict, _lock) = load_locked(args.user_path.as_deref(), args.force)?;
+    let mut dict = Layered::new(SystemDictionaryLoader::new().load()?, user_dict);
+    let mut text = String::new();
+    if args.file.as_os_str() == "-" {
+        stdin().read_to_string(&mut text)?;
+    } else {
+        File::open(&args.file)?.read_to_string(&mut text)?;
+    }
+    let terms: Vec<String> = match &args.words {
+        Some(path) => fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect(),
+        None => vec![],
+    };
+
+    let finder = PhraseFinder::new(&dict)
+        .terms(terms.iter().cloned())
+        .min_count(args.min_count)
+        .min_pmi(args.min_pmi)
+        .max_len(args.max_len);
+    for term in &terms {
+        if finder.reading(term).is_none() {
+            eprintln!("skipped {}: no reading", term);
+        }
+    }
+    let phrases = finder.find(&text);
+    let verb = if args.dry_run { "would add" } else { "added" };
+    for phrase in &phrases {
+        let reading: Vec<String> = phrase.syllables.iter().map(|s| s.to_string()).collect();
+        println!(
+            "{} {} {} ({})",
+            verb,
+            phrase.phrase,
+            reading.join(" "),
+            phrase.count
+        );
+    }
+    if !args.dry_run {
+        add_new_phrases(&mut dict, &phrases)?;
+        dict.flush()?;
+    }
+    Ok(())
+}
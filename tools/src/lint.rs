@@ -0,0 +1,380 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    process::ExitCode,
+};
+
+use anyhow::{Context, Result};
+use chewing::zhuyin::Syllable;
+use serde_json::{Value, json};
+use unicode_normalization::{UnicodeNormalization, is_nfc};
+
+use crate::{
+    flags::{self, LintFormat},
+    init_database::parse_line,
+};
+
+/// A parsed line of a dictionary source.
+struct Entry {
+    file: usize,
+    line: usize,
+    phrase: String,
+    freq: u32,
+    syllables: Vec<Syllable>,
+}
+
+/// A problem found in a dictionary source.
+#[derive(Debug, PartialEq)]
+struct Diagnostic {
+    file: usize,
+    line: usize,
+    /// Error or warning
+    severity: &'static str,
+    /// Short name of the check
+    code: &'static str,
+    message: String,
+}
+
+impl Diagnostic {
+    fn warning(entry: &Entry, code: &'static str, message: String) -> Diagnostic {
+        Diagnostic {
+            file: entry.file,
+            line: entry.line,
+            severity: "warning",
+            code,
+            message,
+        }
+    }
+}
+
+fn check(entries: &[Entry], readings: &HashSet<(char, Syllable)>, outlier: f64) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut seen: HashMap<(&str, &[Syllable]), &Entry> = HashMap::new();
+    for entry in entries {
+        if let Some(first) = seen.get(&(entry.phrase.as_str(), entry.syllables.as_slice())) {
+            diagnostics.push(Diagnostic::warning(
+                entry,
+                "duplicate",
+                if first.file == entry.file {
+                    format!("duplicate of the entry on line {}", first.line)
+                } else {
+                    "duplicate of an entry in an earlier source".to_string()
+                },
+            ));
+        } else {
+            seen.insert((&entry.phrase, &entry.syllables), entry);
+        }
+        for syl in &entry.syllables {
+            if !syl.is_valid() {
+                let suggestion = syl
+                    .nearest_valid()
+                    .map(|valid| format!(", did you mean {}?", valid))
+                    .unwrap_or_default();
+                diagnostics.push(Diagnostic::warning(
+                    entry,
+                    "invalid-syllable",
+                    format!("{} is not a Mandarin syllable{}", syl, suggestion),
+                ));
+            }
+        }
+        let len = entry.phrase.chars().count();
+        if len != entry.syllables.len() {
+            diagnostics.push(Diagnostic::warning(
+                entry,
+                "length-mismatch",
+                format!(
+                    "{} has {} characters but {} syllables",
+                    entry.phrase,
+                    len,
+                    entry.syllables.len()
+                ),
+            ));
+        } else if len > 1 && !readings.is_empty() {
+            for (ch, syl) in entry.phrase.chars().zip(&entry.syllables) {
+                if !readings.contains(&(ch, *syl)) {
+                    diagnostics.push(Diagnostic::warning(
+                        entry,
+                        "missing-reading",
+                        format!("{} is not read {} in word.src", ch, syl),
+                    ));
+                }
+            }
+        }
+        if !is_nfc(&entry.phrase) {
+            // The normalized text usually looks the same, so show the code
+            // points too
+            let nfc: String = entry.phrase.nfc().collect();
+            let code_points: Vec<_> = nfc
+                .chars()
+                .map(|ch| format!("U+{:04X}", u32::from(ch)))
+                .collect();
+            diagnostics.push(Diagnostic::warning(
+                entry,
+                "not-nfc",
+                format!(
+                    "{} is not in NFC, use {} ({})",
+                    entry.phrase,
+                    nfc,
+                    code_points.join(" ")
+                ),
+            ));
+        }
+    }
+
+    // Frequencies of single characters are usually ignored, so only phrases
+    // are compared.
+    let phrases: Vec<&Entry> = entries
+        .iter()
+        .filter(|entry| entry.phrase.chars().count() > 1)
+        .collect();
+    let log_freq = |entry: &Entry| f64::from(entry.freq).ln_1p();
+    let n = phrases.len() as f64;
+    let mean = phrases.iter().map(|entry| log_freq(entry)).sum::<f64>() / n;
+    let variance = phrases
+        .iter()
+        .map(|entry| (log_freq(entry) - mean).powi(2))
+        .sum::<f64>()
+        / n;
+    let sd = variance.sqrt();
+    if phrases.len() > 1 && sd > 0.0 {
+        for entry in phrases {
+            let z = (log_freq(entry) - mean) / sd;
+            if z.abs() > outlier {
+                diagnostics.push(Diagnostic::warning(
+                    entry,
+                    "frequency-outlier",
+                    format!(
+                        "frequency {} is {:.1} standard deviations from the mean",
+                        entry.freq, z
+                    ),
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Parses the source into entries, collecting the lines that fail to parse.
+fn read_source(
+    file: usize,
+    path: &Path,
+    csv: bool,
+    entries: &mut Vec<Entry>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<()> {
+    let reader =
+        BufReader::new(File::open(path).with_context(|| format!("opening {}", path.display()))?);
+    let delimiter = if csv { ',' } else { ' ' };
+    for (line_num, line) in reader.lines().enumerate() {
+        if csv && line_num == 0 {
+            continue;
+        }
+        let line = line?;
+        match parse_line(line_num, delimiter, &line, true) {
+            Ok((syllables, phrase, freq)) => entries.push(Entry {
+                file,
+                line: line_num + 1,
+                phrase: phrase.to_string(),
+                freq,
+                syllables,
+            }),
+            Err(error) => diagnostics.push(Diagnostic {
+                file,
+                line: line_num + 1,
+                severity: "error",
+                code: "parse-error",
+                message: error
+                    .chain()
+                    .skip(1)
+                    .map(|cause| cause.to_string())
+                    .collect::<Vec<_>>()
+                    .join(": "),
+            }),
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn run(args: flags::Lint) -> Result<ExitCode> {
+    let mut entries = vec![];
+    let mut diagnostics = vec![];
+    for (file, path) in args.sources.iter().enumerate() {
+        read_source(file, path, args.csv, &mut entries, &mut diagnostics)?;
+    }
+    let readings: HashSet<(char, Syllable)> = match &args.word {
+        Some(path) => {
+            let mut words = vec![];
+            read_source(usize::MAX, path, args.csv, &mut words, &mut vec![])?;
+            words
+                .iter()
+                .filter_map(|entry| Some((single_char(&entry.phrase)?, *entry.syllables.first()?)))
+                .collect()
+        }
+        None => entries
+            .iter()
+            .filter(|entry| entry.syllables.len() == 1)
+            .filter_map(|entry| Some((single_char(&entry.phrase)?, entry.syllables[0])))
+            .collect(),
+    };
+    diagnostics.extend(check(&entries, &readings, args.outlier));
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.file, diagnostic.line));
+
+    let names: Vec<String> = args
+        .sources
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    match args.format {
+        LintFormat::Gnu => {
+            for d in &diagnostics {
+                println!(
+                    "{}:{}: {}: {} [{}]",
+                    names[d.file], d.line, d.severity, d.message, d.code
+                );
+            }
+        }
+        LintFormat::Github => {
+            for d in &diagnostics {
+                println!(
+                    "::{} file={},line={},title={}::{}",
+                    d.severity, names[d.file], d.line, d.code, d.message
+                );
+            }
+        }
+        LintFormat::Json => print_json(&names, &diagnostics),
+    }
+    if diagnostics
+        .iter()
+        .any(|d| d.severity == "error" || args.deny_warnings)
+    {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    let ch = chars.next()?;
+    chars.next().is_none().then_some(ch)
+}
+
+fn print_json(names: &[String], diagnostics: &[Diagnostic]) {
+    let diagnostics: Vec<Value> = diagnostics
+        .iter()
+        .map(|d| {
+            json!({
+                "path": names[d.file],
+                "line": d.line,
+                "severity": d.severity,
+                "code": d.code,
+                "message": d.message,
+            })
+        })
+        .collect();
+    println!("{:#}", Value::from(diagnostics));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use chewing::{
+        syl,
+        zhuyin::{Bopomofo::*, Syllable},
+    };
+
+    use super::{Entry, check};
+
+    fn entry(line: usize, phrase: &str, freq: u32, syllables: Vec<Syllable>) -> Entry {
+        Entry {
+            file: 0,
+            line,
+            phrase: phrase.to_string(),
+            freq,
+            syllables,
+        }
+    }
+
+    #[test]
+    fn semantic_checks() {
+        let entries = [
+            entry(1, "測", 0, vec![syl![C, E, TONE4]]),
+            entry(2, "試", 0, vec![syl![SH, TONE4]]),
+            entry(3, "測試", 100, vec![syl![C, E, TONE4], syl![SH, TONE4]]),
+            entry(4, "測試", 100, vec![syl![C, E, TONE4], syl![SH, TONE4]]),
+            entry(5, "測試", 1, vec![syl![C, E, TONE4], syl![S, TONE4]]),
+            entry(6, "試", 0, vec![syl![B, IU]]),
+            entry(7, "測試卷", 1, vec![syl![C, E, TONE4], syl![SH, TONE4]]),
+        ];
+        let readings: HashSet<_> = [('測', syl![C, E, TONE4]), ('試', syl![SH, TONE4])].into();
+        let codes: Vec<_> = check(&entries, &readings, 4.0)
+            .into_iter()
+            .map(|d| (d.line, d.code))
+            .collect();
+        assert_eq!(
+            vec![
+                (4, "duplicate"),
+                (5, "missing-reading"),
+                (6, "invalid-syllable"),
+                (7, "length-mismatch"),
+            ],
+            codes
+        );
+    }
+
+    #[test]
+    fn frequency_outliers() {
+        let mut entries: Vec<_> = (0..20)
+            .map(|i| {
+                entry(
+                    i + 1,
+                    "測試",
+                    100 + i as u32,
+                    vec![syl![C, E, TONE4], syl![SH, TONE4]],
+                )
+            })
+            .collect();
+        entries.push(entry(
+            21,
+            "是書",
+            1_000_000,
+            vec![syl![SH, TONE4], syl![SH, U]],
+        ));
+        let outliers: Vec<_> = check(&entries, &HashSet::new(), 4.0)
+            .into_iter()
+            .filter(|d| d.code == "frequency-outlier")
+            .map(|d| d.line)
+            .collect();
+        assert_eq!(vec![21], outliers);
+    }
+
+    #[test]
+    fn detect_non_nfc() {
+        let entries = [
+            entry(1, "測", 0, vec![syl![C, E, TONE4]]),
+            entry(2, "\u{F900}", 0, vec![syl![G, AI, TONE3]]),
+            entry(3, "\u{FA0E}", 0, vec![syl![G, AI, TONE3]]),
+            entry(4, "e\u{0301}", 0, vec![syl![E]]),
+        ];
+        let diagnostics: Vec<_> = check(&entries, &HashSet::new(), 4.0)
+            .into_iter()
+            .filter(|d| d.code == "not-nfc")
+            .map(|d| (d.line, d.message))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    2,
+                    "\u{F900} is not in NFC, use \u{8C48} (U+8C48)".to_string()
+                ),
+                (
+                    4,
+                    "e\u{0301} is not in NFC, use \u{E9} (U+00E9)".to_string()
+                ),
+            ],
+            diagnostics
+        );
+    }
+}
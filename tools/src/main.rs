@@ -1,12 +1,29 @@
+use std::process::ExitCode;
+
 use anyhow::Result;
 use clap::Parser;
 
+mod convert;
+mod diff;
 mod dump;
+mod eval;
 mod flags;
+mod fsck;
+mod import;
 mod info;
 mod init_database;
+mod learn;
+mod lint;
+mod merge;
+mod proofread;
+mod query;
+mod sign;
+mod stats;
+mod train;
+mod user;
+mod verify;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     #[cfg(feature = "mangen")]
     {
         use clap::CommandFactory;
@@ -15,7 +32,7 @@ fn main() -> Result<()> {
                 flags::ChewingCli::command(),
                 std::env::args().nth(1).unwrap(),
             )?;
-            return Ok(());
+            return Ok(ExitCode::SUCCESS);
         }
     }
     let cli = flags::ChewingCli::parse();
@@ -23,6 +40,21 @@ fn main() -> Result<()> {
         flags::ChewingCliCmd::InitDatabase(args) => init_database::run(args)?,
         flags::ChewingCliCmd::Info(args) => info::run(args)?,
         flags::ChewingCliCmd::Dump(args) => dump::run(args)?,
+        flags::ChewingCliCmd::Proofread(args) => proofread::run(args)?,
+        flags::ChewingCliCmd::Convert(args) => convert::run(args)?,
+        flags::ChewingCliCmd::Eval(args) => eval::run(args)?,
+        flags::ChewingCliCmd::Train(args) => train::run(args)?,
+        flags::ChewingCliCmd::Learn(args) => learn::run(args)?,
+        flags::ChewingCliCmd::Query(args) => query::run(args)?,
+        flags::ChewingCliCmd::User(args) => user::run(args)?,
+        flags::ChewingCliCmd::Import(args) => import::run(args)?,
+        flags::ChewingCliCmd::Diff(args) => diff::run(args)?,
+        flags::ChewingCliCmd::Merge(args) => merge::run(args)?,
+        flags::ChewingCliCmd::Lint(args) => return lint::run(args),
+        flags::ChewingCliCmd::Fsck(args) => return fsck::run(args),
+        flags::ChewingCliCmd::Stats(args) => stats::run(args)?,
+        flags::ChewingCliCmd::Sign(args) => sign::run(args)?,
+        flags::ChewingCliCmd::Verify(args) => return verify::run(args),
     }
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
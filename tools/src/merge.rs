@@ -0,0 +1,198 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, Result, anyhow};
+use chewing::{
+    dictionary::{
+        Dictionary, DictionaryBuilder, DictionaryInfo, Phrase, SqliteDictionary,
+        SqliteDictionaryBuilder, Trie, TrieBuilder,
+    },
+    zhuyin::Syllable,
+};
+
+use crate::flags::{self, DbType, MergePolicy};
+
+/// Entries of a dictionary keyed by syllables and phrase.
+pub(crate) type EntryMap = BTreeMap<(Vec<Syllable>, String), Phrase>;
+
+/// Opens a dictionary file without modifying it.
+pub(crate) fn open_read_only(path: &Path) -> Result<Box<dyn Dictionary>> {
+    let ext = path
+        .extension()
+        .ok_or(anyhow!("Unknown dictionary format: {}", path.display()))?;
+    let dict: Box<dyn Dictionary> = if ext.eq_ignore_ascii_case("sqlite3") {
+        Box::new(SqliteDictionary::open_read_only(path)?)
+    } else {
+        Box::new(Trie::open(path).with_context(|| format!("opening {}", path.display()))?)
+    };
+    Ok(dict)
+}
+
+/// Returns the entries of the dictionary. Duplicated entries keep the one
+/// with the highest frequency.
+pub(crate) fn entry_map(dict: &dyn Dictionary) -> EntryMap {
+    let mut entries = EntryMap::new();
+    for (syllables, phrase) in dict.entries() {
+        let key = (syllables, phrase.as_str().to_string());
+        match entries.get(&key) {
+            Some(old) if *old >= phrase => {}
+            _ => {
+                entries.insert(key, phrase);
+            }
+        }
+    }
+    entries
+}
+
+/// Combines the entry of a preferred dictionary with the entry of a later
+/// dictionary.
+fn resolve(policy: MergePolicy, old: Phrase, new: Phrase) -> Phrase {
+    match policy {
+        MergePolicy::Max => {
+            if new > old {
+                new
+            } else {
+                old
+            }
+        }
+        MergePolicy::Sum => {
+            let freq = old.freq().saturating_add(new.freq());
+            let last_used = old.last_used().max(new.last_used());
+            let phrase = Phrase::new(old.as_str(), freq);
+            match last_used {
+                Some(last_used) => phrase.with_time(last_used),
+                None => phrase,
+            }
+        }
+        MergePolicy::PreferLeft => old,
+        MergePolicy::Newest => {
+            if new.last_used() > old.last_used() {
+                new
+            } else {
+                old
+            }
+        }
+    }
+}
+
+/// Joins the distinct non-empty values in order.
+fn join_distinct<'a>(values: impl Iterator<Item = &'a str>, sep: &str) -> String {
+    let mut distinct: Vec<&str> = vec![];
+    for value in values {
+        if !value.is_empty() && !distinct.contains(&value) {
+            distinct.push(value);
+        }
+    }
+    distinct.join(sep)
+}
+
+/// Returns the info of the merged dictionary.
+///
+/// The name and version come from the first dictionary. The copyright
+/// holders of all dictionaries are kept, and differing licenses are combined
+/// into an SPDX `AND` expression.
+fn merge_info(infos: &[DictionaryInfo]) -> DictionaryInfo {
+    let first = infos.first().cloned().unwrap_or_default();
+    DictionaryInfo {
+        copyright: join_distinct(infos.iter().map(|info| info.copyright.as_str()), "; "),
+        license: join_distinct(infos.iter().map(|info| info.license.as_str()), " AND "),
+        software: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        ..first
+    }
+}
+
+pub(crate) fn run(args: flags::Merge) -> Result<()> {
+    let mut infos = vec![];
+    let mut merged = EntryMap::new();
+    for path in &args.dict {
+        let dict = open_read_only(path)?;
+        infos.push(dict.about());
+        for (key, phrase) in entry_map(dict.as_ref()) {
+            let phrase = match merged.remove(&key) {
+                Some(old) => resolve(args.policy, old, phrase),
+                None => phrase,
+            };
+            merged.insert(key, phrase);
+        }
+    }
+
+    let mut info = merge_info(&infos);
+    if let Some(name) = args.name {
+        info.name = name;
+    }
+    if let Some(copyright) = args.copyright {
+        info.copyright = copyright;
+    }
+    if let Some(license) = args.license {
+        info.license = license;
+    }
+    if let Some(version) = args.version {
+        info.version = version;
+    }
+    let mut builder: Box<dyn DictionaryBuilder> = match args.db_type {
+        DbType::Sqlite => Box::new(SqliteDictionaryBuilder::new()),
+        DbType::Trie => Box::new(TrieBuilder::new()),
+    };
+    builder.set_info(info)?;
+    for ((syllables, _), phrase) in &merged {
+        builder.insert(syllables, phrase.clone())?;
+    }
+    let path: &Path = args.output.as_ref();
+    if path.exists() {
+        fs::remove_file(path).context("unable to overwrite output")?;
+    }
+    builder.build(path)?;
+    eprintln!(
+        "Merged {} dictionaries into {} phrases",
+        args.dict.len(),
+        merged.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chewing::dictionary::{DictionaryInfo, Phrase};
+
+    use super::{merge_info, resolve};
+    use crate::flags::MergePolicy;
+
+    #[test]
+    fn resolve_conflicts() {
+        let old = Phrase::new("測試", 100).with_time(1);
+        let new = Phrase::new("測試", 50).with_time(2);
+        assert_eq!(old, resolve(MergePolicy::Max, old.clone(), new.clone()));
+        assert_eq!(
+            Phrase::new("測試", 150).with_time(2),
+            resolve(MergePolicy::Sum, old.clone(), new.clone())
+        );
+        assert_eq!(
+            old,
+            resolve(MergePolicy::PreferLeft, old.clone(), new.clone())
+        );
+        assert_eq!(new, resolve(MergePolicy::Newest, old.clone(), new.clone()));
+    }
+
+    #[test]
+    fn combine_info() {
+        let info = merge_info(&[
+            DictionaryInfo {
+                name: "團隊詞庫".to_string(),
+                copyright: "Team".to_string(),
+                license: "MIT".to_string(),
+                version: "1.0.0".to_string(),
+                software: "".to_string(),
+            },
+            DictionaryInfo {
+                name: "我的詞庫".to_string(),
+                copyright: "Me".to_string(),
+                license: "MIT".to_string(),
+                version: "0.0.0".to_string(),
+                software: "".to_string(),
+            },
+        ]);
+        assert_eq!("團隊詞庫", info.name);
+        assert_eq!("Team; Me", info.copyright);
+        assert_eq!("MIT", info.license);
+        assert_eq!("1.0.0", info.version);
+    }
+}
@@ -0,0 +1,40 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, stdin},
+};
+
+use anyhow::Result;
+use chewing::{
+    conversion::Proofreader,
+    dictionary::{Layered, SystemDictionaryLoader, UserDictionaryLoader},
+};
+
+use crate::flags;
+
+pub(crate) fn run(args: flags::Proofread) -> Result<()> {
+    let dict = Layered::new(
+        SystemDictionaryLoader::new().load()?,
+        UserDictionaryLoader::new().load()?,
+    );
+    let proofreader = Proofreader::new(&dict).min_confidence(args.min_confidence);
+    let source: Box<dyn Read> = if args.file.as_os_str() == "-" {
+        Box::new(stdin())
+    } else {
+        Box::new(File::open(&args.file)?)
+    };
+    let name = args.file.display();
+    for (line_no, line) in BufReader::new(source).lines().enumerate() {
+        for suggestion in proofreader.check(&line?) {
+            println!(
+                "{}:{}:{}: {} -> {} ({:.2})",
+                name,
+                line_no + 1,
+                suggestion.range.start + 1,
+                suggestion.original,
+                suggestion.suggestion,
+                suggestion.confidence
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use anyhow::{Result, bail};
+use chewing::{
+    dictionary::{Dictionary, LookupStrategy, Phrase, SystemDictionaryLoader, TrieBuf},
+    path::userphrase_path,
+    zhuyin::{Bopomofo, Syllable},
+};
+use serde_json::{Value, json};
+
+use crate::{convert::syllable_format, flags, merge::open_read_only};
+
+/// A phrase and reading found in the dictionaries.
+struct Match {
+    phrase: Phrase,
+    syllables: Vec<Syllable>,
+    source: String,
+}
+
+pub(crate) fn run(args: flags::Query) -> Result<()> {
+    let all = !args.system && !args.extra && !args.user && args.path.is_empty();
+    let loader = SystemDictionaryLoader::new();
+    let mut dictionaries: Vec<Box<dyn Dictionary>> = vec![];
+    if all || args.system {
+        dictionaries.extend(loader.load_system()?);
+    }
+    if all || args.extra {
+        dictionaries.extend(loader.load_extra()?);
+    }
+    for path in &args.path {
+        dictionaries.push(open_read_only(path)?);
+    }
+    if all || args.user {
+        // A missing user dictionary is not created by a query
+        match userphrase_path().filter(|path| path.exists()) {
+            Some(path) => dictionaries.push(open_read_only(&path)?),
+            None if args.user => bail!("user dictionary does not exist"),
+            None => (),
+        }
+    }
+
+    let is_phrase = args.phrase
+        || args
+            .query
+            .chars()
+            .any(|ch| !ch.is_ascii() && Bopomofo::try_from(ch).is_err());
+    let matches = if is_phrase {
+        lookup_readings(&dictionaries, &args.query)
+    } else {
+        if args.format == flags::InputFormat::Keys {
+            bail!("query takes syllables as bopomofo or pinyin");
+        }
+        let format = syllable_format(args.format, "")?;
+        // Bopomofo and pinyin are parsed without the dictionaries
+        let syllables = format.parse(&TrieBuf::new_in_memory(), &args.query)?;
+        let strategy = match args.strategy {
+            flags::Strategy::Standard => LookupStrategy::Standard,
+            flags::Strategy::FuzzyPartialPrefix => LookupStrategy::FuzzyPartialPrefix,
+        };
+        lookup_phrases(&dictionaries, &syllables, strategy)
+    };
+    if args.json {
+        print_json(&matches);
+    } else {
+        for m in &matches {
+            println!(
+                "{}\t{}\t{}\t{}",
+                m.phrase.as_str(),
+                reading(&m.syllables),
+                m.phrase.freq(),
+                m.source
+            );
+        }
+    }
+    Ok(())
+}
+
+fn source(dict: &dyn Dictionary) -> String {
+    dict.path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| dict.about().name)
+}
+
+pub(crate) fn reading(syllables: &[Syllable]) -> String {
+    syllables
+        .iter()
+        .map(|syl| syl.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Adds the match like `Layered` does: the first appearance keeps its
+/// place and the highest frequency wins.
+fn merge(matches: &mut Vec<Match>, new: Match, same: impl Fn(&Match, &Match) -> bool) {
+    match matches.iter_mut().find(|m| same(m, &new)) {
+        Some(m) => {
+            if new.phrase > m.phrase {
+                *m = new;
+            }
+        }
+        None => matches.push(new),
+    }
+}
+
+fn lookup_phrases(
+    dictionaries: &[Box<dyn Dictionary>],
+    syllables: &[Syllable],
+    strategy: LookupStrategy,
+) -> Vec<Match> {
+    let mut matches = vec![];
+    for dict in dictionaries {
+        let found: Vec<(Vec<Syllable>, Phrase)> = match strategy {
+            LookupStrategy::Standard => dict
+                .lookup_all_phrases(&syllables, strategy)
+                .into_iter()
+                .map(|phrase| (syllables.to_vec(), phrase))
+                .collect(),
+            // The phrases of a fuzzy lookup may have other readings than the
+            // query
+            LookupStrategy::FuzzyPartialPrefix => dict
+                .entries()
+                .filter(|(entry, _)| {
+                    entry.len() == syllables.len()
+                        && entry.iter().zip(syllables).all(|(a, b)| a.starts_with(*b))
+                })
+                .collect(),
+        };
+        for (syllables, phrase) in found {
+            let new = Match {
+                phrase,
+                syllables,
+                source: source(dict.as_ref()),
+            };
+            merge(&mut matches, new, |m, new| {
+                m.phrase.as_str() == new.phrase.as_str() && m.syllables == new.syllables
+            });
+        }
+    }
+    matches
+}
+
+fn lookup_readings(dictionaries: &[Box<dyn Dictionary>], text: &str) -> Vec<Match> {
+    let mut matches = vec![];
+    for dict in dictionaries {
+        for (syllables, phrase) in dict.entries() {
+            if phrase.as_str() != text {
+                continue;
+            }
+            let new = Match {
+                phrase,
+                syllables,
+                source: source(dict.as_ref()),
+            };
+            merge(&mut matches, new, |m, new| m.syllables == new.syllables);
+        }
+    }
+    matches
+}
+
+fn print_json(matches: &[Match]) {
+    let matches: Vec<Value> = matches
+        .iter()
+        .map(|m| {
+            json!({
+                "phrase": m.phrase.as_str(),
+                "syllables": reading(&m.syllables),
+                "freq": m.phrase.freq(),
+                "source": m.source,
+            })
+        })
+        .collect();
+    println!("{:#}", Value::from(matches));
+}
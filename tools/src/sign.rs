@@ -0,0 +1,93 @@
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use chewing::dictionary::signature_path;
+use minisign::{SecretKey, SecretKeyBox};
+
+use crate::flags;
+
+/// Decodes a minisign secret key file. The password is only asked for
+/// encrypted keys, if it is not given.
+fn decode_secret_key(text: &str, password: Option<String>) -> Result<SecretKey> {
+    let decode = |password| SecretKey::from_box(SecretKeyBox::from_string(text)?, password);
+    // Unencrypted keys pass the checksum with an empty password
+    if let Ok(key) = decode(Some(String::new())) {
+        return Ok(key);
+    }
+    decode(password).context("wrong password or corrupted secret key")
+}
+
+/// Returns the content of a minisign signature file for the data.
+fn sign(key: &SecretKey, data: &[u8], trusted_comment: &str) -> Result<String> {
+    let signature = minisign::sign(
+        None,
+        key,
+        data,
+        Some(trusted_comment),
+        Some("signature from chewing-cli secret key"),
+    )?;
+    Ok(signature.into_string())
+}
+
+pub(crate) fn run(args: flags::Sign) -> Result<()> {
+    let text = fs::read_to_string(&args.secret_key)
+        .with_context(|| format!("reading {}", args.secret_key.display()))?;
+    let key = decode_secret_key(&text, None)?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    for path in &args.files {
+        let data = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        let trusted_comment = match &args.trusted_comment {
+            Some(comment) => comment.clone(),
+            None => format!(
+                "timestamp:{}\tfile:{}\thashed",
+                timestamp,
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+        };
+        let sig_path = signature_path(path);
+        fs::write(&sig_path, sign(&key, &data, &trusted_comment)?)
+            .with_context(|| format!("writing {}", sig_path.display()))?;
+        eprintln!("Signed {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chewing::dictionary::TrustedKeys;
+    use minisign::KeyPair;
+
+    use super::{decode_secret_key, sign};
+
+    /// Signs with the encoded secret key and verifies with the public key.
+    fn sign_and_verify(key_pair: &KeyPair, password: &str) {
+        let encoded = key_pair.sk.to_box(None).unwrap().into_string();
+        let key = decode_secret_key(&encoded, Some(password.to_string())).unwrap();
+        let signature = sign(&key, b"dictionary", "file:tsi.dat").unwrap();
+
+        let mut keys = TrustedKeys::new();
+        keys.add(&key_pair.pk.to_base64()).unwrap();
+        assert_eq!(
+            "file:tsi.dat",
+            keys.verify_bytes(b"dictionary", &signature).unwrap()
+        );
+        assert!(keys.verify_bytes(b"tampered", &signature).is_err());
+    }
+
+    #[test]
+    fn sign_with_unencrypted_key() {
+        let key_pair = KeyPair::generate_encrypted_keypair(Some(String::new())).unwrap();
+        sign_and_verify(&key_pair, "unused");
+    }
+
+    #[test]
+    fn sign_with_encrypted_key() {
+        let key_pair = KeyPair::generate_encrypted_keypair(Some("secret".to_string())).unwrap();
+        let encoded = key_pair.sk.to_box(None).unwrap().into_string();
+        assert!(decode_secret_key(&encoded, Some("wrong".to_string())).is_err());
+        sign_and_verify(&key_pair, "secret");
+    }
+}
@@ -0,0 +1,192 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use chewing::{
+    dictionary::{
+        Dictionary, DictionaryStatistics, SqliteDictionary, Trie, TrieSectionSizes, statistics,
+    },
+    zhuyin::valid_syllables,
+};
+use serde_json::{Map, Value, json};
+
+use crate::flags;
+
+/// The analysis of a dictionary file.
+struct Report {
+    path: String,
+    name: String,
+    file_size: u64,
+    /// Only trie dictionaries have sections
+    sections: Option<TrieSectionSizes>,
+    stats: DictionaryStatistics,
+}
+
+fn analyze(path: &Path) -> Result<Report> {
+    let ext = path.extension().ok_or(anyhow::anyhow!(
+        "Unknown dictionary format: {}",
+        path.display()
+    ))?;
+    let (dict, sections): (Box<dyn Dictionary>, _) = if ext.eq_ignore_ascii_case("sqlite3") {
+        (Box::new(SqliteDictionary::open_read_only(path)?), None)
+    } else {
+        let trie = Trie::open(path).with_context(|| format!("opening {}", path.display()))?;
+        let sections = trie.section_sizes();
+        (Box::new(trie), Some(sections))
+    };
+    Ok(Report {
+        path: path.display().to_string(),
+        name: dict.about().name,
+        file_size: fs::metadata(path)?.len(),
+        sections,
+        stats: statistics(dict.as_ref()),
+    })
+}
+
+/// Returns the lowest and the highest frequency of the histogram bucket.
+fn bucket_range(bucket: usize) -> (u64, u64) {
+    match bucket {
+        0 => (0, 0),
+        _ => (
+            10u64.pow(bucket as u32 - 1),
+            (10u64.pow(bucket as u32) - 1).min(u32::MAX.into()),
+        ),
+    }
+}
+
+fn percent(part: usize, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+pub(crate) fn run(args: flags::Stats) -> Result<()> {
+    let reports = args
+        .paths
+        .iter()
+        .map(|path| analyze(path))
+        .collect::<Result<Vec<_>>>()?;
+    if args.json {
+        print_json_reports(&reports);
+    } else {
+        print_reports(&reports);
+    }
+    Ok(())
+}
+
+fn print_reports(reports: &[Report]) {
+    for (i, report) in reports.iter().enumerate() {
+        let stats = &report.stats;
+        if i > 0 {
+            println!();
+        }
+        println!("== {} ==", report.path);
+        println!("Name                 : {}", report.name);
+        println!("Phrase count         : {}", stats.phrase_count);
+        println!("Node count           : {}", stats.node_count);
+        println!("Leaf count           : {}", stats.leaf_count);
+        println!("Average children     : {:.2}", stats.avg_children);
+        println!("Syllable count       : {}", stats.syllable_count);
+        println!(
+            "Syllable coverage    : {}/{} ({:.1}%)",
+            stats.covered_syllables,
+            valid_syllables().len(),
+            stats.syllable_coverage() * 100.0
+        );
+        println!("Max frequency        : {}", stats.max_frequency);
+        println!("Phrase lengths:");
+        for (len, count) in &stats.phrase_lengths {
+            println!("  {:<18} : {}", len, count);
+        }
+        println!("Frequencies:");
+        let last = stats.frequencies.iter().rposition(|&count| count > 0);
+        for (bucket, count) in stats.frequencies.iter().enumerate() {
+            if last.is_none_or(|last| bucket > last) {
+                break;
+            }
+            let range = match bucket_range(bucket) {
+                (min, max) if min == max => min.to_string(),
+                (min, max) => format!("{}-{}", min, max),
+            };
+            println!("  {:<18} : {}", range, count);
+        }
+        println!("File size            : {}", report.file_size);
+        if let Some(sections) = &report.sections {
+            for (name, size) in [
+                ("header", sections.header),
+                ("info", sections.info),
+                ("index", sections.index),
+                ("phrases", sections.phrases),
+            ] {
+                println!(
+                    "  {:<18} : {} ({:.1}%)",
+                    name,
+                    size,
+                    percent(size, report.file_size)
+                );
+            }
+        }
+    }
+}
+
+fn print_json_reports(reports: &[Report]) {
+    let reports: Vec<Value> = reports
+        .iter()
+        .map(|report| {
+            let stats = &report.stats;
+            let phrase_lengths: Map<String, Value> = stats
+                .phrase_lengths
+                .iter()
+                .map(|(len, count)| (len.to_string(), Value::from(*count)))
+                .collect();
+            let frequencies: Vec<Value> = stats
+                .frequencies
+                .iter()
+                .enumerate()
+                .map(|(bucket, count)| {
+                    let (min, max) = bucket_range(bucket);
+                    json!({"min": min, "max": max, "count": count})
+                })
+                .collect();
+            let sections = report.sections.as_ref().map(|sections| {
+                json!({
+                    "header": sections.header,
+                    "info": sections.info,
+                    "index": sections.index,
+                    "phrases": sections.phrases,
+                })
+            });
+            json!({
+                "path": report.path,
+                "name": report.name,
+                "phrase_count": stats.phrase_count,
+                "node_count": stats.node_count,
+                "leaf_count": stats.leaf_count,
+                "avg_children": stats.avg_children,
+                "syllable_count": stats.syllable_count,
+                "covered_syllables": stats.covered_syllables,
+                "valid_syllables": valid_syllables().len(),
+                "max_frequency": stats.max_frequency,
+                "phrase_lengths": phrase_lengths,
+                "frequencies": frequencies,
+                "file_size": report.file_size,
+                "sections": sections,
+            })
+        })
+        .collect();
+    println!("{:#}", Value::from(reports));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bucket_range;
+
+    #[test]
+    fn frequency_bucket_ranges() {
+        assert_eq!((0, 0), bucket_range(0));
+        assert_eq!((1, 9), bucket_range(1));
+        assert_eq!((100, 999), bucket_range(3));
+        assert_eq!((1_000_000_000, u32::MAX.into()), bucket_range(10));
+    }
+}
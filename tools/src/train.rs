@@ -0,0 +1,333 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufReader, Read, stdin},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use chewing::{
+    conversion::{
+        Annotation, Annotator, ChewingEngine, Composition, ConversionEngine, Interval, MAX_RUN_LEN,
+    },
+    dictionary::{
+        Dictionary, DictionaryBuilder, DictionaryInfo, Layered, SqliteDictionaryBuilder,
+        SystemDictionaryLoader, TrieBuf, TrieBuilder,
+    },
+};
+
+use crate::{
+    flags::{self, DbType, Segmentation},
+    merge::{EntryMap, entry_map, open_read_only},
+};
+
+/// Parameters of the frequency estimation.
+struct Estimator {
+    smoothing: f64,
+    weight: f64,
+    /// Total of the existing frequencies of the observed phrases
+    old_total: f64,
+    /// Total of the smoothed counts of the observed phrases
+    count_total: f64,
+}
+
+impl Estimator {
+    /// Returns the frequency from the existing frequency and the corpus
+    /// count.
+    ///
+    /// The smoothed counts are scaled to the total of the existing
+    /// frequencies, then mixed with the existing frequency by weight.
+    /// Phrases that were not observed keep their frequency.
+    fn estimate(&self, old: u32, count: f64) -> u32 {
+        if count <= 0.0 {
+            return old;
+        }
+        let smoothed = count + self.smoothing;
+        let trained = if self.old_total > 0.0 && self.count_total > 0.0 {
+            smoothed / self.count_total * self.old_total
+        } else {
+            smoothed
+        };
+        let freq = self.weight * trained + (1.0 - self.weight) * f64::from(old);
+        freq.round().clamp(0.0, f64::from(u32::MAX)) as u32
+    }
+}
+
+/// A dictionary to train and its entries.
+struct Source {
+    path: PathBuf,
+    about: DictionaryInfo,
+    entries: EntryMap,
+}
+
+pub(crate) fn run(args: flags::Train) -> Result<()> {
+    if !(0.0..=1.0).contains(&args.weight) {
+        bail!("weight must be between 0 and 1");
+    }
+    if args.smoothing < 0.0 {
+        bail!("smoothing must not be negative");
+    }
+    let dictionaries = if args.dict.is_empty() {
+        SystemDictionaryLoader::new().load()?
+    } else {
+        args.dict
+            .iter()
+            .map(|path| open_read_only(path))
+            .collect::<Result<_>>()?
+    };
+    let mut sources = vec![];
+    for dict in &dictionaries {
+        let path = dict
+            .path()
+            .ok_or(anyhow!("dictionary {} has no file", dict.about().name))?;
+        let file_name = path
+            .file_name()
+            .ok_or(anyhow!("invalid dictionary path {}", path.display()))?;
+        let extension = match args.db_type {
+            DbType::Sqlite => "sqlite3",
+            DbType::Trie => "dat",
+        };
+        let output = args.output.join(file_name).with_extension(extension);
+        if fs::canonicalize(&output).is_ok_and(|output| fs::canonicalize(path).ok() == Some(output))
+        {
+            bail!("output {} would overwrite its source", output.display());
+        }
+        sources.push(Source {
+            path: output,
+            about: dict.about(),
+            entries: entry_map(dict.as_ref()),
+        });
+    }
+
+    let dict = Layered::new(dictionaries, Box::new(TrieBuf::new_in_memory()));
+    let annotator = Annotator::new(&dict);
+    let engine = ChewingEngine::new();
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    for path in &args.corpus {
+        let source: Box<dyn Read> = if path.as_os_str() == "-" {
+            Box::new(stdin())
+        } else {
+            Box::new(File::open(path).with_context(|| format!("opening {}", path.display()))?)
+        };
+        for line in BufReader::new(source).lines() {
+            let line = line?;
+            let phrases = match args.segmentation {
+                Segmentation::LongestMatch => annotator
+                    .annotate_longest_match(&line)
+                    .into_iter()
+                    .filter(|annotation| annotation.has_reading())
+                    .map(|annotation| annotation.text)
+                    .collect(),
+                Segmentation::Engine => engine_segments(&engine, &dict, annotator.annotate(&line)),
+            };
+            for phrase in phrases {
+                *counts.entry(phrase).or_default() += 1;
+            }
+        }
+    }
+
+    fs::create_dir_all(&args.output)
+        .with_context(|| format!("creating {}", args.output.display()))?;
+    for source in sources {
+        train(&args, &source, &counts)
+            .with_context(|| format!("writing {}", source.path.display()))?;
+    }
+    eprintln!(
+        "Counted {} phrases, {} distinct",
+        counts.values().sum::<u64>(),
+        counts.len()
+    );
+    Ok(())
+}
+
+/// Splits the text into phrases the way the conversion engine would
+/// convert its readings.
+fn engine_segments(
+    engine: &ChewingEngine,
+    dict: &dyn Dictionary,
+    annotations: Vec<Annotation>,
+) -> Vec<String> {
+    let mut segments = vec![];
+    let mut comp = Composition::new();
+    let mut text = vec![];
+    let mut convert = |comp: &mut Composition, text: &mut Vec<char>| {
+        if comp.is_empty() {
+            return;
+        }
+        // Pin the characters of the text so only its segmentation is chosen
+        for (start, ch) in text.iter().enumerate() {
+            comp.push_selection(Interval {
+                start,
+                end: start + 1,
+                is_phrase: true,
+                str: ch.to_string().into(),
+            });
+        }
+        if let Some(intervals) = engine.convert(dict, comp).next() {
+            segments.extend(intervals.into_iter().map(|interval| interval.str.into()));
+        }
+        comp.clear();
+        text.clear();
+    };
+    for annotation in annotations {
+        let len = annotation.syllables.len();
+        if !annotation.has_reading() || comp.len() + len > MAX_RUN_LEN {
+            convert(&mut comp, &mut text);
+        }
+        for (ch, syl) in annotation.chars() {
+            if let Some(syl) = syl {
+                comp.push(syl.into());
+                text.push(ch);
+            }
+        }
+    }
+    convert(&mut comp, &mut text);
+    segments
+}
+
+/// Returns the count of each entry.
+///
+/// The count of a phrase is shared by its readings in proportion to their
+/// existing frequencies.
+fn attribute_counts(entries: &EntryMap, counts: &HashMap<String, u64>) -> Vec<f64> {
+    // The number of readings and their total frequency of each phrase
+    let mut readings: HashMap<&str, (usize, u64)> = HashMap::new();
+    for ((_, phrase), entry) in entries {
+        let (len, total) = readings.entry(phrase).or_default();
+        *len += 1;
+        *total += u64::from(entry.freq());
+    }
+    entries
+        .iter()
+        .map(|((_, phrase), entry)| {
+            let count = counts.get(phrase).copied().unwrap_or(0) as f64;
+            match readings[phrase.as_str()] {
+                (len, 0) => count / len as f64,
+                (_, total) => count * f64::from(entry.freq()) / total as f64,
+            }
+        })
+        .collect()
+}
+
+/// Writes the dictionary with the frequencies re-estimated from the counts.
+fn train(args: &flags::Train, source: &Source, counts: &HashMap<String, u64>) -> Result<()> {
+    let attributed = attribute_counts(&source.entries, counts);
+    let observed = || {
+        source
+            .entries
+            .values()
+            .zip(&attributed)
+            .filter(|(_, count)| **count > 0.0)
+    };
+    let estimator = Estimator {
+        smoothing: args.smoothing,
+        weight: args.weight,
+        old_total: observed().map(|(entry, _)| f64::from(entry.freq())).sum(),
+        count_total: observed().map(|(_, count)| count + args.smoothing).sum(),
+    };
+
+    let mut builder: Box<dyn DictionaryBuilder> = match args.db_type {
+        DbType::Sqlite => Box::new(SqliteDictionaryBuilder::new()),
+        DbType::Trie => Box::new(TrieBuilder::new()),
+    };
+    builder.set_info(DictionaryInfo {
+        software: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        ..source.about.clone()
+    })?;
+    for (((syllables, phrase), entry), count) in source.entries.iter().zip(attributed) {
+        let freq = estimator.estimate(entry.freq(), count);
+        builder.insert(syllables, (phrase.as_str(), freq).into())?;
+    }
+    let path: &Path = source.path.as_ref();
+    if path.exists() {
+        fs::remove_file(path).context("unable to overwrite output")?;
+    }
+    builder.build(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chewing::{
+        conversion::{Annotator, ChewingEngine},
+        dictionary::TrieBuf,
+        syl,
+        zhuyin::Bopomofo::*,
+    };
+
+    use super::{Estimator, attribute_counts, engine_segments};
+    use crate::merge::entry_map;
+
+    #[test]
+    fn estimate_frequencies() {
+        let estimator = Estimator {
+            smoothing: 1.0,
+            weight: 1.0,
+            old_total: 300.0,
+            count_total: 3.0,
+        };
+        assert_eq!(200, estimator.estimate(0, 1.0));
+        assert_eq!(150, estimator.estimate(200, 0.5));
+
+        let estimator = Estimator {
+            weight: 0.5,
+            ..estimator
+        };
+        assert_eq!(150, estimator.estimate(100, 1.0));
+        let estimator = Estimator {
+            weight: 0.0,
+            ..estimator
+        };
+        assert_eq!(100, estimator.estimate(100, 1.0));
+    }
+
+    #[test]
+    fn unobserved_phrases_keep_frequency() {
+        let estimator = Estimator {
+            smoothing: 1.0,
+            weight: 0.5,
+            old_total: 300.0,
+            count_total: 3.0,
+        };
+        assert_eq!(200, estimator.estimate(200, 0.0));
+    }
+
+    fn dict() -> TrieBuf {
+        TrieBuf::from([
+            (vec![syl![X, I, ENG, TONE2]], vec![("行", 300)]),
+            (vec![syl![H, ANG, TONE2]], vec![("行", 100)]),
+            (vec![syl![I, EN, TONE2]], vec![("銀", 100)]),
+            (
+                vec![syl![I, EN, TONE2], syl![H, ANG, TONE2]],
+                vec![("銀行", 100)],
+            ),
+        ])
+    }
+
+    #[test]
+    fn segment_with_engine() {
+        let dict = dict();
+        let annotator = Annotator::new(&dict);
+        let segments =
+            engine_segments(&ChewingEngine::new(), &dict, annotator.annotate("銀行，行"));
+        assert_eq!(vec!["銀行", "行"], segments);
+    }
+
+    #[test]
+    fn share_counts_between_readings() {
+        let entries = entry_map(&dict());
+        let counts = HashMap::from([("行".to_string(), 4)]);
+        let attributed: Vec<_> = entries
+            .keys()
+            .map(|(_, phrase)| phrase.as_str())
+            .zip(attribute_counts(&entries, &counts))
+            .collect();
+        assert_eq!(4, attributed.len());
+        assert!(attributed.contains(&("行", 3.0)));
+        assert!(attributed.contains(&("行", 1.0)));
+        assert!(attributed.contains(&("銀", 0.0)));
+        assert!(attributed.contains(&("銀行", 0.0)));
+    }
+}
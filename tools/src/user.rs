@@ -0,0 +1,181 @@
+use std::{fs, io, path::Path};
+
+use anyhow::{Context, Result, bail};
+use chewing::{
+    dictionary::{
+        Dictionary, DictionaryMut, LookupStrategy, Phrase, TrieBuf, UserDictionaryLoader,
+        UserDictionaryLock,
+    },
+    path::userphrase_path,
+    zhuyin::Syllable,
+};
+use serde_json::{Value, json};
+
+use crate::{
+    convert::syllable_format,
+    flags::{self, UserCmd},
+    query::reading,
+};
+
+/// Locks and loads the user dictionary, unless a running editor is using
+/// it.
+///
+/// With force the dictionary is loaded even if it is in use, and no lock is
+/// returned in that case.
+pub(crate) fn load_locked(
+    user_path: Option<&Path>,
+    force: bool,
+) -> Result<(Box<dyn Dictionary>, Option<UserDictionaryLock>)> {
+    let Some(path) = user_path.map(Path::to_path_buf).or_else(userphrase_path) else {
+        bail!("unable to find the user dictionary");
+    };
+    let lock = match path.parent() {
+        _ if path.ends_with(":memory:") => None,
+        Some(dir) => {
+            fs::create_dir_all(dir)?;
+            match UserDictionaryLock::acquire(&path) {
+                Ok(lock) => Some(lock),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock && force => {
+                    eprintln!("warning: {error}");
+                    None
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    bail!("{error}, close the editor first or use --force")
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+        None => None,
+    };
+    let dict = UserDictionaryLoader::new().userphrase_path(&path).load()?;
+    Ok((dict, lock))
+}
+
+pub(crate) fn run(args: flags::User) -> Result<()> {
+    let (mut dict, _lock) = load_locked(args.user_path.as_deref(), args.force)?;
+
+    match args.command {
+        UserCmd::List(args) => print_entries(dict.entries().collect(), args.json),
+        UserCmd::Search(args) => {
+            let entries = dict
+                .entries()
+                .filter(|(syllables, phrase)| {
+                    phrase.as_str().contains(&args.text) || reading(syllables).contains(&args.text)
+                })
+                .collect();
+            print_entries(entries, args.json);
+        }
+        UserCmd::Add(args) => {
+            let syllables = parse_reading(&args.entry)?;
+            let phrase = args.entry.phrase.as_str();
+            if find(dict.as_ref(), &syllables, phrase).is_some() {
+                bail!(
+                    "{} {} is already in the user dictionary",
+                    phrase,
+                    reading(&syllables)
+                );
+            }
+            let dict = dict_mut(dict.as_mut())?;
+            dict.add_phrase(&syllables, (phrase, args.freq).into())?;
+            dict.flush()?;
+        }
+        UserCmd::Remove(args) => {
+            let syllables = parse_reading(&args)?;
+            let phrase = args.phrase.as_str();
+            if find(dict.as_ref(), &syllables, phrase).is_none() {
+                bail!(
+                    "{} {} is not in the user dictionary",
+                    phrase,
+                    reading(&syllables)
+                );
+            }
+            let dict = dict_mut(dict.as_mut())?;
+            dict.remove_phrase(&syllables, phrase)?;
+            dict.flush()?;
+        }
+        UserCmd::SetFreq(args) => {
+            let syllables = parse_reading(&args.entry)?;
+            let phrase = args.entry.phrase.as_str();
+            let Some(old) = find(dict.as_ref(), &syllables, phrase) else {
+                bail!(
+                    "{} {} is not in the user dictionary",
+                    phrase,
+                    reading(&syllables)
+                );
+            };
+            let last_used = old.last_used().unwrap_or_default();
+            let dict = dict_mut(dict.as_mut())?;
+            // Updating alone cannot lower the frequency of every backend, so
+            // replace the entry instead.
+            dict.remove_phrase(&syllables, phrase)?;
+            if let Err(error) = dict.update_phrase(
+                &syllables,
+                Phrase::new(phrase, args.freq).with_time(last_used),
+                args.freq,
+                last_used,
+            ) {
+                // Put the old entry back instead of losing it
+                dict.update_phrase(&syllables, old.clone(), old.freq(), last_used)
+                    .context("unable to restore the old entry")?;
+                return Err(error.into());
+            }
+            dict.flush()?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn dict_mut(dict: &mut dyn Dictionary) -> Result<&mut dyn DictionaryMut> {
+    match dict.as_dict_mut() {
+        Some(dict) => Ok(dict),
+        None => bail!("the user dictionary cannot be modified"),
+    }
+}
+
+fn parse_reading(entry: &flags::UserEntry) -> Result<Vec<Syllable>> {
+    if entry.format == flags::InputFormat::Keys {
+        bail!("the reading must be written as bopomofo or pinyin");
+    }
+    let format = syllable_format(entry.format, "")?;
+    let syllables = format.parse(&TrieBuf::new_in_memory(), &entry.reading)?;
+    if syllables.len() != entry.phrase.chars().count() {
+        bail!(
+            "{} has {} characters but {} syllables",
+            entry.phrase,
+            entry.phrase.chars().count(),
+            syllables.len()
+        );
+    }
+    Ok(syllables)
+}
+
+pub(crate) fn find(dict: &dyn Dictionary, syllables: &[Syllable], phrase: &str) -> Option<Phrase> {
+    dict.lookup_all_phrases(&syllables, LookupStrategy::Standard)
+        .into_iter()
+        .find(|it| it.as_str() == phrase)
+}
+
+fn print_entries(entries: Vec<(Vec<Syllable>, Phrase)>, json: bool) {
+    if !json {
+        for (syllables, phrase) in &entries {
+            println!(
+                "{}\t{}\t{}",
+                phrase.as_str(),
+                reading(syllables),
+                phrase.freq()
+            );
+        }
+        return;
+    }
+    let entries: Vec<Value> = entries
+        .iter()
+        .map(|(syllables, phrase)| {
+            json!({
+                "phrase": phrase.as_str(),
+                "syllables": reading(syllables),
+                "freq": phrase.freq(),
+            })
+        })
+        .collect();
+    println!("{:#}", Value::from(entries));
+}
@@ -0,0 +1,42 @@
+use std::process::ExitCode;
+
+use anyhow::{Context, Result};
+use chewing::dictionary::TrustedKeys;
+
+use crate::flags;
+
+pub(crate) fn run(args: flags::Verify) -> Result<ExitCode> {
+    let mut keys = TrustedKeys::new();
+    for path in &args.public_key {
+        if path.is_dir() {
+            keys.add_dir(path)
+        } else {
+            keys.add_file(path)
+        }
+        .with_context(|| format!("loading public key {}", path.display()))?;
+    }
+    for key in &args.key {
+        keys.add(key)
+            .with_context(|| format!("loading public key {key}"))?;
+    }
+
+    let mut failed = 0;
+    for path in &args.files {
+        match keys.verify(path) {
+            Ok(comment) => println!("{}: OK ({})", path.display(), comment),
+            Err(error) => {
+                failed += 1;
+                println!("{}: FAILED ({})", path.display(), error);
+            }
+        }
+    }
+    if failed > 0 {
+        eprintln!(
+            "{} of {} files failed verification",
+            failed,
+            args.files.len()
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}